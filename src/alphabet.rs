@@ -0,0 +1,250 @@
+use crate::{Counter, Multiset, MultisetError};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A bounded, bidirectional mapping between items and multiset elements.
+///
+/// Items are assigned elements in the order they are first inserted, so the
+/// first distinct item is element `0`, the second is element `1` and so on,
+/// up to the capacity of the alphabet.
+///
+/// # Examples
+///
+/// ```
+/// use utote::Alphabet;
+///
+/// let mut alphabet = Alphabet::with_capacity(2);
+/// assert_eq!(alphabet.insert("foo"), Ok(0));
+/// assert_eq!(alphabet.insert("bar"), Ok(1));
+/// assert_eq!(alphabet.insert("foo"), Ok(0));
+/// assert!(alphabet.insert("baz").is_err());
+///
+/// assert_eq!(alphabet.elem_of(&"bar"), Some(1));
+/// assert_eq!(alphabet.item(0), Some(&"foo"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alphabet<T: Hash + Eq> {
+    indices: HashMap<T, usize>,
+    items: Vec<T>,
+    capacity: usize,
+}
+
+impl<T: Hash + Eq + Clone> Alphabet<T> {
+    /// Returns the element for `item`, assigning it the next free element if
+    /// it has not been seen before.
+    ///
+    /// # Errors
+    /// Returns [`MultisetError::AlphabetFull`] if `item` is new and the
+    /// alphabet already holds `capacity` items.
+    pub fn insert(&mut self, item: T) -> Result<usize, MultisetError> {
+        if let Some(&elem) = self.indices.get(&item) {
+            return Ok(elem);
+        }
+        if self.items.len() == self.capacity {
+            return Err(MultisetError::AlphabetFull {
+                capacity: self.capacity,
+            });
+        }
+        let elem = self.items.len();
+        self.indices.insert(item.clone(), elem);
+        self.items.push(item);
+        Ok(elem)
+    }
+}
+
+impl<T: Hash + Eq> Alphabet<T> {
+    /// Constructs an empty alphabet which can hold at most `capacity` items.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Alphabet {
+            indices: HashMap::with_capacity(capacity),
+            items: Vec::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Returns the element assigned to `item`, if any.
+    pub fn elem_of<Q>(&self, item: &Q) -> Option<usize>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.indices.get(item).copied()
+    }
+
+    /// Returns the item assigned to `elem`, if any.
+    pub fn item(&self, elem: usize) -> Option<&T> {
+        self.items.get(elem)
+    }
+
+    /// Returns the items of the alphabet in element order.
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    /// Returns the number of items in the alphabet.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns `true` if the alphabet contains no items.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns the maximum number of items the alphabet can hold.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+/// A [`Multiset`] of arbitrary hashable items, using an [`Alphabet`] to map
+/// each distinct item to an element.
+///
+/// At most `SIZE` distinct items can be counted.
+///
+/// # Examples
+///
+/// ```
+/// use utote::CountedAlphabet;
+///
+/// let mut counted = CountedAlphabet::<&str, u32, 8>::new();
+/// for token in "the cat sat on the mat".split(' ') {
+///     counted.add(token).unwrap();
+/// }
+/// assert_eq!(counted.count(&"the"), 2);
+/// assert_eq!(counted.count(&"dog"), 0);
+///
+/// let (alphabet, multiset) = counted.into_parts();
+/// assert_eq!(alphabet.len(), 5);
+/// assert_eq!(multiset.total(), 6);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CountedAlphabet<T: Hash + Eq, N: Counter, const SIZE: usize> {
+    alphabet: Alphabet<T>,
+    multiset: Multiset<N, SIZE>,
+}
+
+impl<T: Hash + Eq, N: Counter, const SIZE: usize> CountedAlphabet<T, N, SIZE> {
+    /// Constructs an empty `CountedAlphabet`.
+    pub fn new() -> Self {
+        CountedAlphabet {
+            alphabet: Alphabet::with_capacity(SIZE),
+            multiset: Multiset::empty(),
+        }
+    }
+
+    /// Returns the count of `item`, which is zero if it has not been added.
+    pub fn count<Q>(&self, item: &Q) -> N
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.alphabet.elem_of(item) {
+            // Safety: every element in the alphabet is less than SIZE.
+            Some(elem) => unsafe { *self.multiset.get_unchecked(elem) },
+            None => N::zero(),
+        }
+    }
+
+    /// Returns a reference to the alphabet.
+    pub fn alphabet(&self) -> &Alphabet<T> {
+        &self.alphabet
+    }
+
+    /// Returns a reference to the multiset of counts.
+    pub fn multiset(&self) -> &Multiset<N, SIZE> {
+        &self.multiset
+    }
+
+    /// Consumes the `CountedAlphabet`, returning the alphabet and the
+    /// multiset of counts.
+    pub fn into_parts(self) -> (Alphabet<T>, Multiset<N, SIZE>) {
+        (self.alphabet, self.multiset)
+    }
+}
+
+impl<T: Hash + Eq + Clone, N: Counter, const SIZE: usize> CountedAlphabet<T, N, SIZE> {
+    /// Increments the count of `item` by one, saturating at the maximum value
+    /// of the counter, and returns the element assigned to it.
+    ///
+    /// # Errors
+    /// Returns [`MultisetError::AlphabetFull`] if `item` is new and `SIZE`
+    /// distinct items have already been added. The counts are left unchanged.
+    pub fn add(&mut self, item: T) -> Result<usize, MultisetError> {
+        let elem = self.alphabet.insert(item)?;
+        // Safety: every element in the alphabet is less than SIZE.
+        let count = unsafe { self.multiset.get_unchecked_mut(elem) };
+        *count = count.saturating_add(&N::one());
+        Ok(elem)
+    }
+}
+
+impl<T: Hash + Eq, N: Counter, const SIZE: usize> Default for CountedAlphabet<T, N, SIZE> {
+    fn default() -> Self {
+        CountedAlphabet::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alphabet_insert() {
+        let mut alphabet = Alphabet::with_capacity(3);
+        assert!(alphabet.is_empty());
+        assert_eq!(alphabet.insert('a'), Ok(0));
+        assert_eq!(alphabet.insert('b'), Ok(1));
+        assert_eq!(alphabet.insert('a'), Ok(0));
+        assert_eq!(alphabet.insert('c'), Ok(2));
+        assert_eq!(
+            alphabet.insert('d'),
+            Err(MultisetError::AlphabetFull { capacity: 3 })
+        );
+        assert_eq!(alphabet.insert('b'), Ok(1));
+        assert_eq!(alphabet.len(), 3);
+        assert_eq!(alphabet.items(), &['a', 'b', 'c']);
+    }
+
+    #[test]
+    fn test_alphabet_lookup() {
+        let mut alphabet = Alphabet::with_capacity(4);
+        alphabet.insert(String::from("foo")).unwrap();
+        alphabet.insert(String::from("bar")).unwrap();
+        assert_eq!(alphabet.elem_of("bar"), Some(1));
+        assert_eq!(alphabet.elem_of("baz"), None);
+        assert_eq!(alphabet.item(0).map(String::as_str), Some("foo"));
+        assert_eq!(alphabet.item(2), None);
+    }
+
+    #[test]
+    fn test_counted_alphabet() {
+        let mut counted = CountedAlphabet::<String, u8, 4>::new();
+        for token in "a b a c a b".split(' ') {
+            counted.add(token.to_string()).unwrap();
+        }
+        assert_eq!(counted.count("a"), 3);
+        assert_eq!(counted.count("b"), 2);
+        assert_eq!(counted.count("c"), 1);
+        assert_eq!(counted.count("d"), 0);
+        assert_eq!(counted.multiset(), &Multiset::from([3, 2, 1, 0]));
+
+        let (alphabet, multiset) = counted.into_parts();
+        assert_eq!(alphabet.elem_of("c"), Some(2));
+        assert_eq!(multiset.total(), 6);
+    }
+
+    #[test]
+    fn test_counted_alphabet_full() {
+        let mut counted = CountedAlphabet::<u64, u16, 2>::default();
+        assert_eq!(counted.add(10), Ok(0));
+        assert_eq!(counted.add(20), Ok(1));
+        assert_eq!(
+            counted.add(30),
+            Err(MultisetError::AlphabetFull { capacity: 2 })
+        );
+        assert_eq!(counted.add(10), Ok(0));
+        assert_eq!(counted.multiset(), &Multiset::from([2, 1]));
+    }
+}
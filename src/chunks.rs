@@ -67,7 +67,7 @@ impl<'a, T, const C: usize> Iterator for ChunksExactMut<'a, T, C> {
         if self.slice.is_empty() {
             None
         } else {
-            let tmp = std::mem::replace(&mut self.slice, &mut []);
+            let tmp = std::mem::take(&mut self.slice);
             let (head, tail) = tmp.split_at_mut(C);
             self.slice = tail;
             Some(head)
@@ -181,34 +181,51 @@ trait ChunkPadUtils<T> {
         F: FnMut(&mut [T], &[T]);
     fn zip_all_chunks_remainder<F, const C: usize>(&self, other: &Self, f: F) -> bool
     where
-        F: Fn(&[T], &[T]) -> bool;
+        F: FnMut(&[T], &[T]) -> bool;
     fn zip_all_chunks_exact<F, const C: usize>(&self, other: &Self, f: F) -> bool
     where
-        F: Fn(&[T], &[T]) -> bool;
+        F: FnMut(&[T], &[T]) -> bool;
+    fn zip3_all_chunks_remainder<F, const C: usize>(&self, b: &Self, c: &Self, f: F) -> bool
+    where
+        F: FnMut(&[T], &[T], &[T]) -> bool;
+    fn zip3_all_chunks_exact<F, const C: usize>(&self, b: &Self, c: &Self, f: F) -> bool
+    where
+        F: FnMut(&[T], &[T], &[T]) -> bool;
     fn zip_any_chunks_remainder<F, const C: usize>(&self, other: &Self, f: F) -> bool
     where
-        F: Fn(&[T], &[T]) -> bool;
+        F: FnMut(&[T], &[T]) -> bool;
     fn zip_any_chunks_exact<F, const C: usize>(&self, other: &Self, f: F) -> bool
     where
-        F: Fn(&[T], &[T]) -> bool;
+        F: FnMut(&[T], &[T]) -> bool;
     fn fold_chunks_remainder<Acc, F, const C: usize>(&self, init: Acc, f: F) -> Acc
     where
         F: FnMut(Acc, &[T]) -> Acc;
     fn fold_chunks_exact<Acc, F, const C: usize>(&self, init: Acc, f: F) -> Acc
     where
         F: FnMut(Acc, &[T]) -> Acc;
+    fn zip_fold_chunks_remainder<Acc, F, const C: usize>(
+        &self,
+        other: &Self,
+        init: Acc,
+        f: F,
+    ) -> Acc
+    where
+        F: FnMut(Acc, &[T], &[T]) -> Acc;
+    fn zip_fold_chunks_exact<Acc, F, const C: usize>(&self, other: &Self, init: Acc, f: F) -> Acc
+    where
+        F: FnMut(Acc, &[T], &[T]) -> Acc;
     fn all_chunks_remainder<F, const C: usize>(&self, f: F) -> bool
     where
-        F: Fn(&[T]) -> bool;
+        F: FnMut(&[T]) -> bool;
     fn all_chunks_exact<F, const C: usize>(&self, f: F) -> bool
     where
-        F: Fn(&[T]) -> bool;
+        F: FnMut(&[T]) -> bool;
     fn any_chunks_remainder<F, const C: usize>(&self, f: F) -> bool
     where
-        F: Fn(&[T]) -> bool;
+        F: FnMut(&[T]) -> bool;
     fn any_chunks_exact<F, const C: usize>(&self, f: F) -> bool
     where
-        F: Fn(&[T]) -> bool;
+        F: FnMut(&[T]) -> bool;
 }
 
 impl<T> ChunkPadUtils<T> for [T]
@@ -270,9 +287,9 @@ where
     }
 
     #[inline]
-    fn zip_all_chunks_remainder<F, const C: usize>(&self, other: &Self, f: F) -> bool
+    fn zip_all_chunks_remainder<F, const C: usize>(&self, other: &Self, mut f: F) -> bool
     where
-        F: Fn(&[T], &[T]) -> bool,
+        F: FnMut(&[T], &[T]) -> bool,
     {
         let self_chunks = ChunksPad::<'_, T, C>::new(self);
         let other_chunks = ChunksPad::<'_, T, C>::new(other);
@@ -284,9 +301,9 @@ where
     }
 
     #[inline]
-    fn zip_all_chunks_exact<F, const C: usize>(&self, other: &Self, f: F) -> bool
+    fn zip_all_chunks_exact<F, const C: usize>(&self, other: &Self, mut f: F) -> bool
     where
-        F: Fn(&[T], &[T]) -> bool,
+        F: FnMut(&[T], &[T]) -> bool,
     {
         self.strict_chunks_exact::<C>()
             .zip(other.strict_chunks_exact::<C>())
@@ -294,9 +311,43 @@ where
     }
 
     #[inline]
-    fn zip_any_chunks_remainder<F, const C: usize>(&self, other: &Self, f: F) -> bool
+    fn zip3_all_chunks_remainder<F, const C: usize>(&self, b: &Self, c: &Self, mut f: F) -> bool
+    where
+        F: FnMut(&[T], &[T], &[T]) -> bool,
+    {
+        let self_chunks = ChunksPad::<'_, T, C>::new(self);
+        let b_chunks = ChunksPad::<'_, T, C>::new(b);
+        let c_chunks = ChunksPad::<'_, T, C>::new(c);
+        self_chunks
+            .iter()
+            .zip(b_chunks.iter().zip(c_chunks.iter()))
+            .all(|(x, (y, z))| f(x, y, z))
+            && {
+                f(
+                    self_chunks.remainder(),
+                    b_chunks.remainder(),
+                    c_chunks.remainder(),
+                )
+            }
+    }
+
+    #[inline]
+    fn zip3_all_chunks_exact<F, const C: usize>(&self, b: &Self, c: &Self, mut f: F) -> bool
     where
-        F: Fn(&[T], &[T]) -> bool,
+        F: FnMut(&[T], &[T], &[T]) -> bool,
+    {
+        self.strict_chunks_exact::<C>()
+            .zip(
+                b.strict_chunks_exact::<C>()
+                    .zip(c.strict_chunks_exact::<C>()),
+            )
+            .all(|(x, (y, z))| f(x, y, z))
+    }
+
+    #[inline]
+    fn zip_any_chunks_remainder<F, const C: usize>(&self, other: &Self, mut f: F) -> bool
+    where
+        F: FnMut(&[T], &[T]) -> bool,
     {
         let self_chunks = ChunksPad::<'_, T, C>::new(self);
         let other_chunks = ChunksPad::<'_, T, C>::new(other);
@@ -308,9 +359,9 @@ where
     }
 
     #[inline]
-    fn zip_any_chunks_exact<F, const C: usize>(&self, other: &Self, f: F) -> bool
+    fn zip_any_chunks_exact<F, const C: usize>(&self, other: &Self, mut f: F) -> bool
     where
-        F: Fn(&[T], &[T]) -> bool,
+        F: FnMut(&[T], &[T]) -> bool,
     {
         self.strict_chunks_exact::<C>()
             .zip(other.strict_chunks_exact::<C>())
@@ -344,37 +395,77 @@ where
     }
 
     #[inline]
-    fn all_chunks_remainder<F, const C: usize>(&self, f: F) -> bool
+    fn zip_fold_chunks_remainder<Acc, F, const C: usize>(
+        &self,
+        other: &Self,
+        init: Acc,
+        mut f: F,
+    ) -> Acc
     where
-        F: Fn(&[T]) -> bool,
+        F: FnMut(Acc, &[T], &[T]) -> Acc,
     {
+        let mut res = init;
         let self_chunks = ChunksPad::<'_, T, C>::new(self);
-        self_chunks.iter().all(|slice| f(slice)) && f(self_chunks.remainder())
+        let other_chunks = ChunksPad::<'_, T, C>::new(other);
+        for (a, b) in self_chunks.iter().zip(other_chunks.iter()) {
+            res = f(res, a, b);
+        }
+        res = f(res, self_chunks.remainder(), other_chunks.remainder());
+        res
     }
 
     #[inline]
-    fn all_chunks_exact<F, const C: usize>(&self, f: F) -> bool
+    fn zip_fold_chunks_exact<Acc, F, const C: usize>(
+        &self,
+        other: &Self,
+        init: Acc,
+        mut f: F,
+    ) -> Acc
     where
-        F: Fn(&[T]) -> bool,
+        F: FnMut(Acc, &[T], &[T]) -> Acc,
     {
-        self.strict_chunks_exact::<C>().all(|slice| f(slice))
+        let mut res = init;
+        for (a, b) in self
+            .strict_chunks_exact::<C>()
+            .zip(other.strict_chunks_exact::<C>())
+        {
+            res = f(res, a, b);
+        }
+        res
     }
 
     #[inline]
-    fn any_chunks_remainder<F, const C: usize>(&self, f: F) -> bool
+    fn all_chunks_remainder<F, const C: usize>(&self, mut f: F) -> bool
     where
-        F: Fn(&[T]) -> bool,
+        F: FnMut(&[T]) -> bool,
     {
         let self_chunks = ChunksPad::<'_, T, C>::new(self);
-        self_chunks.iter().any(|slice| f(slice)) || f(self_chunks.remainder())
+        self_chunks.iter().all(&mut f) && f(self_chunks.remainder())
     }
 
     #[inline]
-    fn any_chunks_exact<F, const C: usize>(&self, f: F) -> bool
+    fn all_chunks_exact<F, const C: usize>(&self, mut f: F) -> bool
     where
-        F: Fn(&[T]) -> bool,
+        F: FnMut(&[T]) -> bool,
     {
-        self.strict_chunks_exact::<C>().any(|slice| f(slice))
+        self.strict_chunks_exact::<C>().all(&mut f)
+    }
+
+    #[inline]
+    fn any_chunks_remainder<F, const C: usize>(&self, mut f: F) -> bool
+    where
+        F: FnMut(&[T]) -> bool,
+    {
+        let self_chunks = ChunksPad::<'_, T, C>::new(self);
+        self_chunks.iter().any(&mut f) || f(self_chunks.remainder())
+    }
+
+    #[inline]
+    fn any_chunks_exact<F, const C: usize>(&self, mut f: F) -> bool
+    where
+        F: FnMut(&[T]) -> bool,
+    {
+        self.strict_chunks_exact::<C>().any(&mut f)
     }
 }
 
@@ -385,21 +476,37 @@ pub(crate) trait ChunkUtils<T> {
     fn zip_map_chunks_mut<F, const C: usize>(&mut self, other: &Self, f: F)
     where
         F: FnMut(&mut [T], &[T]);
+    /// Returns `true` if `f` holds for every pair of chunks. Stops calling
+    /// `f` at the first pair for which it returns `false`.
     fn zip_all_chunks<F, const C: usize>(&self, other: &Self, f: F) -> bool
     where
-        F: Fn(&[T], &[T]) -> bool;
+        F: FnMut(&[T], &[T]) -> bool;
+    /// Returns `true` if `f` holds for every triple of chunks. Stops calling
+    /// `f` at the first triple for which it returns `false`.
+    fn zip3_all_chunks<F, const C: usize>(&self, b: &Self, c: &Self, f: F) -> bool
+    where
+        F: FnMut(&[T], &[T], &[T]) -> bool;
+    /// Returns `true` if `f` holds for any pair of chunks. Stops calling `f`
+    /// at the first pair for which it returns `true`.
     fn zip_any_chunks<F, const C: usize>(&self, other: &Self, f: F) -> bool
     where
-        F: Fn(&[T], &[T]) -> bool;
+        F: FnMut(&[T], &[T]) -> bool;
     fn fold_chunks<Acc, F, const C: usize>(&self, init: Acc, f: F) -> Acc
     where
         F: FnMut(Acc, &[T]) -> Acc;
+    fn zip_fold_chunks<Acc, F, const C: usize>(&self, other: &Self, init: Acc, f: F) -> Acc
+    where
+        F: FnMut(Acc, &[T], &[T]) -> Acc;
+    /// Returns `true` if `f` holds for every chunk. Stops calling `f` at the
+    /// first chunk for which it returns `false`.
     fn all_chunks<F, const C: usize>(&self, f: F) -> bool
     where
-        F: Fn(&[T]) -> bool;
+        F: FnMut(&[T]) -> bool;
+    /// Returns `true` if `f` holds for any chunk. Stops calling `f` at the
+    /// first chunk for which it returns `true`.
     fn any_chunks<F, const C: usize>(&self, f: F) -> bool
     where
-        F: Fn(&[T]) -> bool;
+        F: FnMut(&[T]) -> bool;
 }
 
 impl<T> ChunkUtils<T> for [T]
@@ -411,7 +518,7 @@ where
     where
         F: FnMut(&[T], &[T], &mut [T]),
     {
-        if self.len() % C == 0 {
+        if self.len().is_multiple_of(C) {
             self.zip_map_chunks_exact::<F, C>(other, out, f)
         } else {
             self.zip_map_chunks_remainder::<F, C>(other, out, f)
@@ -423,31 +530,43 @@ where
     where
         F: FnMut(&mut [T], &[T]),
     {
-        if self.len() % C == 0 {
-            self.zip_map_chunks_mut_remainder::<F, C>(other, f)
-        } else {
+        if self.len().is_multiple_of(C) {
             self.zip_map_chunks_mut_exact::<F, C>(other, f)
+        } else {
+            self.zip_map_chunks_mut_remainder::<F, C>(other, f)
         }
     }
 
     #[inline]
     fn zip_all_chunks<F, const C: usize>(&self, other: &Self, f: F) -> bool
     where
-        F: Fn(&[T], &[T]) -> bool,
+        F: FnMut(&[T], &[T]) -> bool,
     {
-        if self.len() % C == 0 {
+        if self.len().is_multiple_of(C) {
             self.zip_all_chunks_exact::<F, C>(other, f)
         } else {
             self.zip_all_chunks_remainder::<F, C>(other, f)
         }
     }
 
+    #[inline]
+    fn zip3_all_chunks<F, const C: usize>(&self, b: &Self, c: &Self, f: F) -> bool
+    where
+        F: FnMut(&[T], &[T], &[T]) -> bool,
+    {
+        if self.len().is_multiple_of(C) {
+            self.zip3_all_chunks_exact::<F, C>(b, c, f)
+        } else {
+            self.zip3_all_chunks_remainder::<F, C>(b, c, f)
+        }
+    }
+
     #[inline]
     fn zip_any_chunks<F, const C: usize>(&self, other: &Self, f: F) -> bool
     where
-        F: Fn(&[T], &[T]) -> bool,
+        F: FnMut(&[T], &[T]) -> bool,
     {
-        if self.len() % C == 0 {
+        if self.len().is_multiple_of(C) {
             self.zip_any_chunks_exact::<F, C>(other, f)
         } else {
             self.zip_any_chunks_remainder::<F, C>(other, f)
@@ -459,19 +578,31 @@ where
     where
         F: FnMut(Acc, &[T]) -> Acc,
     {
-        if self.len() % C == 0 {
+        if self.len().is_multiple_of(C) {
             self.fold_chunks_exact::<Acc, F, C>(init, f)
         } else {
             self.fold_chunks_remainder::<Acc, F, C>(init, f)
         }
     }
 
+    #[inline]
+    fn zip_fold_chunks<Acc, F, const C: usize>(&self, other: &Self, init: Acc, f: F) -> Acc
+    where
+        F: FnMut(Acc, &[T], &[T]) -> Acc,
+    {
+        if self.len().is_multiple_of(C) {
+            self.zip_fold_chunks_exact::<Acc, F, C>(other, init, f)
+        } else {
+            self.zip_fold_chunks_remainder::<Acc, F, C>(other, init, f)
+        }
+    }
+
     #[inline]
     fn all_chunks<F, const C: usize>(&self, f: F) -> bool
     where
-        F: Fn(&[T]) -> bool,
+        F: FnMut(&[T]) -> bool,
     {
-        if self.len() % C == 0 {
+        if self.len().is_multiple_of(C) {
             self.all_chunks_exact::<F, C>(f)
         } else {
             self.all_chunks_remainder::<F, C>(f)
@@ -481,9 +612,9 @@ where
     #[inline]
     fn any_chunks<F, const C: usize>(&self, f: F) -> bool
     where
-        F: Fn(&[T]) -> bool,
+        F: FnMut(&[T]) -> bool,
     {
-        if self.len() % C == 0 {
+        if self.len().is_multiple_of(C) {
             self.any_chunks_exact::<F, C>(f)
         } else {
             self.any_chunks_remainder::<F, C>(f)
@@ -668,6 +799,24 @@ mod tests {
         assert!(!is_false);
     }
 
+    #[test]
+    fn test_zip3_all_chunks() {
+        const CHUNK: usize = 2;
+        for len in [4, 5] {
+            let this: Vec<u16> = (1..=len).collect();
+            let b = vec![1u16; len as usize];
+            let c: Vec<u16> = (0..len).collect();
+
+            let fits = |x: &[u16], y: &[u16], z: &[u16]| {
+                x.iter()
+                    .zip(y.iter().zip(z.iter()))
+                    .all(|(x, (y, z))| x <= &(y + z))
+            };
+            assert!(this.zip3_all_chunks::<_, CHUNK>(&b, &c, fits));
+            assert!(!this.zip3_all_chunks::<_, CHUNK>(&c, &c, fits));
+        }
+    }
+
     #[test]
     fn test_zip_any_chunks_remainder() {
         const CHUNK: usize = 2;
@@ -739,6 +888,30 @@ mod tests {
         assert_eq!(res, 4);
     }
 
+    #[test]
+    fn test_zip_fold_chunks_remainder() {
+        const CHUNK: usize = 2;
+        let this: [u16; 5] = [1, 2, 3, 4, 5];
+        let other = [5, 4, 3, 2, 1];
+
+        let res = this.zip_fold_chunks_remainder::<u16, _, CHUNK>(&other, 0, |acc, a, b| {
+            acc + a.iter().zip(b.iter()).map(|(x, y)| x.min(y)).sum::<u16>()
+        });
+        assert_eq!(res, 9);
+    }
+
+    #[test]
+    fn test_zip_fold_chunks_exact() {
+        const CHUNK: usize = 2;
+        let this: [u16; 4] = [1, 2, 3, 4];
+        let other = [4, 3, 2, 1];
+
+        let res = this.zip_fold_chunks_exact::<u16, _, CHUNK>(&other, 0, |acc, a, b| {
+            acc + a.iter().zip(b.iter()).map(|(x, y)| x.min(y)).sum::<u16>()
+        });
+        assert_eq!(res, 6);
+    }
+
     #[test]
     fn test_all_chunks_remainder() {
         const CHUNK: usize = 2;
@@ -774,4 +947,105 @@ mod tests {
         let res = this.any_chunks_exact::<_, CHUNK>(|slice| slice.iter().any(|e| e > &4));
         assert!(!res);
     }
+
+    #[test]
+    fn test_zip_all_chunks_short_circuit() {
+        const CHUNK: usize = 4;
+        let this = vec![0u16; 1 << 20];
+        let other = vec![1u16; 1 << 20];
+
+        let mut calls = 0;
+        let res = this.zip_all_chunks::<_, CHUNK>(&other, |a, b| {
+            calls += 1;
+            a.iter().zip(b.iter()).all(|(x, y)| x >= y)
+        });
+        assert!(!res);
+        assert_eq!(calls, 1);
+
+        // A mismatch in the padded remainder chunk is still found.
+        let this = vec![1u16; (1 << 10) + 1];
+        let mut other = vec![0u16; (1 << 10) + 1];
+        *other.last_mut().unwrap() = 2;
+        let mut calls = 0;
+        let res = this.zip_all_chunks::<_, CHUNK>(&other, |a, b| {
+            calls += 1;
+            a.iter().zip(b.iter()).all(|(x, y)| x >= y)
+        });
+        assert!(!res);
+        assert_eq!(calls, (1 << 10) / CHUNK + 1);
+    }
+
+    #[test]
+    fn test_zip_any_chunks_short_circuit() {
+        const CHUNK: usize = 4;
+        let this = vec![1u16; (1 << 20) + 3];
+        let other = vec![1u16; (1 << 20) + 3];
+
+        let mut calls = 0;
+        let res = this.zip_any_chunks::<_, CHUNK>(&other, |a, b| {
+            calls += 1;
+            a.iter().zip(b.iter()).any(|(x, y)| x == y)
+        });
+        assert!(res);
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_all_any_chunks_short_circuit() {
+        const CHUNK: usize = 8;
+        let this = vec![0u16; 1 << 20];
+
+        let mut calls = 0;
+        assert!(!this.all_chunks::<_, CHUNK>(|_| {
+            calls += 1;
+            false
+        }));
+        assert_eq!(calls, 1);
+
+        let mut calls = 0;
+        assert!(this.any_chunks::<_, CHUNK>(|_| {
+            calls += 1;
+            true
+        }));
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_zip_map_chunks_dispatch() {
+        const CHUNK: usize = 2;
+        for len in [4, 5] {
+            let this: Vec<u16> = (1..=len).collect();
+            let other = vec![1u16; len as usize];
+            let mut out = vec![0u16; len as usize];
+
+            this.zip_map_chunks::<_, CHUNK>(&other, &mut out, |a, b, r| {
+                r.iter_mut()
+                    .zip(a.iter().zip(b.iter()))
+                    .for_each(|(r, (a, b))| *r = a + b)
+            });
+            assert_eq!(out, (2..=len + 1).collect::<Vec<_>>());
+
+            let mut this_mut = this.clone();
+            this_mut.zip_map_chunks_mut::<_, CHUNK>(&other, |a, b| {
+                a.iter_mut().zip(b.iter()).for_each(|(a, b)| *a += b)
+            });
+            assert_eq!(this_mut, out);
+        }
+    }
+
+    #[test]
+    fn test_fold_chunks_dispatch() {
+        const CHUNK: usize = 2;
+        for len in [4u16, 5] {
+            let this: Vec<u16> = (1..=len).collect();
+            let sum =
+                this.fold_chunks::<_, _, CHUNK>(0, |acc, slice| acc + slice.iter().sum::<u16>());
+            assert_eq!(sum, len * (len + 1) / 2);
+
+            let zip_sum = this.zip_fold_chunks::<_, _, CHUNK>(&this, 0, |acc, a, b| {
+                acc + a.iter().chain(b.iter()).sum::<u16>()
+            });
+            assert_eq!(zip_sum, 2 * sum);
+        }
+    }
 }
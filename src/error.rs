@@ -0,0 +1,65 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result};
+
+/// The error type for fallible `Multiset` construction and conversion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MultisetError {
+    /// An element was outside the bounds of the multiset (`elem >= size`).
+    ElementOutOfBounds { elem: usize, size: usize },
+    /// The number of counts given did not match the size of the multiset.
+    WrongLength { expected: usize, got: usize },
+    /// An iterator of counts did not yield exactly as many items as the size
+    /// of the multiset. `consumed` is the number of items taken from the
+    /// iterator before stopping, which is at most `expected + 1`.
+    WrongIterLength { expected: usize, consumed: usize },
+    /// A pair in a sparse string representation was not of the form
+    /// `element:count`.
+    InvalidPair { pair: String },
+    /// An element was given more than once in a sparse representation.
+    DuplicateElement { elem: usize },
+    /// A count could not be represented by the counter type of the multiset.
+    CountOutOfRange { elem: usize },
+    /// A new item could not be added to an alphabet which already holds
+    /// `capacity` items.
+    AlphabetFull { capacity: usize },
+    /// An empty multiset was given where at least one member is needed, such
+    /// as to sample from.
+    Empty,
+}
+
+impl Display for MultisetError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            MultisetError::ElementOutOfBounds { elem, size } => write!(
+                f,
+                "element: {} not in Multiset (element >= SIZE: {})",
+                elem, size
+            ),
+            MultisetError::WrongLength { expected, got } => write!(
+                f,
+                "wrong number of counts for Multiset (expected: {}, got: {})",
+                expected, got
+            ),
+            MultisetError::WrongIterLength { expected, consumed } => write!(
+                f,
+                "wrong number of counts for Multiset (expected: {}, consumed: {})",
+                expected, consumed
+            ),
+            MultisetError::InvalidPair { pair } => {
+                write!(f, "invalid pair: {:?} (expected element:count)", pair)
+            }
+            MultisetError::DuplicateElement { elem } => {
+                write!(f, "element: {} given more than once", elem)
+            }
+            MultisetError::CountOutOfRange { elem } => {
+                write!(f, "count for element: {} out of range of counter", elem)
+            }
+            MultisetError::AlphabetFull { capacity } => {
+                write!(f, "alphabet full (capacity: {})", capacity)
+            }
+            MultisetError::Empty => write!(f, "multiset is empty"),
+        }
+    }
+}
+
+impl Error for MultisetError {}
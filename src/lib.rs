@@ -37,6 +37,20 @@
 //! let bar_count: &u16 = multiset.get(*item_map.get("bar").unwrap()).unwrap();
 //! ```
 //!
+//! [`CountedAlphabet`] packages this pattern up, assigning elements to items
+//! as they are first seen.
+//!
+//! ```
+//! use utote::CountedAlphabet;
+//!
+//! let mut counted = CountedAlphabet::<&str, u16, 2>::new();
+//! counted.add("foo").unwrap();
+//! counted.add("bar").unwrap();
+//! counted.add("foo").unwrap();
+//!
+//! assert_eq!(counted.count("foo"), 2);
+//! ```
+//!
 //! # SIMD and Generics
 //!
 //! Due to remaining rough edges in the const generic feature there is an extra
@@ -51,11 +65,24 @@
 //! # Cargo Features
 //!
 //! - __simd__: Requires nightly rust toolchain. Enables simd implementations
-//! using the [__packed_simd__ crate](https://docs.rs/packed_simd_2) crate and
-//! unsatble features: [const_generics](https://github.com/rust-lang/rust/issues/44580)
-//! and [const_evaluatable_checked](https://github.com/rust-lang/rust/issues/76560).
-//! - __rand__: Enables [`choose_random`](Multiset::choose_random) methods for
-//! multiset structs using the [__rand__ crate](https://docs.rs/rand).
+//!   using the [__packed_simd__ crate](https://docs.rs/packed_simd_2) crate and
+//!   unsatble features: [const_generics](https://github.com/rust-lang/rust/issues/44580)
+//!   and [const_evaluatable_checked](https://github.com/rust-lang/rust/issues/76560).
+//!   Kernels are chosen at runtime from the features the cpu supports:
+//!   SSE4.2, AVX or AVX2 on x86 and x86_64, and NEON on aarch64. On wasm32
+//!   the simd128 kernels are used when built with
+//!   `-C target-feature=+simd128`.
+//! - __rand__: Enables [`choose_random`](Multiset::choose_random) and
+//!   [`sample`](Multiset::sample) methods for multiset structs using the
+//!   [__rand__](https://docs.rs/rand) and
+//!   [__rand_distr__](https://docs.rs/rand_distr) crates.
+//! - __serde__: Implements `Serialize` and `Deserialize` for `Multiset` using
+//!   the [__serde__ crate](https://docs.rs/serde). A multiset is serialized as
+//!   a fixed length sequence of its counts, or as a map of its non-zero
+//!   counts with [`serde_sparse`].
+//! - __proptest__: Implements `Arbitrary` for `Multiset` and provides
+//!   strategies for generating multisets in the [`strategy`] module, for use
+//!   with the [__proptest__ crate](https://docs.rs/proptest).
 //!
 //! # Performance
 //!
@@ -75,9 +102,39 @@
     allow(incomplete_features)
 )]
 
+mod alphabet;
+pub use alphabet::*;
+mod error;
+pub use error::*;
 mod multiset;
 pub use multiset::*;
-#[cfg(feature = "simd")]
+// Only the simd kernels use the chunk utilities, and targets without a simd
+// tier build none of them.
+#[cfg(any(
+    test,
+    all(
+        feature = "simd",
+        any(
+            target_arch = "x86",
+            target_arch = "x86_64",
+            target_arch = "aarch64",
+            all(target_arch = "wasm32", target_feature = "simd128")
+        )
+    )
+))]
 mod chunks;
+pub mod ord;
+#[cfg(feature = "rand")]
+mod rand_impls;
+#[cfg(feature = "rand")]
+pub use rand_impls::MultisetDistribution;
+#[cfg(feature = "serde")]
+mod serde_impls;
+#[cfg(feature = "serde")]
+pub use serde_impls::serde_sparse;
 #[cfg(feature = "simd")]
 mod simd;
+#[cfg(feature = "simd")]
+pub use simd::{cpu_feature, CPUFeature};
+#[cfg(any(feature = "proptest", test))]
+pub mod strategy;
@@ -1,18 +1,30 @@
 #[cfg(feature = "simd")]
 use crate::simd::SimdTypes;
-use num_traits::{AsPrimitive, One, Unsigned, Zero};
-#[cfg(all(not(feature = "simd"), feature = "rand"))]
-use rand::{Rng, RngCore};
-#[cfg(not(feature = "simd"))]
+use crate::MultisetError;
+use num_traits::{
+    AsPrimitive, Bounded, CheckedAdd, NumCast, One, SaturatingAdd, SaturatingMul, SaturatingSub,
+    Unsigned, Zero,
+};
+#[cfg(feature = "rand")]
+use rand::seq::SliceRandom;
+#[cfg(feature = "rand")]
+use rand::Rng;
+#[cfg(feature = "rand")]
+use rand_distr::{Binomial, Distribution};
+use std::borrow::Borrow;
 use std::cmp::Ordering;
-use std::fmt::{Debug, Display, Formatter, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+use std::fmt::{Debug, Display, Formatter, Result, Write};
 use std::hash::{Hash, Hasher};
-use std::iter::FromIterator;
-use std::mem;
+use std::iter::{FromIterator, Sum};
 use std::ops::{
     Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Rem, RemAssign, Sub, SubAssign,
 };
+#[cfg(not(feature = "simd"))]
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign};
 use std::slice::{Iter, IterMut, SliceIndex};
+use std::str::FromStr;
 
 mod sealed {
     pub trait Sealed {}
@@ -31,12 +43,16 @@ pub trait CounterArithmetic:
     + Sized
     + Add
     + AddAssign
+    + CheckedAdd
     + Div
     + DivAssign
     + Mul
     + MulAssign
     + Rem
     + RemAssign
+    + SaturatingAdd
+    + SaturatingMul
+    + SaturatingSub
     + Sub
     + SubAssign
 {
@@ -54,12 +70,14 @@ impl CounterArithmetic for usize {}
 #[doc(hidden)]
 pub trait CounterBasic:
     sealed::Sealed
+    + Bounded
     + Clone
     + Copy
     + Debug
     + Default
     + Display
     + Hash
+    + NumCast
     + One
     + Ord
     + PartialEq
@@ -67,6 +85,8 @@ pub trait CounterBasic:
     + Unsigned
     + Zero
     + AsPrimitive<usize>
+    + AsPrimitive<u64>
+    + AsPrimitive<u128>
     + AsPrimitive<f64>
 {
     // empty
@@ -81,6 +101,11 @@ impl CounterBasic for usize {}
 #[cfg(not(feature = "simd"))]
 /// The Counter trait simplifies the use of Multiset with generics. This trait
 /// is sealed and not implementable outside of this crate.
+///
+/// Counters are the unsigned integer types only. Multisets rely on counts
+/// being totally ordered, hashable and exact, which floats are not; for
+/// fractional weights pass an `&[f64]` alongside the multiset, as with
+/// [`Multiset::weighted_shannon_entropy`].
 pub trait Counter: sealed::Sealed + CounterArithmetic + CounterBasic {
     // empty
 }
@@ -166,6 +191,27 @@ impl Counter for usize {}
 /// [`get`]: Multiset::get
 /// [`get_mut`]: Multiset::get_mut
 ///
+/// # Copying
+///
+/// Like the array it wraps, `Multiset` is always [`Copy`], and copying it
+/// copies all `SIZE` counts. A `Multiset<u16, 4096>` is 8 KiB, so methods take
+/// their multiset arguments by reference, and the operator traits are also
+/// implemented for references (`&a + &b`) to avoid copies. Methods which
+/// return a new multiset are `#[must_use]`; most have an in-place or
+/// `_into` variant for reusing an existing multiset.
+///
+/// ```
+/// use utote::Multiset;
+///
+/// let a = Multiset::<u16, 4096>::repeat(1);
+/// assert_eq!(std::mem::size_of_val(&a), 8192);
+///
+/// // `b` is a copy, so `a` can still be used.
+/// let mut b = a;
+/// b.union_mut(&(&a + &a));
+/// assert_eq!(a.intersection(&b), a);
+/// ```
+///
 /// # Using Generically
 ///
 /// The `Counter` trait is provided to simplify using `Multiset` generically.
@@ -186,11 +232,137 @@ pub struct Multiset<N: Counter, const SIZE: usize> {
 // Common trait implementations for Multiset
 ////////////////////////////////////////////////////////////////////////////////
 
+// The number of non-zero element counts shown by the Debug impl before the
+// remaining ones are summarised.
+const DEBUG_MAX_ENTRIES: usize = 16;
+
+/// Formats the size, total and non-zero element counts of the multiset. Only
+/// the first 16 non-zero counts are shown, followed by the number omitted.
+///
+/// # Examples
+///
+/// ```
+/// use utote::Multiset;
+///
+/// let multiset = Multiset::from([0u8, 7, 0, 1]);
+/// assert_eq!(
+///     format!("{:?}", multiset),
+///     "Multiset { size: 4, total: 8, counts: {1: 7, 3: 1} }"
+/// );
+/// ```
 impl<N: Counter, const SIZE: usize> Debug for Multiset<N, SIZE> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        f.debug_struct("Multiset")
-            .field("data", &self.data)
-            .finish()
+        let total: u128 = self.iter().map(|e| AsPrimitive::<u128>::as_(*e)).sum();
+        write!(
+            f,
+            "Multiset {{ size: {}, total: {}, counts: {{",
+            SIZE, total
+        )?;
+        let mut shown = 0;
+        let mut omitted = 0;
+        for (elem, count) in self.iter().enumerate() {
+            if count > &N::zero() {
+                if shown == DEBUG_MAX_ENTRIES {
+                    omitted += 1;
+                    continue;
+                }
+                if shown > 0 {
+                    f.write_str(", ")?;
+                }
+                write!(f, "{}: {:?}", elem, count)?;
+                shown += 1;
+            }
+        }
+        if omitted > 0 {
+            write!(f, ", ... ({} more)", omitted)?;
+        }
+        f.write_str("} }")
+    }
+}
+
+/// Formats the non-zero element counts of the multiset in element order, as
+/// `{element: count, ...}`.
+///
+/// # Examples
+///
+/// ```
+/// use utote::Multiset;
+///
+/// let multiset = Multiset::from([0u8, 7, 0, 1]);
+/// assert_eq!(multiset.to_string(), "{1: 7, 3: 1}");
+/// assert_eq!(Multiset::<u8, 4>::empty().to_string(), "{}");
+/// ```
+impl<N: Counter, const SIZE: usize> Display for Multiset<N, SIZE> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.write_str("{")?;
+        let mut first = true;
+        for (elem, count) in self.iter().enumerate() {
+            if count > &N::zero() {
+                if !first {
+                    f.write_str(", ")?;
+                }
+                write!(f, "{}: {}", elem, count)?;
+                first = false;
+            }
+        }
+        f.write_str("}")
+    }
+}
+
+/// Parses a comma separated list of `element:count` pairs, such as
+/// `"0:3, 4:1, 17:2"`. Whitespace around elements and counts is ignored, and
+/// the list may optionally be enclosed in braces, so the output of [`Display`]
+/// can be parsed back. Elements which are not listed have a count of zero.
+///
+/// # Examples
+///
+/// ```
+/// use utote::{Multiset, MultisetError};
+///
+/// let multiset: Multiset<u8, 5> = "0:3, 4:1".parse().unwrap();
+/// assert_eq!(multiset, Multiset::from([3, 0, 0, 0, 1]));
+///
+/// let parsed: Multiset<u8, 5> = multiset.to_string().parse().unwrap();
+/// assert_eq!(parsed, multiset);
+///
+/// let duplicate = "1:1,1:2".parse::<Multiset<u8, 5>>();
+/// assert_eq!(duplicate, Err(MultisetError::DuplicateElement { elem: 1 }));
+/// ```
+impl<N: Counter, const SIZE: usize> FromStr for Multiset<N, SIZE> {
+    type Err = MultisetError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let mut s = s.trim();
+        if let Some(inner) = s.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+            s = inner.trim();
+        }
+        let mut res: Self = Multiset::empty();
+        let mut seen = [false; SIZE];
+        if s.is_empty() {
+            return Ok(res);
+        }
+        for pair in s.split(',') {
+            let invalid = || MultisetError::InvalidPair {
+                pair: pair.trim().to_string(),
+            };
+            let mut parts = pair.splitn(2, ':');
+            let (elem, count) = match (parts.next(), parts.next()) {
+                (Some(elem), Some(count)) => (elem, count),
+                _ => return Err(invalid()),
+            };
+            let elem: usize = elem.trim().parse().map_err(|_| invalid())?;
+            let count: u128 = count.trim().parse().map_err(|_| invalid())?;
+            if elem >= SIZE {
+                return Err(MultisetError::ElementOutOfBounds { elem, size: SIZE });
+            }
+            if seen[elem] {
+                return Err(MultisetError::DuplicateElement { elem });
+            }
+            seen[elem] = true;
+            res.data[elem] =
+                <N as NumCast>::from(count).ok_or(MultisetError::CountOutOfRange { elem })?;
+        }
+        Ok(res)
     }
 }
 
@@ -237,12 +409,15 @@ impl<N: Counter, I: SliceIndex<[N]>, const SIZE: usize> IndexMut<I> for Multiset
     }
 }
 
+/// Collects counts positionally into a multiset.
+///
+/// This is lenient about the length of the iterator: if it yields fewer than
+/// `SIZE` items the remaining counts are zero, and any items beyond `SIZE` are
+/// ignored. See [`Multiset::try_from_iter`] for a strict alternative.
 impl<N: Counter, const SIZE: usize> FromIterator<N> for Multiset<N, SIZE> {
     #[inline]
     fn from_iter<T: IntoIterator<Item = N>>(iter: T) -> Self {
-        // Safety: iter chained with repeated zeroes guarantees that we have
-        // enough elements to fully initialise the Multiset.
-        let mut res: Self = unsafe { Multiset::new_uninitialized() };
+        let mut res = Multiset::empty();
         let it = iter.into_iter().chain(std::iter::repeat(N::zero()));
         res.iter_mut().zip(it).for_each(|(r, e)| *r = e);
         res
@@ -256,13 +431,102 @@ impl<'a, N: 'a + Counter, const SIZE: usize> FromIterator<&'a N> for Multiset<N,
     }
 }
 
+/// Collects `(element, count)` pairs into a multiset, adding each count to
+/// the given element. Counts for repeated elements are summed, saturating at
+/// the maximum value of the counter.
+///
+/// # Panics
+/// If any element is out of bounds of the Multiset. See
+/// [`Multiset::try_from_pairs`] for a fallible alternative.
+impl<N: Counter, const SIZE: usize> FromIterator<(usize, N)> for Multiset<N, SIZE> {
+    #[inline]
+    fn from_iter<T: IntoIterator<Item = (usize, N)>>(iter: T) -> Self {
+        iter.into_iter()
+            .fold(Multiset::empty(), |mut acc, (elem, count)| {
+                if elem >= SIZE {
+                    panic!("element: {} not in Multiset (element > SIZE)", elem)
+                }
+                // Safety: Above condition ensures `elem` is not out of bounds.
+                let c = unsafe { acc.get_unchecked_mut(elem) };
+                *c = c.saturating_add(&count);
+                acc
+            })
+    }
+}
+
+/// Increments the count of each element yielded by the iterator by one,
+/// saturating at the maximum value of the counter.
+///
+/// # Panics
+/// If any element is out of bounds of the Multiset.
+impl<N: Counter, const SIZE: usize> Extend<usize> for Multiset<N, SIZE> {
+    #[inline]
+    fn extend<T: IntoIterator<Item = usize>>(&mut self, iter: T) {
+        for elem in iter {
+            match self.get_mut(elem) {
+                Some(c) => *c = c.saturating_add(&N::one()),
+                None => panic!("element: {} not in Multiset (element > SIZE)", elem),
+            }
+        }
+    }
+}
+
+/// Adds each count to the given element, saturating at the maximum value of
+/// the counter.
+///
+/// # Panics
+/// If any element is out of bounds of the Multiset.
+impl<N: Counter, const SIZE: usize> Extend<(usize, N)> for Multiset<N, SIZE> {
+    #[inline]
+    fn extend<T: IntoIterator<Item = (usize, N)>>(&mut self, iter: T) {
+        for (elem, count) in iter {
+            match self.get_mut(elem) {
+                Some(c) => *c = c.saturating_add(&count),
+                None => panic!("element: {} not in Multiset (element > SIZE)", elem),
+            }
+        }
+    }
+}
+
+/// Sums an iterator of multisets element-wise, starting from the empty
+/// multiset. Counts saturate at the maximum value of the counter.
+///
+/// # Examples
+///
+/// ```
+/// use utote::Multiset;
+///
+/// let sets = vec![Multiset::from([1u8, 0, 2]), Multiset::from([3u8, 1, 0])];
+/// let total: Multiset<u8, 3> = sets.iter().sum();
+/// assert_eq!(total, Multiset::from([4, 1, 2]));
+/// assert_eq!(sets.into_iter().sum::<Multiset<u8, 3>>(), total);
+/// ```
+impl<N: Counter, const SIZE: usize> Sum for Multiset<N, SIZE> {
+    #[inline]
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Multiset::empty(), |mut acc, set| {
+            acc += &set;
+            acc
+        })
+    }
+}
+
+impl<'a, N: 'a + Counter, const SIZE: usize> Sum<&'a Multiset<N, SIZE>> for Multiset<N, SIZE> {
+    #[inline]
+    fn sum<I: Iterator<Item = &'a Multiset<N, SIZE>>>(iter: I) -> Self {
+        let mut res = Multiset::empty();
+        res.merge_from(iter);
+        res
+    }
+}
+
 impl<N: Counter, const SIZE: usize> IntoIterator for Multiset<N, SIZE> {
     type Item = N;
     type IntoIter = std::array::IntoIter<N, SIZE>;
 
     #[inline]
     fn into_iter(self) -> Self::IntoIter {
-        std::array::IntoIter::new(self.data)
+        IntoIterator::into_iter(self.data)
     }
 }
 
@@ -307,10 +571,17 @@ impl<N: Counter, const SIZE: usize> From<Multiset<N, SIZE>> for [N; SIZE] {
     }
 }
 
-impl<N: Counter, const SIZE: usize> From<&[N]> for Multiset<N, SIZE> {
+impl<N: Counter, const SIZE: usize> TryFrom<&[N]> for Multiset<N, SIZE> {
+    type Error = MultisetError;
+
     #[inline]
-    fn from(slice: &[N]) -> Self {
-        slice.iter().collect()
+    fn try_from(slice: &[N]) -> std::result::Result<Self, Self::Error> {
+        <[N; SIZE]>::try_from(slice)
+            .map(Multiset::from)
+            .map_err(|_| MultisetError::WrongLength {
+                expected: SIZE,
+                got: slice.len(),
+            })
     }
 }
 
@@ -327,6 +598,28 @@ impl<'a, N: Counter, const SIZE: usize> From<&'a mut Multiset<N, SIZE>> for &'a
     }
 }
 
+impl<N: Counter, const SIZE: usize> AsRef<[N]> for Multiset<N, SIZE> {
+    #[inline]
+    fn as_ref(&self) -> &[N] {
+        &self.data
+    }
+}
+
+impl<N: Counter, const SIZE: usize> AsMut<[N]> for Multiset<N, SIZE> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut [N] {
+        &mut self.data
+    }
+}
+
+// Hash and Eq both defer to the counts, so they agree with those of the slice.
+impl<N: Counter, const SIZE: usize> Borrow<[N]> for Multiset<N, SIZE> {
+    #[inline]
+    fn borrow(&self) -> &[N] {
+        &self.data
+    }
+}
+
 /// Partial order based on proper sub/super sets
 #[cfg(not(feature = "simd"))]
 impl<N: Counter, const SIZE: usize> PartialOrd for Multiset<N, SIZE> {
@@ -373,12 +666,56 @@ impl<N: Counter, const SIZE: usize> PartialOrd for Multiset<N, SIZE> {
 // there is no partial-order to that data being aliased then the D-M ordering
 // should not be used.
 
+/// A wrapper which gives a multiset a total order, using
+/// [`Multiset::cmp_lexicographic`].
+///
+/// `Multiset` is only partially ordered by inclusion, so this is needed to
+/// sort multisets or use them as keys in ordered collections. The order has
+/// no meaning beyond being total and consistent with equality.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BTreeSet;
+/// use utote::{Lex, Multiset};
+///
+/// let mut set = BTreeSet::new();
+/// set.insert(Lex(Multiset::from([0u8, 2])));
+/// set.insert(Lex(Multiset::from([1u8, 0])));
+/// set.insert(Lex(Multiset::from([0u8, 1])));
+///
+/// let sorted: Vec<_> = set.into_iter().map(|Lex(ms)| ms).collect();
+/// assert_eq!(
+///     sorted,
+///     vec![
+///         Multiset::from([0, 1]),
+///         Multiset::from([0, 2]),
+///         Multiset::from([1, 0]),
+///     ]
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[repr(transparent)]
+pub struct Lex<T>(pub T);
+
+impl<N: Counter, const SIZE: usize> PartialOrd for Lex<Multiset<N, SIZE>> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N: Counter, const SIZE: usize> Ord for Lex<Multiset<N, SIZE>> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp_lexicographic(&other.0)
+    }
+}
+
 impl<N: Counter, const SIZE: usize> Default for Multiset<N, SIZE> {
     #[inline]
     fn default() -> Self {
-        Multiset {
-            data: [N::default(); SIZE],
-        }
+        Multiset::empty()
     }
 }
 
@@ -388,40 +725,80 @@ impl<N: Counter, const SIZE: usize> Default for Multiset<N, SIZE> {
 
 // todo: use SIMD for ops?
 
+/// Element-wise sum of two multisets, saturating at the maximum value of the
+/// counter. Equivalent to [`Multiset::sum`].
+///
+/// # Examples
+///
+/// ```
+/// use utote::Multiset;
+///
+/// let a = Multiset::from([250u8, 1, 0]);
+/// let b = Multiset::from([10u8, 2, 0]);
+/// assert_eq!(a + b, Multiset::from([255, 3, 0]));
+/// assert_eq!(&a + &b, Multiset::from([255, 3, 0]));
+/// ```
 impl<N: Counter, const SIZE: usize> Add for Multiset<N, SIZE> {
     type Output = Multiset<N, SIZE>;
 
     #[inline]
     fn add(self, rhs: Self) -> Self::Output {
-        self.into_iter()
-            .zip(rhs.into_iter())
-            .map(|(a, b)| a + b)
-            .collect()
+        self.zip_map(&rhs, |a, b| a.saturating_add(&b))
+    }
+}
+
+impl<N: Counter, const SIZE: usize> Add<&Multiset<N, SIZE>> for &Multiset<N, SIZE> {
+    type Output = Multiset<N, SIZE>;
+
+    #[inline]
+    fn add(self, rhs: &Multiset<N, SIZE>) -> Self::Output {
+        self.zip_map(rhs, |a, b| a.saturating_add(&b))
     }
 }
 
+/// Adds `rhs` to every count, saturating at the maximum value of the counter.
 impl<N: Counter, const SIZE: usize> Add<N> for Multiset<N, SIZE> {
     type Output = Multiset<N, SIZE>;
 
     #[inline]
     fn add(self, rhs: N) -> Self::Output {
-        self.into_iter().map(|a| a + rhs).collect()
+        self.into_iter().map(|a| a.saturating_add(&rhs)).collect()
     }
 }
 
+/// In-place element-wise sum, saturating at the maximum value of the counter.
+///
+/// # Examples
+///
+/// ```
+/// use utote::Multiset;
+///
+/// let mut a = Multiset::from([250u8, 1, 0]);
+/// a += &Multiset::from([10u8, 2, 0]);
+/// assert_eq!(a, Multiset::from([255, 3, 0]));
+/// ```
 impl<N: Counter, const SIZE: usize> AddAssign for Multiset<N, SIZE> {
     #[inline]
     fn add_assign(&mut self, rhs: Self) {
+        *self += &rhs;
+    }
+}
+
+impl<N: Counter, const SIZE: usize> AddAssign<&Multiset<N, SIZE>> for Multiset<N, SIZE> {
+    #[inline]
+    fn add_assign(&mut self, rhs: &Multiset<N, SIZE>) {
         self.iter_mut()
-            .zip(rhs.into_iter())
-            .for_each(|(l, r)| *l += r);
+            .zip(rhs.iter())
+            .for_each(|(l, r)| *l = l.saturating_add(r));
     }
 }
 
+/// Adds `rhs` to every count in place, saturating at the maximum value of the
+/// counter.
 impl<N: Counter, const SIZE: usize> AddAssign<N> for Multiset<N, SIZE> {
     #[inline]
     fn add_assign(&mut self, rhs: N) {
-        self.iter_mut().for_each(|l| *l += rhs);
+        self.iter_mut().for_each(|l| *l = l.saturating_add(&rhs));
     }
 }
 
@@ -430,13 +807,23 @@ impl<N: Counter, const SIZE: usize> Div for Multiset<N, SIZE> {
 
     #[inline]
     fn div(self, rhs: Self) -> Self::Output {
-        self.into_iter()
-            .zip(rhs.into_iter())
-            .map(|(a, b)| a / b)
-            .collect()
+        self.into_iter().zip(rhs).map(|(a, b)| a / b).collect()
     }
 }
 
+/// Divides every count by `rhs`, rounding down.
+///
+/// # Panics
+/// Panics if `rhs` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use utote::Multiset;
+///
+/// let multiset = Multiset::from([9u8, 4, 0, 1]);
+/// assert_eq!(multiset / 2, Multiset::from([4, 2, 0, 0]));
+/// ```
 impl<N: Counter, const SIZE: usize> Div<N> for Multiset<N, SIZE> {
     type Output = Multiset<N, SIZE>;
 
@@ -449,12 +836,14 @@ impl<N: Counter, const SIZE: usize> Div<N> for Multiset<N, SIZE> {
 impl<N: Counter, const SIZE: usize> DivAssign for Multiset<N, SIZE> {
     #[inline]
     fn div_assign(&mut self, rhs: Self) {
-        self.iter_mut()
-            .zip(rhs.into_iter())
-            .for_each(|(l, r)| *l /= r);
+        self.iter_mut().zip(rhs).for_each(|(l, r)| *l /= r);
     }
 }
 
+/// Divides every count by `rhs` in place, rounding down.
+///
+/// # Panics
+/// Panics if `rhs` is zero.
 impl<N: Counter, const SIZE: usize> DivAssign<N> for Multiset<N, SIZE> {
     #[inline]
     fn div_assign(&mut self, rhs: N) {
@@ -462,40 +851,67 @@ impl<N: Counter, const SIZE: usize> DivAssign<N> for Multiset<N, SIZE> {
     }
 }
 
+/// Multiplies the counts of each element, saturating at the maximum value of
+/// the counter.
+///
+/// # Examples
+///
+/// ```
+/// use utote::Multiset;
+///
+/// let a = Multiset::from([100u8, 3, 0, 1]);
+/// let b = Multiset::from([3u8, 3, 5, 0]);
+/// assert_eq!(a * b, Multiset::from([255, 9, 0, 0]));
+/// ```
 impl<N: Counter, const SIZE: usize> Mul for Multiset<N, SIZE> {
     type Output = Multiset<N, SIZE>;
 
     #[inline]
     fn mul(self, rhs: Self) -> Self::Output {
         self.into_iter()
-            .zip(rhs.into_iter())
-            .map(|(a, b)| a * b)
+            .zip(rhs)
+            .map(|(a, b)| a.saturating_mul(&b))
             .collect()
     }
 }
 
+/// Multiplies every count by `rhs`, saturating at the maximum value of the
+/// counter.
+///
+/// # Examples
+///
+/// ```
+/// use utote::Multiset;
+///
+/// let multiset = Multiset::from([100u8, 3, 0, 1]);
+/// assert_eq!(multiset * 3, Multiset::from([255, 9, 0, 3]));
+/// ```
 impl<N: Counter, const SIZE: usize> Mul<N> for Multiset<N, SIZE> {
     type Output = Multiset<N, SIZE>;
 
     #[inline]
     fn mul(self, rhs: N) -> Self::Output {
-        self.into_iter().map(|a| a * rhs).collect()
+        self.into_iter().map(|a| a.saturating_mul(&rhs)).collect()
     }
 }
 
+/// Multiplies the counts of each element in place, saturating at the maximum
+/// value of the counter.
 impl<N: Counter, const SIZE: usize> MulAssign for Multiset<N, SIZE> {
     #[inline]
     fn mul_assign(&mut self, rhs: Self) {
         self.iter_mut()
-            .zip(rhs.into_iter())
-            .for_each(|(l, r)| *l *= r);
+            .zip(rhs)
+            .for_each(|(l, r)| *l = l.saturating_mul(&r));
     }
 }
 
+/// Multiplies every count by `rhs` in place, saturating at the maximum value
+/// of the counter.
 impl<N: Counter, const SIZE: usize> MulAssign<N> for Multiset<N, SIZE> {
     #[inline]
     fn mul_assign(&mut self, rhs: N) {
-        self.iter_mut().for_each(|l| *l *= rhs);
+        self.iter_mut().for_each(|l| *l = l.saturating_mul(&rhs));
     }
 }
 
@@ -504,10 +920,7 @@ impl<N: Counter, const SIZE: usize> Rem for Multiset<N, SIZE> {
 
     #[inline]
     fn rem(self, rhs: Self) -> Self::Output {
-        self.into_iter()
-            .zip(rhs.into_iter())
-            .map(|(a, b)| a % b)
-            .collect()
+        self.into_iter().zip(rhs).map(|(a, b)| a % b).collect()
     }
 }
 
@@ -523,9 +936,7 @@ impl<N: Counter, const SIZE: usize> Rem<N> for Multiset<N, SIZE> {
 impl<N: Counter, const SIZE: usize> RemAssign for Multiset<N, SIZE> {
     #[inline]
     fn rem_assign(&mut self, rhs: Self) {
-        self.iter_mut()
-            .zip(rhs.into_iter())
-            .for_each(|(l, r)| *l %= r);
+        self.iter_mut().zip(rhs).for_each(|(l, r)| *l %= r);
     }
 }
 
@@ -536,83 +947,217 @@ impl<N: Counter, const SIZE: usize> RemAssign<N> for Multiset<N, SIZE> {
     }
 }
 
+/// Element-wise difference of two multisets, saturating at zero. Equivalent to
+/// [`Multiset::difference`].
+///
+/// Because counts never go below zero, subtraction is not the inverse of
+/// addition: `(a - b) + b` is only equal to `a` when `b` is a subset of `a`.
+///
+/// # Examples
+///
+/// ```
+/// use utote::Multiset;
+///
+/// let a = Multiset::from([5u8, 1, 0]);
+/// let b = Multiset::from([2u8, 3, 0]);
+/// assert_eq!(a - b, Multiset::from([3, 0, 0]));
+/// assert_eq!(&a - &b, Multiset::from([3, 0, 0]));
+/// assert_ne!((a - b) + b, a);
+/// ```
 impl<N: Counter, const SIZE: usize> Sub for Multiset<N, SIZE> {
     type Output = Multiset<N, SIZE>;
 
     #[inline]
     fn sub(self, rhs: Self) -> Self::Output {
-        self.into_iter()
-            .zip(rhs.into_iter())
-            .map(|(a, b)| a - b)
-            .collect()
+        self.zip_map(&rhs, |a, b| a.saturating_sub(&b))
+    }
+}
+
+impl<N: Counter, const SIZE: usize> Sub<&Multiset<N, SIZE>> for &Multiset<N, SIZE> {
+    type Output = Multiset<N, SIZE>;
+
+    #[inline]
+    fn sub(self, rhs: &Multiset<N, SIZE>) -> Self::Output {
+        self.zip_map(rhs, |a, b| a.saturating_sub(&b))
     }
 }
 
+/// Subtracts `rhs` from every count, saturating at zero.
 impl<N: Counter, const SIZE: usize> Sub<N> for Multiset<N, SIZE> {
     type Output = Multiset<N, SIZE>;
 
     #[inline]
     fn sub(self, rhs: N) -> Self::Output {
-        self.into_iter().map(|a| a - rhs).collect()
+        self.into_iter().map(|a| a.saturating_sub(&rhs)).collect()
     }
 }
 
+/// In-place element-wise difference, saturating at zero.
+///
+/// # Examples
+///
+/// ```
+/// use utote::Multiset;
+///
+/// let mut a = Multiset::from([5u8, 1, 0]);
+/// a -= &Multiset::from([2u8, 3, 0]);
+/// assert_eq!(a, Multiset::from([3, 0, 0]));
+/// ```
 impl<N: Counter, const SIZE: usize> SubAssign for Multiset<N, SIZE> {
     #[inline]
     fn sub_assign(&mut self, rhs: Self) {
+        *self -= &rhs;
+    }
+}
+
+impl<N: Counter, const SIZE: usize> SubAssign<&Multiset<N, SIZE>> for Multiset<N, SIZE> {
+    #[inline]
+    fn sub_assign(&mut self, rhs: &Multiset<N, SIZE>) {
         self.iter_mut()
-            .zip(rhs.into_iter())
-            .for_each(|(l, r)| *l -= r);
+            .zip(rhs.iter())
+            .for_each(|(l, r)| *l = l.saturating_sub(r));
     }
 }
 
+/// Subtracts `rhs` from every count in place, saturating at zero.
 impl<N: Counter, const SIZE: usize> SubAssign<N> for Multiset<N, SIZE> {
     #[inline]
     fn sub_assign(&mut self, rhs: N) {
-        self.iter_mut().for_each(|l| *l -= rhs);
+        self.iter_mut().for_each(|l| *l = l.saturating_sub(&rhs));
     }
 }
 
-////////////////////////////////////////////////////////////////////////////////
-// Inherent methods
-////////////////////////////////////////////////////////////////////////////////
-
-impl<N: Counter, const SIZE: usize> Multiset<N, SIZE> {
-    /// The number of elements in the multiset.
-    pub const SIZE: usize = SIZE;
+/// The intersection of two multisets, equivalent to [`Multiset::intersection`].
+///
+/// # Examples
+///
+/// ```
+/// use utote::Multiset;
+///
+/// let a = Multiset::from([1u8, 2, 0, 4]);
+/// let b = Multiset::from([0u8, 3, 1, 4]);
+/// assert_eq!(a & b, a.intersection(&b));
+/// assert_eq!(&a & &b, a.intersection(&b));
+/// ```
+#[cfg(not(feature = "simd"))]
+impl<N: Counter, const SIZE: usize> BitAnd for Multiset<N, SIZE> {
+    type Output = Multiset<N, SIZE>;
 
-    /// Returns the number of elements in the multiset.
     #[inline]
-    pub fn len() -> usize {
-        SIZE
+    fn bitand(self, rhs: Self) -> Self::Output {
+        self.intersection(&rhs)
     }
+}
+
+#[cfg(not(feature = "simd"))]
+impl<N: Counter, const SIZE: usize> BitAnd<&Multiset<N, SIZE>> for &Multiset<N, SIZE> {
+    type Output = Multiset<N, SIZE>;
 
-    /// Constructs a new Multiset.
-    ///
-    /// This method is equivalent to calling [`Multiset::from`] with an array
-    /// of the correct type.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use utote::Multiset;
-    ///
-    /// let multiset = Multiset::new([1u8, 2, 3, 4]);
-    /// let multiset_from = Multiset::from([1, 2, 3, 4]);
-    /// assert_eq!(multiset, multiset_from);
-    /// ```
     #[inline]
-    pub fn new(data: [N; SIZE]) -> Multiset<N, SIZE> {
-        Multiset { data }
+    fn bitand(self, rhs: &Multiset<N, SIZE>) -> Self::Output {
+        self.intersection(rhs)
     }
+}
 
-    // Safety: The Multiset created by this function must only be used once the
-    // Multiset.data array is guaranteed to be fully initialised.
+/// Sets `self` to the intersection of `self` and `rhs`, equivalent to
+/// [`Multiset::intersection_mut`].
+#[cfg(not(feature = "simd"))]
+impl<N: Counter, const SIZE: usize> BitAndAssign for Multiset<N, SIZE> {
     #[inline]
-    unsafe fn new_uninitialized() -> Self {
-        Multiset {
-            data: mem::MaybeUninit::<[N; SIZE]>::uninit().assume_init(),
-        }
+    fn bitand_assign(&mut self, rhs: Self) {
+        self.intersection_mut(&rhs);
+    }
+}
+
+#[cfg(not(feature = "simd"))]
+impl<N: Counter, const SIZE: usize> BitAndAssign<&Multiset<N, SIZE>> for Multiset<N, SIZE> {
+    #[inline]
+    fn bitand_assign(&mut self, rhs: &Multiset<N, SIZE>) {
+        self.intersection_mut(rhs);
+    }
+}
+
+/// The union of two multisets, equivalent to [`Multiset::union`].
+///
+/// # Examples
+///
+/// ```
+/// use utote::Multiset;
+///
+/// let a = Multiset::from([1u8, 2, 0, 4]);
+/// let b = Multiset::from([0u8, 3, 1, 4]);
+/// assert_eq!(a | b, a.union(&b));
+/// assert_eq!(&a | &b, a.union(&b));
+/// ```
+#[cfg(not(feature = "simd"))]
+impl<N: Counter, const SIZE: usize> BitOr for Multiset<N, SIZE> {
+    type Output = Multiset<N, SIZE>;
+
+    #[inline]
+    fn bitor(self, rhs: Self) -> Self::Output {
+        self.union(&rhs)
+    }
+}
+
+#[cfg(not(feature = "simd"))]
+impl<N: Counter, const SIZE: usize> BitOr<&Multiset<N, SIZE>> for &Multiset<N, SIZE> {
+    type Output = Multiset<N, SIZE>;
+
+    #[inline]
+    fn bitor(self, rhs: &Multiset<N, SIZE>) -> Self::Output {
+        self.union(rhs)
+    }
+}
+
+/// Sets `self` to the union of `self` and `rhs`, equivalent to
+/// [`Multiset::union_mut`].
+#[cfg(not(feature = "simd"))]
+impl<N: Counter, const SIZE: usize> BitOrAssign for Multiset<N, SIZE> {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.union_mut(&rhs);
+    }
+}
+
+#[cfg(not(feature = "simd"))]
+impl<N: Counter, const SIZE: usize> BitOrAssign<&Multiset<N, SIZE>> for Multiset<N, SIZE> {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: &Multiset<N, SIZE>) {
+        self.union_mut(rhs);
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Inherent methods
+////////////////////////////////////////////////////////////////////////////////
+
+impl<N: Counter, const SIZE: usize> Multiset<N, SIZE> {
+    /// The number of elements in the multiset.
+    pub const SIZE: usize = SIZE;
+
+    /// Returns the number of elements in the multiset.
+    #[inline]
+    pub fn len() -> usize {
+        SIZE
+    }
+
+    /// Constructs a new Multiset.
+    ///
+    /// This method is equivalent to calling [`Multiset::from`] with an array
+    /// of the correct type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::new([1u8, 2, 3, 4]);
+    /// let multiset_from = Multiset::from([1, 2, 3, 4]);
+    /// assert_eq!(multiset, multiset_from);
+    /// ```
+    #[inline]
+    pub fn new(data: [N; SIZE]) -> Multiset<N, SIZE> {
+        Multiset { data }
     }
 
     #[inline]
@@ -626,16 +1171,44 @@ impl<N: Counter, const SIZE: usize> Multiset<N, SIZE> {
         N3: Counter,
         F: FnMut(N, N2) -> N3,
     {
-        // Safety: both self and other *must* be the same length as res,
-        // therefore every element of res is guaranteed to be initialised upon
-        // completing the iter_mut.
-        let mut res = unsafe { Multiset::new_uninitialized() };
+        let mut res = Multiset::empty();
         res.iter_mut()
             .zip(self.iter().zip(other.iter()))
             .for_each(|(r, (a, b))| *r = f(*a, *b));
         res
     }
 
+    /// Constructs a Multiset from a slice of element counts.
+    ///
+    /// For a non-panicking alternative use [`TryFrom`].
+    ///
+    /// # Panics
+    /// If the length of the slice is not equal to the size of the Multiset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::TryFrom;
+    /// use utote::{Multiset, MultisetError};
+    ///
+    /// let counts = vec![1u8, 2, 3];
+    /// let multiset = Multiset::<u8, 3>::from_slice(&counts);
+    /// assert_eq!(multiset, Multiset::from([1, 2, 3]));
+    ///
+    /// let wrong_length = Multiset::<u8, 4>::try_from(&counts[..]);
+    /// assert_eq!(
+    ///     wrong_length,
+    ///     Err(MultisetError::WrongLength { expected: 4, got: 3 })
+    /// );
+    /// ```
+    #[inline]
+    pub fn from_slice(slice: &[N]) -> Self {
+        match Multiset::try_from(slice) {
+            Ok(res) => res,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
     /// Returns a Multiset of the given array size with all element counts set
     /// to zero.
     ///
@@ -678,6 +1251,9 @@ impl<N: Counter, const SIZE: usize> Multiset<N, SIZE> {
     /// Constructs a Multiset from an iterator of elements in the multiset,
     /// incrementing the count of each element as it occurs in the iterator.
     ///
+    /// The iterator may yield elements either by value or by reference. Counts
+    /// saturate at the maximum value of the counter.
+    ///
     /// # Panics
     /// If any item in the iterator is out of bounds of the Multiset.
     ///
@@ -687,21 +1263,141 @@ impl<N: Counter, const SIZE: usize> Multiset<N, SIZE> {
     /// use utote::Multiset;
     ///
     /// let multiset = Multiset::<u8, 4>::from_elements(&[1, 1, 0, 2, 2, 2]);
-    /// assert_eq!(multiset, Multiset::from([1, 2, 3, 0]))
+    /// assert_eq!(multiset, Multiset::from([1, 2, 3, 0]));
+    ///
+    /// let rolls = vec![3, 0, 3, 1];
+    /// let multiset = Multiset::<u8, 4>::from_elements(rolls);
+    /// assert_eq!(multiset, Multiset::from([1, 1, 0, 2]));
     /// ```
     #[inline]
-    pub fn from_elements<'a, I>(elements: I) -> Self
+    pub fn from_elements<I>(elements: I) -> Self
     where
-        I: IntoIterator<Item = &'a usize>,
+        I: IntoIterator,
+        I::Item: Borrow<usize>,
     {
-        elements.into_iter().fold(Multiset::empty(), |mut acc, &e| {
-            if e >= SIZE {
-                panic!("element: {} not in Multiset (element > SIZE)", e)
-            }
-            // Safety: Above condition ensures `e` is not out of bounds.
-            unsafe { *acc.get_unchecked_mut(e) += N::one() };
-            acc
-        })
+        let mut res = Multiset::empty();
+        res.extend(elements.into_iter().map(|e| *e.borrow()));
+        res
+    }
+
+    /// Constructs a Multiset from an iterator of counts, requiring that the
+    /// iterator yields exactly `SIZE` items.
+    ///
+    /// Unlike collecting with [`FromIterator`], this does not pad short
+    /// iterators with zeroes or ignore extra items.
+    ///
+    /// # Errors
+    /// Returns [`MultisetError::WrongIterLength`] if the iterator yields fewer
+    /// or more than `SIZE` items. At most `SIZE + 1` items are consumed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::{Multiset, MultisetError};
+    ///
+    /// let multiset = Multiset::<u8, 3>::try_from_iter(vec![1, 2, 3]);
+    /// assert_eq!(multiset, Ok(Multiset::from([1, 2, 3])));
+    ///
+    /// let too_short = Multiset::<u8, 3>::try_from_iter(vec![1, 2]);
+    /// assert_eq!(
+    ///     too_short,
+    ///     Err(MultisetError::WrongIterLength { expected: 3, consumed: 2 })
+    /// );
+    /// ```
+    #[inline]
+    pub fn try_from_iter<I>(iter: I) -> std::result::Result<Self, MultisetError>
+    where
+        I: IntoIterator<Item = N>,
+    {
+        let mut res: Self = Multiset::empty();
+        let mut it = iter.into_iter();
+        let mut consumed = 0;
+        for (r, e) in res.iter_mut().zip(&mut it) {
+            *r = e;
+            consumed += 1;
+        }
+        if consumed == SIZE && it.next().is_none() {
+            Ok(res)
+        } else {
+            Err(MultisetError::WrongIterLength {
+                expected: SIZE,
+                consumed: if consumed == SIZE { SIZE + 1 } else { consumed },
+            })
+        }
+    }
+
+    /// Constructs a Multiset from an iterator of `(element, count)` pairs,
+    /// adding each count to the given element.
+    ///
+    /// Counts for repeated elements are summed, saturating at the maximum
+    /// value of the counter. This is the fallible equivalent of collecting
+    /// pairs with [`FromIterator`].
+    ///
+    /// # Errors
+    /// Returns [`MultisetError::ElementOutOfBounds`] if any element in the
+    /// iterator is out of bounds of the Multiset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::{Multiset, MultisetError};
+    ///
+    /// let multiset = Multiset::<u8, 4>::try_from_pairs(vec![(0, 2), (3, 1), (0, 1)]);
+    /// assert_eq!(multiset, Ok(Multiset::from([3, 0, 0, 1])));
+    ///
+    /// let out_of_bounds = Multiset::<u8, 4>::try_from_pairs(vec![(4, 1)]);
+    /// assert_eq!(
+    ///     out_of_bounds,
+    ///     Err(MultisetError::ElementOutOfBounds { elem: 4, size: 4 })
+    /// );
+    /// ```
+    #[inline]
+    pub fn try_from_pairs<I>(pairs: I) -> std::result::Result<Self, MultisetError>
+    where
+        I: IntoIterator<Item = (usize, N)>,
+    {
+        let mut res: Self = Multiset::empty();
+        for (elem, count) in pairs {
+            let c = res
+                .get_mut(elem)
+                .ok_or(MultisetError::ElementOutOfBounds { elem, size: SIZE })?;
+            *c = c.saturating_add(&count);
+        }
+        Ok(res)
+    }
+
+    /// Increments the count of each element yielded by the iterator by one,
+    /// saturating at the maximum value of the counter, without doing bounds
+    /// checking.
+    ///
+    /// For a safe alternative see [`Extend`].
+    ///
+    /// # Safety
+    ///
+    /// Calling this method with an iterator which yields an out-of-bounds
+    /// element is *[undefined behavior]*.
+    ///
+    /// [undefined behavior]: https://doc.rust-lang.org/reference/behavior-considered-undefined.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let mut multiset = Multiset::<u8, 4>::empty();
+    /// unsafe { multiset.extend_unchecked(vec![0, 2, 2, 3]) };
+    /// assert_eq!(multiset, Multiset::from([1, 0, 2, 1]));
+    /// ```
+    #[inline]
+    pub unsafe fn extend_unchecked<I>(&mut self, elements: I)
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        for elem in elements {
+            debug_assert!(elem < SIZE, "element: {} not in Multiset", elem);
+            let c = self.get_unchecked_mut(elem);
+            *c = c.saturating_add(&N::one());
+        }
     }
 
     /// Return an [Iter](`std::slice::Iter`) of the element counts in the
@@ -718,6 +1414,142 @@ impl<N: Counter, const SIZE: usize> Multiset<N, SIZE> {
         self.data.iter_mut()
     }
 
+    /// Consumes the multiset, returning the array of element counts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([1u8, 2, 3, 4]);
+    /// assert_eq!(multiset.to_array(), [1, 2, 3, 4]);
+    /// ```
+    #[inline]
+    pub fn to_array(self) -> [N; SIZE] {
+        self.data
+    }
+
+    /// Returns a slice containing the element counts of the multiset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([1u8, 2, 3, 4]);
+    /// assert_eq!(multiset.as_slice(), &[1, 2, 3, 4]);
+    /// ```
+    #[inline]
+    pub fn as_slice(&self) -> &[N] {
+        &self.data
+    }
+
+    /// Returns a mutable slice containing the element counts of the multiset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let mut multiset = Multiset::from([1u8, 2, 3, 4]);
+    /// multiset.as_mut_slice()[0] = 5;
+    /// assert_eq!(multiset, Multiset::from([5, 2, 3, 4]));
+    /// ```
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [N] {
+        &mut self.data
+    }
+
+    /// Returns the non-zero element counts of the multiset as a comma separated
+    /// list of `element:count` pairs, which can be parsed with [`FromStr`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([3u8, 0, 0, 0, 1]);
+    /// assert_eq!(multiset.to_sparse_string(), "0:3,4:1");
+    /// ```
+    pub fn to_sparse_string(&self) -> String {
+        let mut res = String::new();
+        for (elem, count) in self.iter().enumerate().filter(|(_, c)| c > &&N::zero()) {
+            let sep = if res.is_empty() { "" } else { "," };
+            // Writing to a String cannot fail.
+            write!(res, "{}{}:{}", sep, elem, count).unwrap();
+        }
+        res
+    }
+
+    /// Returns a `HashMap` from element to count, containing only the elements
+    /// with a non-zero count.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([3u32, 0, 0, 1]);
+    /// let map = multiset.to_counts_map();
+    /// assert_eq!(map.len(), 2);
+    /// assert_eq!(map.get(&0), Some(&3));
+    /// assert_eq!(map.get(&1), None);
+    /// ```
+    pub fn to_counts_map(&self) -> HashMap<usize, N> {
+        self.iter()
+            .enumerate()
+            .filter(|(_, count)| count > &&N::zero())
+            .map(|(elem, count)| (elem, *count))
+            .collect()
+    }
+
+    /// Returns a `BTreeMap` from element to count, containing only the
+    /// elements with a non-zero count.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([3u32, 0, 0, 1]);
+    /// let map = multiset.to_counts_btree_map();
+    /// assert_eq!(map.into_iter().collect::<Vec<_>>(), vec![(0, 3), (3, 1)]);
+    /// ```
+    pub fn to_counts_btree_map(&self) -> BTreeMap<usize, N> {
+        self.iter()
+            .enumerate()
+            .filter(|(_, count)| count > &&N::zero())
+            .map(|(elem, count)| (elem, *count))
+            .collect()
+    }
+
+    /// Constructs a Multiset from a map of element to count, such as a
+    /// reference to a `HashMap<usize, N>` or `BTreeMap<usize, N>`. Elements
+    /// which are not in the map have a count of zero.
+    ///
+    /// # Errors
+    /// Returns [`MultisetError::ElementOutOfBounds`] if any element in the map
+    /// is out of bounds of the Multiset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use utote::Multiset;
+    ///
+    /// let mut map = HashMap::new();
+    /// map.insert(2, 5u16);
+    /// let multiset = Multiset::<u16, 3>::try_from_counts_map(&map);
+    /// assert_eq!(multiset, Ok(Multiset::from([0, 0, 5])));
+    /// ```
+    pub fn try_from_counts_map<'a, M>(map: M) -> std::result::Result<Self, MultisetError>
+    where
+        M: IntoIterator<Item = (&'a usize, &'a N)>,
+        N: 'a,
+    {
+        Multiset::try_from_pairs(map.into_iter().map(|(&elem, &count)| (elem, count)))
+    }
+
     /// Sets all element counts in the multiset to zero.
     ///
     /// # Examples
@@ -1016,6 +1848,7 @@ impl<N: Counter, const SIZE: usize> Multiset<N, SIZE> {
     /// ```
     #[cfg(not(feature = "simd"))]
     #[inline]
+    #[must_use]
     pub fn intersection(&self, other: &Self) -> Self {
         self.zip_map(other, |e1, e2| e1.min(e2))
     }
@@ -1038,203 +1871,221 @@ impl<N: Counter, const SIZE: usize> Multiset<N, SIZE> {
     /// ```
     #[cfg(not(feature = "simd"))]
     #[inline]
+    #[must_use]
     pub fn union(&self, other: &Self) -> Self {
         self.zip_map(other, |e1, e2| e1.max(e2))
     }
 
-    /// Returns a multiset which is the difference of `self` and `other`.
+    /// Sets `self` to the intersection of `self` and `other`.
     ///
-    /// The difference of this multiset and another is the count of elements in
-    /// this and those that occur in this and other without repeats. `A`
-    /// difference `B` is the multiset `C` where `C[i] == min(A[i], B[i])` if
-    /// `A[i] > 0` and `B[i] > 0`, otherwise `A[i]` for all `i` in `C`.
+    /// This is the in-place equivalent of [`Multiset::intersection`].
     ///
     /// # Examples
     ///
     /// ```
     /// use utote::Multiset;
     ///
-    /// let a = Multiset::from([1u8, 2, 0, 0]);
+    /// let mut a = Multiset::from([1u8, 2, 0, 0]);
     /// let b = Multiset::from([0, 1, 3, 0]);
-    /// let c = Multiset::from([1, 1, 0, 0]);
-    /// assert_eq!(a.difference(&b), c);
+    /// a.intersection_mut(&b);
+    /// assert_eq!(a, Multiset::from([0, 1, 0, 0]));
     /// ```
+    #[cfg(not(feature = "simd"))]
     #[inline]
-    pub fn difference(&self, other: &Self) -> Self {
-        self.zip_map(other, |e1, e2| {
-            if e1 > N::zero() && e2 > N::zero() {
-                e1.min(e2)
-            } else {
-                e1
-            }
-        })
+    pub fn intersection_mut(&mut self, other: &Self) {
+        self.iter_mut()
+            .zip(other.iter())
+            .for_each(|(a, b)| *a = (*a).min(*b));
     }
 
-    /// Returns a multiset which is the symmetric_difference of `self` and
-    /// `other`.
+    /// Sets `self` to the union of `self` and `other`.
     ///
-    /// The symmetric_difference of two multisets is the count of elements that
-    /// occur in either, and the count, without repeats that occurs in both.
-    /// `A` symmetric_difference `B` is the multiset `C` where
-    /// `C[i] == min(A[i], B[i])` if `A[i] > 0` and `B[i] > 0`, otherwise
-    /// `max(A[i], B[i])` for all `i` in `C`.
+    /// This is the in-place equivalent of [`Multiset::union`].
     ///
     /// # Examples
     ///
     /// ```
     /// use utote::Multiset;
     ///
-    /// let a = Multiset::from([1u8, 2, 0, 0]);
+    /// let mut a = Multiset::from([1u8, 2, 0, 0]);
     /// let b = Multiset::from([0, 1, 3, 0]);
-    /// let c = Multiset::from([1, 1, 3, 0]);
-    /// assert_eq!(a.symmetric_difference(&b), c);
+    /// a.union_mut(&b);
+    /// assert_eq!(a, Multiset::from([1, 2, 3, 0]));
     /// ```
+    #[cfg(not(feature = "simd"))]
     #[inline]
-    pub fn symmetric_difference(&self, other: &Self) -> Self {
-        self.zip_map(other, |e1, e2| {
-            if e1 > N::zero() && e2 > N::zero() {
-                e1.min(e2)
-            } else {
-                e1.max(e2)
-            }
-        })
+    pub fn union_mut(&mut self, other: &Self) {
+        self.iter_mut()
+            .zip(other.iter())
+            .for_each(|(a, b)| *a = (*a).max(*b));
     }
 
-    /// Returns the number of elements whose count is non-zero.
+    /// Writes the intersection of `self` and `other` into `out`.
+    ///
+    /// This is equivalent to `*out = self.intersection(other)`, for reusing
+    /// an existing multiset as the output. To store the result in `self`,
+    /// use [`Multiset::intersection_mut`].
     ///
     /// # Examples
     ///
     /// ```
     /// use utote::Multiset;
     ///
-    /// let multiset = Multiset::from([1u8, 0, 0, 0]);
-    /// assert_eq!(multiset.count_non_zero(), 1);
+    /// let a = Multiset::from([1u8, 2, 0, 0]);
+    /// let b = Multiset::from([0, 1, 3, 0]);
+    /// let mut out = Multiset::empty();
+    /// a.intersection_into(&b, &mut out);
+    /// assert_eq!(out, Multiset::from([0, 1, 0, 0]));
     /// ```
     #[cfg(not(feature = "simd"))]
     #[inline]
-    pub fn count_non_zero(&self) -> usize {
-        self.iter().fold(0, |acc, &elem| {
-            acc + <N as AsPrimitive<usize>>::as_(elem.min(N::one()))
-        })
+    pub fn intersection_into(&self, other: &Self, out: &mut Self) {
+        out.iter_mut()
+            .zip(self.iter().zip(other.iter()))
+            .for_each(|(r, (a, b))| *r = (*a).min(*b));
     }
 
-    /// Returns the number of elements whose count is zero.
+    /// Writes the union of `self` and `other` into `out`.
+    ///
+    /// This is equivalent to `*out = self.union(other)`, for reusing an
+    /// existing multiset as the output. To store the result in `self`, use
+    /// [`Multiset::union_mut`].
     ///
     /// # Examples
     ///
     /// ```
     /// use utote::Multiset;
     ///
-    /// let multiset = Multiset::from([1u8, 0, 0, 0]);
-    /// assert_eq!(multiset.count_zero(), 3);
+    /// let a = Multiset::from([1u8, 2, 0, 0]);
+    /// let b = Multiset::from([0, 1, 3, 0]);
+    /// let mut out = Multiset::empty();
+    /// a.union_into(&b, &mut out);
+    /// assert_eq!(out, Multiset::from([1, 2, 3, 0]));
     /// ```
     #[cfg(not(feature = "simd"))]
     #[inline]
-    pub fn count_zero(&self) -> usize {
-        SIZE - self.count_non_zero()
+    pub fn union_into(&self, other: &Self, out: &mut Self) {
+        out.iter_mut()
+            .zip(self.iter().zip(other.iter()))
+            .for_each(|(r, (a, b))| *r = (*a).max(*b));
     }
 
-    /// Returns `true` if only one element in the multiset has a non-zero
-    /// count.
+    /// Returns the intersection of all multisets in `sets`, or `None` if
+    /// `sets` is empty.
+    ///
+    /// The result is accumulated in place, without intermediate multisets.
     ///
     /// # Examples
     ///
     /// ```
     /// use utote::Multiset;
     ///
-    /// let multiset = Multiset::from([0u8, 5, 0, 0]);
-    /// assert_eq!(multiset.is_singleton(), true);
+    /// let sets = [
+    ///     Multiset::from([1u8, 2, 3, 4]),
+    ///     Multiset::from([4u8, 3, 2, 1]),
+    ///     Multiset::from([2u8, 2, 2, 0]),
+    /// ];
+    /// assert_eq!(Multiset::intersection_all(&sets), Some(Multiset::from([1, 2, 2, 0])));
+    /// assert_eq!(Multiset::<u8, 4>::intersection_all(&[]), None);
     /// ```
     #[cfg(not(feature = "simd"))]
     #[inline]
-    pub fn is_singleton(&self) -> bool {
-        self.count_non_zero() == 1
+    #[must_use]
+    pub fn intersection_all(sets: &[Self]) -> Option<Self> {
+        let (first, rest) = sets.split_first()?;
+        let mut res = *first;
+        rest.iter().for_each(|set| res.intersection_mut(set));
+        Some(res)
     }
 
-    /// Returns `true` if `self` is disjoint to `other`.
+    /// Returns the union of all multisets in `sets`, or `None` if `sets` is
+    /// empty.
     ///
-    /// Multiset `A` is disjoint to `B` if `A` has no elements in common with
-    /// `B`. `A` is disjoint `B` if `min(A[i], B[i]) == 0` for all `i` in
-    /// `A`.
+    /// The result is accumulated in place, without intermediate multisets.
     ///
     /// # Examples
     ///
     /// ```
     /// use utote::Multiset;
     ///
-    /// let a = Multiset::from([1u8, 2, 0, 0]);
-    /// assert_eq!(a.is_disjoint(&a), false);
-    ///
-    /// let b = Multiset::from([0, 0, 3, 4]);
-    /// assert_eq!(a.is_disjoint(&b), true);
-    ///
-    /// let c = Multiset::from([0, 1, 1, 0]);
-    /// assert_eq!(a.is_disjoint(&c), false);
+    /// let sets = [
+    ///     Multiset::from([1u8, 2, 3, 4]),
+    ///     Multiset::from([4u8, 3, 2, 1]),
+    ///     Multiset::from([2u8, 2, 2, 0]),
+    /// ];
+    /// assert_eq!(Multiset::union_all(&sets), Some(Multiset::from([4, 3, 3, 4])));
+    /// assert_eq!(Multiset::<u8, 4>::union_all(&[]), None);
     /// ```
     #[cfg(not(feature = "simd"))]
     #[inline]
-    pub fn is_disjoint(&self, other: &Self) -> bool {
-        self.iter()
-            .zip(other.iter())
-            .all(|(a, b)| a.min(b) == &N::zero())
+    #[must_use]
+    pub fn union_all(sets: &[Self]) -> Option<Self> {
+        let (first, rest) = sets.split_first()?;
+        let mut res = *first;
+        rest.iter().for_each(|set| res.union_mut(set));
+        Some(res)
     }
 
-    /// Returns `true` if `self` is a subset of `other`.
+    /// Returns a multiset which is the difference of `self` and `other`.
     ///
-    /// Multiset `A` is a subset of `B` if all the element counts in `A` are
-    /// less than or equal to the counts in `B`. `A` is subset `B` if
-    /// `A[i] <= B[i]` for all `i` in `A`.
+    /// The difference of this multiset and another is the count of elements in
+    /// this which are not matched by an occurrence in other. `A` difference
+    /// `B` is the multiset `C` where `C[i] == A[i] - B[i]` if `A[i] > B[i]`,
+    /// otherwise `0`, for all `i` in `C`. Counts never wrap below zero.
     ///
     /// # Examples
     ///
     /// ```
     /// use utote::Multiset;
     ///
-    /// let a = Multiset::from([1u64, 2, 0, 0]);
-    /// assert_eq!(a.is_subset(&a), true);
-    ///
-    /// let b = Multiset::from([1, 3, 0, 0]);
-    /// assert_eq!(a.is_subset(&b), true);
-    ///
-    /// assert_eq!(a.is_subset(&Multiset::empty()), false);
+    /// let a = Multiset::from([1u8, 2, 0, 0]);
+    /// let b = Multiset::from([0, 1, 3, 0]);
+    /// let c = Multiset::from([1, 1, 0, 0]);
+    /// assert_eq!(a.difference(&b), c);
     /// ```
     #[cfg(not(feature = "simd"))]
     #[inline]
-    pub fn is_subset(&self, other: &Self) -> bool {
-        self.iter().zip(other.iter()).all(|(a, b)| a <= b)
+    #[must_use]
+    pub fn difference(&self, other: &Self) -> Self {
+        self.zip_map(other, |e1, e2| e1.saturating_sub(&e2))
     }
 
-    /// Returns `true` if `self` is a superset of `other`.
+    /// Returns the complement of `self` relative to `universe`.
     ///
-    /// Multiset `A` is a subset of `B` if all the element counts in `A` are
-    /// greater than or equal to the counts in `B`. `A` is superset `B` if
-    /// `A[i] >= B[i]` for all `i` in `A`.
+    /// The complement is the multiset `C` where `C[i] == U[i] - A[i]` for all
+    /// `i` in `C`, i.e. `universe.difference(self)`. `self` is expected to be a
+    /// subset of `universe`; counts of `self` which exceed those of `universe`
+    /// saturate to zero.
+    ///
+    /// # Panics
+    /// In debug builds, panics if `self` is not a subset of `universe`.
     ///
     /// # Examples
     ///
     /// ```
     /// use utote::Multiset;
     ///
-    /// let a = Multiset::from([1u8, 2, 0, 0]);
-    /// assert_eq!(a.is_superset(&a), true);
-    ///
-    /// let b = Multiset::from([1, 1, 0, 0]);
-    /// assert_eq!(a.is_superset(&b), true);
-    ///
-    /// assert_eq!(a.is_superset(&Multiset::empty()), true);
+    /// let domain = Multiset::from([3u8, 1, 2, 0]);
+    /// let used = Multiset::from([1u8, 1, 0, 0]);
+    /// assert_eq!(used.complement(&domain), Multiset::from([2, 0, 2, 0]));
     /// ```
-    #[cfg(not(feature = "simd"))]
     #[inline]
-    pub fn is_superset(&self, other: &Self) -> bool {
-        self.iter().zip(other.iter()).all(|(a, b)| a >= b)
+    #[must_use]
+    pub fn complement(&self, universe: &Self) -> Self {
+        debug_assert!(
+            self.iter().zip(universe.iter()).all(|(a, u)| a <= u),
+            "Multiset is not a subset of universe"
+        );
+        universe.zip_map(self, |u, a| u.saturating_sub(&a))
     }
 
-    /// Returns `true` if `self` is a proper subset of `other`.
+    /// Returns a multiset which is the symmetric_difference of `self` and
+    /// `other`.
     ///
-    /// Multiset `A` is a proper subset of `B` if `A` is a subset of `B` and at
-    /// least one element count of `A` is less than that element count in `B`.
-    /// `A` is proper subset `B` if `A[i] <= B[i]` for all `i` in `A` and there
-    /// exists `j` such that `A[j] < B[j]`.
+    /// The symmetric_difference of two multisets is the count of elements that
+    /// occur in either, but are not matched by an occurrence in the other. `A`
+    /// symmetric_difference `B` is the multiset `C` where
+    /// `C[i] == max(A[i], B[i]) - min(A[i], B[i])` for all `i` in `C`.
     ///
     /// # Examples
     ///
@@ -1242,858 +2093,5405 @@ impl<N: Counter, const SIZE: usize> Multiset<N, SIZE> {
     /// use utote::Multiset;
     ///
     /// let a = Multiset::from([1u8, 2, 0, 0]);
-    /// assert_eq!(a.is_proper_subset(&a), false);
-    ///
-    /// let b = Multiset::from([1, 3, 0, 0]);
-    /// assert_eq!(a.is_proper_subset(&b), true);
+    /// let b = Multiset::from([0, 1, 3, 0]);
+    /// let c = Multiset::from([1, 1, 3, 0]);
+    /// assert_eq!(a.symmetric_difference(&b), c);
     /// ```
     #[cfg(not(feature = "simd"))]
     #[inline]
-    pub fn is_proper_subset(&self, other: &Self) -> bool {
-        self != other && self.is_subset(other)
+    #[must_use]
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        self.zip_map(other, |e1, e2| e1.max(e2) - e1.min(e2))
     }
 
-    /// Returns `true` if `self` is a proper superset of `other`.
+    /// Returns the element-wise absolute difference of the counts of `self`
+    /// and `other`.
     ///
-    /// Multiset `A` is a proper superset of `B` if `A` is a superset of `B`
-    /// and at least one element count of `A` is greater than that element
-    /// count in `B`. `A` is proper superset `B` if `A[i] >= B[i]` for all `i`
-    /// in `A` and there exists `j` such that `A[j] > B[j]`.
+    /// This is an alias of [`Multiset::symmetric_difference`], the multiset
+    /// `C` where `C[i] == max(A[i], B[i]) - min(A[i], B[i])` for all `i` in
+    /// `C`.
     ///
     /// # Examples
     ///
     /// ```
     /// use utote::Multiset;
     ///
-    /// let a = Multiset::from([1u8, 2, 0, 0]);
-    /// assert_eq!(a.is_proper_superset(&a), false);
-    ///
-    /// let b = Multiset::from([1, 1, 0, 0]);
-    /// assert_eq!(a.is_proper_superset(&b), true);
+    /// let a = Multiset::from([1u8, 2, 0, 5]);
+    /// let b = Multiset::from([3u8, 2, 1, 0]);
+    /// assert_eq!(a.abs_diff(&b), Multiset::from([2, 0, 1, 5]));
     /// ```
     #[cfg(not(feature = "simd"))]
     #[inline]
-    pub fn is_proper_superset(&self, other: &Self) -> bool {
-        self != other && self.is_superset(other)
+    #[must_use]
+    pub fn abs_diff(&self, other: &Self) -> Self {
+        self.symmetric_difference(other)
     }
 
-    /// Returns `true` if any element count in `self` is less than that element
-    /// count in `other`.
+    /// Returns a multiset which is the sum of `self` and `other`.
     ///
-    /// `A` is any lesser `B` if there exists some `i` such that `A[i] < B[i]`.
+    /// The sum of two multisets is the count of elements that occur in
+    /// either, adding the counts together. `A` sum `B` is the multiset `C`
+    /// where `C[i] == A[i] + B[i]` for all `i` in `C`. Counts saturate at the
+    /// maximum value of the counter rather than wrapping.
     ///
     /// # Examples
     ///
     /// ```
     /// use utote::Multiset;
     ///
-    /// let a = Multiset::from([1u8, 2, 4, 0]);
-    /// assert_eq!(a.is_any_lesser(&a), false);
-    ///
-    /// let b = Multiset::from([1, 3, 0, 0]);
-    /// assert_eq!(a.is_any_lesser(&b), true);
+    /// let a = Multiset::from([1u8, 2, 0, 250]);
+    /// let b = Multiset::from([0, 1, 3, 10]);
+    /// let c = Multiset::from([1, 3, 3, 255]);
+    /// assert_eq!(a.sum(&b), c);
     /// ```
     #[cfg(not(feature = "simd"))]
     #[inline]
-    pub fn is_any_lesser(&self, other: &Self) -> bool {
-        self.iter().zip(other.iter()).any(|(a, b)| a < b)
+    #[must_use]
+    pub fn sum(&self, other: &Self) -> Self {
+        self.zip_map(other, |e1, e2| e1.saturating_add(&e2))
     }
 
-    /// Returns `true` if any element count in `self` is greater than that
-    /// element count in `other`.
+    /// Returns a multiset which is the sum of `self` and `other`, or `None` if
+    /// any count would overflow the counter.
     ///
-    /// `A` is any greater `B` if there exists some `i` such that
-    /// `A[i] > B[i]`.
+    /// See [`Multiset::sum`] for a saturating alternative.
     ///
     /// # Examples
     ///
     /// ```
     /// use utote::Multiset;
     ///
-    /// let a = Multiset::from([1u8, 2, 0, 0]);
-    /// assert_eq!(a.is_any_greater(&a), false);
+    /// let a = Multiset::from([1u8, 2, 0, 250]);
+    /// let b = Multiset::from([0, 1, 3, 5]);
+    /// assert_eq!(a.checked_sum(&b), Some(Multiset::from([1, 3, 3, 255])));
     ///
-    /// let b = Multiset::from([1, 1, 4, 0]);
-    /// assert_eq!(a.is_any_greater(&b), true);
+    /// let c = Multiset::from([0, 0, 0, 6]);
+    /// assert_eq!(a.checked_sum(&c), None);
     /// ```
     #[cfg(not(feature = "simd"))]
     #[inline]
-    pub fn is_any_greater(&self, other: &Self) -> bool {
-        self.iter().zip(other.iter()).any(|(a, b)| a > b)
+    #[must_use]
+    pub fn checked_sum(&self, other: &Self) -> Option<Self> {
+        let mut res = Multiset::empty();
+        for (r, (a, b)) in res.iter_mut().zip(self.iter().zip(other.iter())) {
+            *r = a.checked_add(b)?;
+        }
+        Some(res)
     }
 
-    /// Returns `true` if all elements have a count of zero.
+    /// Adds each of the borrowed multisets into `self` in place. Counts
+    /// saturate at the maximum value of the counter rather than wrapping.
     ///
     /// # Examples
     ///
     /// ```
     /// use utote::Multiset;
     ///
-    /// let multiset = Multiset::from([0u8, 0, 0, 0]);
-    /// assert_eq!(multiset.is_empty(), true);
-    /// assert_eq!(Multiset::<u8, 4>::empty().is_empty(), true);
+    /// let shards = vec![
+    ///     Multiset::from([1u8, 0, 2]),
+    ///     Multiset::from([0, 3, 1]),
+    ///     Multiset::from([2, 0, 252]),
+    /// ];
+    /// let mut multiset = Multiset::from([1, 1, 1]);
+    /// multiset.merge_from(&shards);
+    /// assert_eq!(multiset, Multiset::from([4, 4, 255]));
     /// ```
     #[inline]
-    pub fn is_empty(&self) -> bool {
-        self.data == [N::zero(); SIZE]
+    pub fn merge_from<'a, I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = &'a Self>,
+        N: 'a,
+    {
+        for set in iter {
+            *self += set;
+        }
     }
 
-    /// The total or cardinality of a multiset is the sum of all element
-    /// counts.
-    ///
-    /// This function converts counts to `usize` to try and avoid overflows.
+    /// Returns a multiset where each count is the minimum of the count in
+    /// `self` and `n`.
     ///
     /// # Examples
     ///
     /// ```
     /// use utote::Multiset;
     ///
-    /// let multiset = Multiset::from([1u8, 2, 3, 4]);
-    /// assert_eq!(multiset.total(), 10);
+    /// let multiset = Multiset::from([1u8, 5, 0, 2]);
+    /// assert_eq!(multiset.min_scalar(2), Multiset::from([1, 2, 0, 2]));
     /// ```
     #[cfg(not(feature = "simd"))]
     #[inline]
-    pub fn total(&self) -> usize {
-        self.iter()
-            .map(|e| <N as AsPrimitive<usize>>::as_(*e))
-            .sum()
+    #[must_use]
+    pub fn min_scalar(&self, n: N) -> Self {
+        self.iter().map(|e| (*e).min(n)).collect()
     }
 
-    /// Returns a tuple containing the element and a reference to the largest
-    /// count in the multiset.
+    /// Returns a multiset where each count is the maximum of the count in
+    /// `self` and `n`.
     ///
     /// # Examples
     ///
     /// ```
     /// use utote::Multiset;
     ///
-    /// let multiset = Multiset::from([2u16, 0, 5, 3]);
-    /// assert_eq!(multiset.elem_count_max(), (2, &5));
+    /// let multiset = Multiset::from([1u8, 5, 0, 2]);
+    /// assert_eq!(multiset.max_scalar(2), Multiset::from([2, 5, 2, 2]));
     /// ```
+    #[cfg(not(feature = "simd"))]
     #[inline]
-    pub fn elem_count_max(&self) -> (usize, &N) {
-        // iter cannot be empty, so it's fine to unwrap
-        self.iter()
-            .enumerate()
-            .max_by_key(|(_, count)| *count)
-            .unwrap()
+    #[must_use]
+    pub fn max_scalar(&self, n: N) -> Self {
+        self.iter().map(|e| (*e).max(n)).collect()
     }
 
-    /// Returns the element with the largest count in the multiset.
+    /// Returns the support of the multiset, where every non-zero count is
+    /// replaced with one.
     ///
     /// # Examples
     ///
     /// ```
     /// use utote::Multiset;
     ///
-    /// let multiset = Multiset::from([2u8, 0, 5, 3]);
-    /// assert_eq!(multiset.elem_max(), 2);
+    /// let multiset = Multiset::from([1u8, 5, 0, 2]);
+    /// assert_eq!(multiset.to_support(), Multiset::from([1, 1, 0, 1]));
     /// ```
+    #[cfg(not(feature = "simd"))]
     #[inline]
-    pub fn elem_max(&self) -> usize {
-        self.elem_count_max().0
+    #[must_use]
+    pub fn to_support(&self) -> Self {
+        self.min_scalar(N::one())
     }
 
-    /// Returns a reference to the largest count in the multiset.
+    /// Returns a multiset with the counts of `self` for elements which are not
+    /// in `other`, and zero for elements which are.
+    ///
+    /// Only whether an element is in `other` matters, not its count.
     ///
     /// # Examples
     ///
     /// ```
     /// use utote::Multiset;
     ///
-    /// let multiset = Multiset::from([2u8, 0, 5, 3]);
-    /// assert_eq!(multiset.count_max(), &5);
+    /// let a = Multiset::from([1u8, 5, 0, 2]);
+    /// let b = Multiset::from([0u8, 1, 9, 0]);
+    /// assert_eq!(a.subtract_support(&b), Multiset::from([1, 0, 0, 2]));
     /// ```
+    #[cfg(not(feature = "simd"))]
     #[inline]
-    pub fn count_max(&self) -> &N {
-        // iter cannot be empty, so it's fine to unwrap
-        self.iter().max().unwrap()
+    #[must_use]
+    pub fn subtract_support(&self, other: &Self) -> Self {
+        self.zip_map(other, |e1, e2| if e2 == N::zero() { e1 } else { N::zero() })
     }
 
-    /// Returns a tuple containing the element and a reference to the smallest
-    /// count in the multiset.
+    /// Returns a multiset with the counts of `self` for elements which are in
+    /// `other`, and zero for elements which are not.
+    ///
+    /// Only whether an element is in `other` matters, not its count.
     ///
     /// # Examples
     ///
     /// ```
     /// use utote::Multiset;
     ///
-    /// let multiset = Multiset::from([2u8, 0, 5, 3]);
-    /// assert_eq!(multiset.elem_count_min(), (1, &0));
+    /// let a = Multiset::from([1u8, 5, 0, 2]);
+    /// let b = Multiset::from([0u8, 1, 9, 0]);
+    /// assert_eq!(a.retain_support(&b), Multiset::from([0, 5, 0, 0]));
     /// ```
+    #[cfg(not(feature = "simd"))]
     #[inline]
-    pub fn elem_count_min(&self) -> (usize, &N) {
-        // iter cannot be empty, so it's fine to unwrap
-        self.iter()
-            .enumerate()
-            .min_by_key(|(_, count)| *count)
-            .unwrap()
+    #[must_use]
+    pub fn retain_support(&self, other: &Self) -> Self {
+        self.zip_map(other, |e1, e2| if e2 == N::zero() { N::zero() } else { e1 })
     }
 
-    /// Returns the element with the smallest count in the multiset.
+    /// Returns the total of the intersection of `self` and `other`, without
+    /// constructing the intersection.
+    ///
+    /// This is equal to `self.intersection(other).total_wide()`.
     ///
     /// # Examples
     ///
     /// ```
     /// use utote::Multiset;
     ///
-    /// let multiset = Multiset::from([2u8, 0, 5, 3]);
-    /// assert_eq!(multiset.elem_min(), 1);
+    /// let a = Multiset::from([1u8, 2, 0, 4]);
+    /// let b = Multiset::from([0u8, 3, 1, 4]);
+    /// assert_eq!(a.count_intersection(&b), 6);
     /// ```
+    #[cfg(not(feature = "simd"))]
     #[inline]
-    pub fn elem_min(&self) -> usize {
-        self.elem_count_min().0
+    pub fn count_intersection(&self, other: &Self) -> u128 {
+        self.iter()
+            .zip(other.iter())
+            .map(|(a, b)| <N as AsPrimitive<u128>>::as_(*a.min(b)))
+            .sum()
     }
 
-    /// Returns a reference to the smallest count in the multiset.
+    /// Returns the total of the union of `self` and `other`, without
+    /// constructing the union.
+    ///
+    /// This is equal to `self.union(other).total_wide()`.
     ///
     /// # Examples
     ///
     /// ```
     /// use utote::Multiset;
     ///
-    /// let multiset = Multiset::from([2u8, 0, 5, 3]);
-    /// assert_eq!(multiset.count_min(), &0);
+    /// let a = Multiset::from([1u8, 2, 0, 4]);
+    /// let b = Multiset::from([0u8, 3, 1, 4]);
+    /// assert_eq!(a.count_union(&b), 9);
     /// ```
+    #[cfg(not(feature = "simd"))]
     #[inline]
-    pub fn count_min(&self) -> &N {
-        // iter cannot be empty, so it's fine to unwrap
-        self.iter().min().unwrap()
+    pub fn count_union(&self, other: &Self) -> u128 {
+        self.iter()
+            .zip(other.iter())
+            .map(|(a, b)| <N as AsPrimitive<u128>>::as_(*a.max(b)))
+            .sum()
     }
 
-    /// Set all element counts, except for the given `elem`, to zero.
+    /// Splits the multiset in two by a predicate on each element and its
+    /// count.
+    ///
+    /// Elements for which `f` returns `true` keep their count in the first
+    /// multiset and have a count of zero in the second, and vice versa.
+    /// Counts are never split, so the two multisets sum to `self`.
     ///
     /// # Examples
     ///
     /// ```
     /// use utote::Multiset;
     ///
-    /// let mut multiset = Multiset::from([2u8, 0, 5, 3]);
-    /// multiset.choose(2);
-    /// let result = Multiset::from([0, 0, 5, 0]);
-    /// assert_eq!(multiset, result);
+    /// let multiset = Multiset::from([1u8, 5, 0, 2]);
+    /// let (large, small) = multiset.partition(|_, count| count > 1);
+    /// assert_eq!(large, Multiset::from([0, 5, 0, 2]));
+    /// assert_eq!(small, Multiset::from([1, 0, 0, 0]));
     /// ```
     #[inline]
-    pub fn choose(&mut self, elem: usize) {
-        let mut res = [N::zero(); SIZE];
-        if elem < SIZE {
-            // Safety: Guaranteed by bounds check on `elem`.
-            unsafe { *res.get_unchecked_mut(elem) = *self.get_unchecked(elem) };
+    #[must_use]
+    pub fn partition<F>(&self, mut f: F) -> (Self, Self)
+    where
+        F: FnMut(usize, N) -> bool,
+    {
+        let mut left = Multiset::empty();
+        let mut right = Multiset::empty();
+        for (elem, &count) in self.iter().enumerate() {
+            if f(elem, count) {
+                left.data[elem] = count;
+            } else {
+                right.data[elem] = count;
+            }
         }
-        self.data = res
+        (left, right)
     }
 
-    /// Set all element counts, except for a random choice, to zero.
+    /// Splits the multiset in two at the element `mid`.
     ///
-    /// The choice is weighted by the counts of the elements, and unless the
-    /// multiset is empty an element with non-zero count will always be chosen.
+    /// The first multiset keeps the counts of elements less than `mid` and
+    /// the second keeps the counts of elements greater than or equal to
+    /// `mid`. All other counts are zero, so the two multisets sum to `self`.
+    ///
+    /// # Panics
+    /// Panics if `mid > SIZE`.
     ///
     /// # Examples
     ///
     /// ```
     /// use utote::Multiset;
-    /// use rand::prelude::*;
     ///
-    /// let rng = &mut StdRng::seed_from_u64(thread_rng().next_u64());
-    /// let mut multiset = Multiset::from([2u8, 0, 5, 3]);
-    /// multiset.choose_random(rng);
-    /// assert_eq!(multiset.is_singleton(), true);
+    /// let multiset = Multiset::from([1u8, 5, 0, 2]);
+    /// let (prefix, suffix) = multiset.split_at(1);
+    /// assert_eq!(prefix, Multiset::from([1, 0, 0, 0]));
+    /// assert_eq!(suffix, Multiset::from([0, 5, 0, 2]));
     /// ```
-    #[cfg(feature = "rand")]
-    #[cfg(not(feature = "simd"))]
     #[inline]
-    pub fn choose_random<T: RngCore>(&mut self, rng: &mut T) {
-        let total = self.total();
-        if total == 0 {
-            return;
-        }
-        let choice_value = rng.gen_range(1..=total);
-        let mut res = [N::zero(); SIZE];
-        let mut acc = 0;
-        for (i, elem) in self.iter().enumerate() {
-            acc += <N as AsPrimitive<usize>>::as_(*elem);
-            if acc >= choice_value {
-                // Safety: `i` cannot be outside of `res`.
-                unsafe { *res.get_unchecked_mut(i) = *elem }
-                break;
-            }
-        }
-        self.data = res
-    }
-
-    /// Calculate the collision entropy of the multiset.
+    #[must_use]
+    pub fn split_at(&self, mid: usize) -> (Self, Self) {
+        assert!(mid <= SIZE, "mid: {} > SIZE: {}", mid, SIZE);
+        let mut prefix = Multiset::empty();
+        let mut suffix = Multiset::empty();
+        prefix.data[..mid].copy_from_slice(&self.data[..mid]);
+        suffix.data[mid..].copy_from_slice(&self.data[mid..]);
+        (prefix, suffix)
+    }
+
+    /// Returns a multiset with the counts of `self` scaled so that their total
+    /// is `target`.
+    ///
+    /// Counts are scaled proportionally and rounded with the largest remainder
+    /// method, so the total is exactly `target` and a count which is larger
+    /// than another in `self` is never smaller in the result. An empty
+    /// multiset cannot be scaled, and is returned as is for any `target`. If a
+    /// scaled count does not fit in the counter it saturates, and the total
+    /// will be less than `target`.
     ///
     /// # Examples
     ///
     /// ```
     /// use utote::Multiset;
     ///
-    /// let multiset = Multiset::from([2u8, 1, 1, 0]);
-    /// let result = multiset.collision_entropy();
-    /// // approximate: result == 1.415037499278844
+    /// let multiset = Multiset::from([1u8, 2, 0, 3]);
+    /// assert_eq!(multiset.scale_to_total(12), Multiset::from([2, 4, 0, 6]));
+    /// assert_eq!(multiset.scale_to_total(5), Multiset::from([1, 2, 0, 2]));
     /// ```
-    ///
-    /// # Warning
-    /// Should not be used if [`Multiset::total`] or any counter in the
-    /// multiset cannot be converted to `f64`. The conversions are handled by
-    /// [`AsPrimitive<f64>`].
-    ///
-    /// [`AsPrimitive<f64>`]: num_traits::AsPrimitive
-    #[cfg(not(feature = "simd"))]
     #[inline]
-    pub fn collision_entropy(&self) -> f64 {
-        let total: f64 = self.total().as_();
-        -self
-            .into_iter()
-            .fold(0.0, |acc, count| {
-                let freq_f64: f64 = count.as_();
-                acc + (freq_f64 / total).powf(2.0)
-            })
-            .log2()
+    #[must_use]
+    pub fn scale_to_total(&self, target: usize) -> Self {
+        let total: u128 = self.iter().map(|e| <N as AsPrimitive<u128>>::as_(*e)).sum();
+        if total == 0 {
+            return *self;
+        }
+        let target = target as u128;
+
+        // Round every count down, keeping the remainders to hand out after.
+        let mut res = Multiset::empty();
+        let mut shortfall = target;
+        let mut remainders = Vec::with_capacity(SIZE);
+        for (elem, (r, count)) in res.iter_mut().zip(self.iter()).enumerate() {
+            let product = <N as AsPrimitive<u128>>::as_(*count) * target;
+            let scaled = product / total;
+            *r = <N as NumCast>::from(scaled).unwrap_or_else(N::max_value);
+            shortfall -= scaled;
+            remainders.push((product % total, *count, elem));
+        }
+
+        // Hand out what was lost to rounding down, largest remainder first.
+        // Ties go to the larger count, so the order of the counts is kept.
+        remainders.sort_unstable_by(|a, b| b.0.cmp(&a.0).then(b.1.cmp(&a.1)).then(a.2.cmp(&b.2)));
+        for &(_, _, elem) in remainders.iter().take(shortfall as usize) {
+            res.data[elem] = res.data[elem].saturating_add(&N::one());
+        }
+        res
     }
 
-    /// Calculate the shannon entropy of the multiset. Uses ln rather than log2.
+    /// Moves the counts between elements, so that each element `elem` takes
+    /// the count that element `perm[elem]` had.
+    ///
+    /// The total and the sorted counts of the multiset are unchanged.
+    ///
+    /// # Errors
+    /// Returns [`MultisetError::WrongLength`] if `perm` does not have length
+    /// `SIZE`, [`MultisetError::ElementOutOfBounds`] if it holds an element
+    /// not in the multiset, and [`MultisetError::DuplicateElement`] if it
+    /// holds an element more than once. The multiset is left unchanged on
+    /// error.
     ///
     /// # Examples
     ///
     /// ```
-    /// use utote::Multiset;
+    /// use utote::{Multiset, MultisetError};
     ///
-    /// let multiset = Multiset::from([2u8, 1, 1, 0]);
-    /// let result = multiset.shannon_entropy();
-    /// // approximate: result == 1.0397207708399179
+    /// let mut multiset = Multiset::from([1u8, 2, 0, 3]);
+    /// multiset.permute_counts(&[3, 0, 1, 2]).unwrap();
+    /// assert_eq!(multiset, Multiset::from([3, 1, 2, 0]));
+    ///
+    /// let result = multiset.permute_counts(&[3, 0, 0, 2]);
+    /// assert_eq!(result, Err(MultisetError::DuplicateElement { elem: 0 }));
     /// ```
+    pub fn permute_counts(&mut self, perm: &[usize]) -> std::result::Result<(), MultisetError> {
+        if perm.len() != SIZE {
+            return Err(MultisetError::WrongLength {
+                expected: SIZE,
+                got: perm.len(),
+            });
+        }
+        let mut seen = [false; SIZE];
+        for &elem in perm {
+            if elem >= SIZE {
+                return Err(MultisetError::ElementOutOfBounds { elem, size: SIZE });
+            }
+            if seen[elem] {
+                return Err(MultisetError::DuplicateElement { elem });
+            }
+            seen[elem] = true;
+        }
+        let old = self.data;
+        for (count, &elem) in self.iter_mut().zip(perm) {
+            *count = old[elem];
+        }
+        Ok(())
+    }
+
+    /// Compares the counts of `self` and `other` element by element, in the
+    /// same way as comparing slices.
     ///
-    /// # Warning
-    /// Should not be used if [`Multiset::total`] or any counter in the
-    /// multiset cannot be converted to `f64`. The conversions are handled by
-    /// [`AsPrimitive<f64>`].
+    /// This is a total order, unlike the inclusion order of [`PartialOrd`].
+    /// [`Lex`] wraps a multiset so that it is [`Ord`] by this comparison.
     ///
-    /// [`AsPrimitive<f64>`]: num_traits::AsPrimitive
+    /// # Examples
+    ///
+    /// ```
+    /// use std::cmp::Ordering;
+    /// use utote::Multiset;
+    ///
+    /// let a = Multiset::from([1u8, 0, 5]);
+    /// let b = Multiset::from([0u8, 3, 0]);
+    /// assert_eq!(a.cmp_lexicographic(&b), Ordering::Greater);
+    /// assert_eq!(a.partial_cmp(&b), None);
+    /// ```
+    #[inline]
+    pub fn cmp_lexicographic(&self, other: &Self) -> Ordering {
+        self.data.cmp(&other.data)
+    }
+
+    /// Returns the number of elements whose count is non-zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([1u8, 0, 0, 0]);
+    /// assert_eq!(multiset.count_non_zero(), 1);
+    /// ```
     #[cfg(not(feature = "simd"))]
     #[inline]
-    pub fn shannon_entropy(&self) -> f64 {
-        let total: f64 = self.total().as_();
-        -self.into_iter().fold(0.0, |acc, count| {
-            if count > &N::zero() {
-                let freq_f64: f64 = count.as_();
-                let prob = freq_f64 / total;
-                acc + prob * prob.ln()
-            } else {
-                acc
-            }
+    pub fn count_non_zero(&self) -> usize {
+        self.iter().fold(0, |acc, &elem| {
+            acc + <N as AsPrimitive<usize>>::as_(elem.min(N::one()))
         })
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use approx::assert_relative_eq;
-    #[cfg(feature = "rand")]
-    use rand::prelude::*;
-    use std::panic;
+    /// Returns the number of elements whose count is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([1u8, 0, 0, 0]);
+    /// assert_eq!(multiset.count_zero(), 3);
+    /// ```
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn count_zero(&self) -> usize {
+        SIZE - self.count_non_zero()
+    }
 
-    fn catch_unwind_silent<F: FnOnce() -> R + panic::UnwindSafe, R>(
-        f: F,
-    ) -> std::thread::Result<R> {
-        let prev_hook = panic::take_hook();
-        panic::set_hook(Box::new(|_| {}));
-        let result = panic::catch_unwind(f);
-        panic::set_hook(prev_hook);
-        result
+    /// Returns the number of elements whose count is greater than `n`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([1u8, 4, 2, 0]);
+    /// assert_eq!(multiset.count_greater_than(1), 2);
+    /// assert_eq!(multiset.count_greater_than(0), multiset.count_non_zero());
+    /// ```
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn count_greater_than(&self, n: N) -> usize {
+        self.iter().filter(|count| **count > n).count()
     }
 
-    #[test]
-    fn test_index() {
-        let set = Multiset::from([1u8, 2, 3, 4]);
-        assert_eq!(set.index(1), &2);
-        assert_eq!(set.index(2..), &[3, 4]);
+    /// Returns the number of elements whose count is at most `n`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([1u8, 4, 2, 0]);
+    /// assert_eq!(multiset.count_at_most(1), 2);
+    /// assert_eq!(multiset.count_at_most(0), multiset.count_zero());
+    /// ```
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn count_at_most(&self, n: N) -> usize {
+        SIZE - self.count_greater_than(n)
     }
 
-    #[test]
-    fn test_index_mut() {
-        let mut set = Multiset::from([1u8, 2, 3, 4]);
-        *set.index_mut(1) = 3;
-        assert_eq!(set, Multiset::from([1u8, 3, 3, 4]));
-        set.index_mut(2..).iter_mut().for_each(|e| *e = 5);
-        assert_eq!(set, Multiset::from([1u8, 3, 5, 5]))
+    /// Returns a map from each count in the multiset to the number of
+    /// elements with that count.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([1u8, 4, 1, 0, 1]);
+    /// let histogram = multiset.count_histogram();
+    /// assert_eq!(histogram.len(), 3);
+    /// assert_eq!(histogram[&0], 1);
+    /// assert_eq!(histogram[&1], 3);
+    /// assert_eq!(histogram[&4], 1);
+    /// ```
+    #[inline]
+    pub fn count_histogram(&self) -> HashMap<N, usize> {
+        let mut histogram = HashMap::new();
+        for count in self.iter() {
+            *histogram.entry(*count).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    /// Returns `true` if only one element in the multiset has a non-zero
+    /// count.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([0u8, 5, 0, 0]);
+    /// assert_eq!(multiset.is_singleton(), true);
+    /// ```
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn is_singleton(&self) -> bool {
+        self.count_non_zero() == 1
+    }
+
+    /// Returns `true` if `self` is disjoint to `other`.
+    ///
+    /// Multiset `A` is disjoint to `B` if `A` has no elements in common with
+    /// `B`. `A` is disjoint `B` if `min(A[i], B[i]) == 0` for all `i` in
+    /// `A`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let a = Multiset::from([1u8, 2, 0, 0]);
+    /// assert_eq!(a.is_disjoint(&a), false);
+    ///
+    /// let b = Multiset::from([0, 0, 3, 4]);
+    /// assert_eq!(a.is_disjoint(&b), true);
+    ///
+    /// let c = Multiset::from([0, 1, 1, 0]);
+    /// assert_eq!(a.is_disjoint(&c), false);
+    /// ```
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.iter()
+            .zip(other.iter())
+            .all(|(a, b)| a.min(b) == &N::zero())
+    }
+
+    /// Returns `true` if `self` is a subset of `other`.
+    ///
+    /// Multiset `A` is a subset of `B` if all the element counts in `A` are
+    /// less than or equal to the counts in `B`. `A` is subset `B` if
+    /// `A[i] <= B[i]` for all `i` in `A`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let a = Multiset::from([1u64, 2, 0, 0]);
+    /// assert_eq!(a.is_subset(&a), true);
+    ///
+    /// let b = Multiset::from([1, 3, 0, 0]);
+    /// assert_eq!(a.is_subset(&b), true);
+    ///
+    /// assert_eq!(a.is_subset(&Multiset::empty()), false);
+    /// ```
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.iter().zip(other.iter()).all(|(a, b)| a <= b)
+    }
+
+    /// Returns `true` if `self` is a subset of the sum of `b` and `c`.
+    ///
+    /// This checks `A[i] <= B[i] + C[i]` for all `i` in `A` without
+    /// constructing the sum, so unlike `self.is_subset(&b.sum(c))` the result
+    /// is never affected by counts saturating.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let demand = Multiset::from([3u8, 1, 255]);
+    /// let b = Multiset::from([1u8, 1, 200]);
+    /// let c = Multiset::from([2u8, 0, 55]);
+    /// assert!(demand.is_subset_of_sum(&b, &c));
+    /// assert!(!demand.is_subset_of_sum(&b, &Multiset::empty()));
+    /// ```
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn is_subset_of_sum(&self, b: &Self, c: &Self) -> bool {
+        self.iter()
+            .zip(b.iter().zip(c.iter()))
+            .all(|(a, (b, c))| a.saturating_sub(b) <= *c)
+    }
+
+    /// Returns `true` if `self` is a superset of `other`.
+    ///
+    /// Multiset `A` is a subset of `B` if all the element counts in `A` are
+    /// greater than or equal to the counts in `B`. `A` is superset `B` if
+    /// `A[i] >= B[i]` for all `i` in `A`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let a = Multiset::from([1u8, 2, 0, 0]);
+    /// assert_eq!(a.is_superset(&a), true);
+    ///
+    /// let b = Multiset::from([1, 1, 0, 0]);
+    /// assert_eq!(a.is_superset(&b), true);
+    ///
+    /// assert_eq!(a.is_superset(&Multiset::empty()), true);
+    /// ```
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn is_superset(&self, other: &Self) -> bool {
+        self.iter().zip(other.iter()).all(|(a, b)| a >= b)
+    }
+
+    /// Returns `true` if `self` is a proper subset of `other`.
+    ///
+    /// Multiset `A` is a proper subset of `B` if `A` is a subset of `B` and at
+    /// least one element count of `A` is less than that element count in `B`.
+    /// `A` is proper subset `B` if `A[i] <= B[i]` for all `i` in `A` and there
+    /// exists `j` such that `A[j] < B[j]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let a = Multiset::from([1u8, 2, 0, 0]);
+    /// assert_eq!(a.is_proper_subset(&a), false);
+    ///
+    /// let b = Multiset::from([1, 3, 0, 0]);
+    /// assert_eq!(a.is_proper_subset(&b), true);
+    /// ```
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn is_proper_subset(&self, other: &Self) -> bool {
+        self != other && self.is_subset(other)
+    }
+
+    /// Returns `true` if `self` is a proper superset of `other`.
+    ///
+    /// Multiset `A` is a proper superset of `B` if `A` is a superset of `B`
+    /// and at least one element count of `A` is greater than that element
+    /// count in `B`. `A` is proper superset `B` if `A[i] >= B[i]` for all `i`
+    /// in `A` and there exists `j` such that `A[j] > B[j]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let a = Multiset::from([1u8, 2, 0, 0]);
+    /// assert_eq!(a.is_proper_superset(&a), false);
+    ///
+    /// let b = Multiset::from([1, 1, 0, 0]);
+    /// assert_eq!(a.is_proper_superset(&b), true);
+    /// ```
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn is_proper_superset(&self, other: &Self) -> bool {
+        self != other && self.is_superset(other)
+    }
+
+    /// Returns `true` if any element count in `self` is less than that element
+    /// count in `other`.
+    ///
+    /// `A` is any lesser `B` if there exists some `i` such that `A[i] < B[i]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let a = Multiset::from([1u8, 2, 4, 0]);
+    /// assert_eq!(a.is_any_lesser(&a), false);
+    ///
+    /// let b = Multiset::from([1, 3, 0, 0]);
+    /// assert_eq!(a.is_any_lesser(&b), true);
+    /// ```
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn is_any_lesser(&self, other: &Self) -> bool {
+        self.iter().zip(other.iter()).any(|(a, b)| a < b)
+    }
+
+    /// Returns `true` if any element count in `self` is greater than that
+    /// element count in `other`.
+    ///
+    /// `A` is any greater `B` if there exists some `i` such that
+    /// `A[i] > B[i]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let a = Multiset::from([1u8, 2, 0, 0]);
+    /// assert_eq!(a.is_any_greater(&a), false);
+    ///
+    /// let b = Multiset::from([1, 1, 4, 0]);
+    /// assert_eq!(a.is_any_greater(&b), true);
+    /// ```
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn is_any_greater(&self, other: &Self) -> bool {
+        self.iter().zip(other.iter()).any(|(a, b)| a > b)
+    }
+
+    /// Returns `true` if all elements have a count of zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([0u8, 0, 0, 0]);
+    /// assert_eq!(multiset.is_empty(), true);
+    /// assert_eq!(Multiset::<u8, 4>::empty().is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.data == [N::zero(); SIZE]
+    }
+
+    /// The total or cardinality of a multiset is the sum of all element
+    /// counts.
+    ///
+    /// This function converts counts to `usize` to try and avoid overflows.
+    /// Use [`Multiset::total_wide`] if the total may not fit in a `usize`, as
+    /// with large `u64` or `usize` counts.
+    ///
+    /// # Panics
+    ///
+    /// The sum overflows like any other `usize` addition, which can only
+    /// happen with `u64` or `usize` counters. It panics when overflow checks
+    /// are enabled, as in debug builds, and wraps otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([1u8, 2, 3, 4]);
+    /// assert_eq!(multiset.total(), 10);
+    /// ```
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn total(&self) -> usize {
+        self.iter()
+            .map(|e| <N as AsPrimitive<usize>>::as_(*e))
+            .sum()
+    }
+
+    /// The total of the multiset in the counter type, or `None` if it does not
+    /// fit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([100u8, 100, 50]);
+    /// assert_eq!(multiset.total_checked(), Some(250));
+    ///
+    /// let multiset = Multiset::from([200u8, 100, 50]);
+    /// assert_eq!(multiset.total_checked(), None);
+    /// ```
+    #[inline]
+    pub fn total_checked(&self) -> Option<N> {
+        self.iter()
+            .try_fold(N::zero(), |acc, count| acc.checked_add(count))
+    }
+
+    /// The total of the multiset as a `u128`.
+    ///
+    /// Unlike [`Multiset::total`] this cannot overflow, even for `u64` or
+    /// `usize` counters or on targets where `usize` is 32 bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([u64::MAX, u64::MAX]);
+    /// assert_eq!(multiset.total_wide(), 2 * u64::MAX as u128);
+    /// ```
+    #[inline]
+    pub fn total_wide(&self) -> u128 {
+        self.iter()
+            .map(|count| <N as AsPrimitive<u128>>::as_(*count))
+            .sum()
+    }
+
+    /// Returns a tuple containing the element and a reference to the largest
+    /// count in the multiset. If several elements share the largest count the
+    /// lowest of them is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([2u16, 0, 5, 3]);
+    /// assert_eq!(multiset.elem_count_max(), (2, &5));
+    ///
+    /// let tied = Multiset::from([2u16, 5, 0, 5]);
+    /// assert_eq!(tied.elem_count_max(), (1, &5));
+    /// ```
+    #[inline]
+    pub fn elem_count_max(&self) -> (usize, &N) {
+        // iter cannot be empty, so it's fine to unwrap. max_by_key returns the
+        // last of equal maxima, so searching in reverse gives the lowest.
+        self.iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_, count)| *count)
+            .unwrap()
+    }
+
+    /// Returns the element with the largest count in the multiset. If several
+    /// elements share the largest count the lowest of them is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([2u8, 0, 5, 3]);
+    /// assert_eq!(multiset.elem_max(), 2);
+    /// ```
+    #[inline]
+    pub fn elem_max(&self) -> usize {
+        self.elem_count_max().0
+    }
+
+    /// Returns every element with the largest count in the multiset, in
+    /// ascending order. As an empty multiset has a largest count of zero, all
+    /// of its elements are returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([5u8, 0, 5, 3, 5]);
+    /// assert_eq!(multiset.elem_max_all(), vec![0, 2, 4]);
+    /// assert_eq!(Multiset::<u8, 3>::empty().elem_max_all(), vec![0, 1, 2]);
+    /// ```
+    #[inline]
+    pub fn elem_max_all(&self) -> Vec<usize> {
+        // One implementation serves both builds. The simd count_max needs the
+        // lane bound, so the maximum is found with a scalar pass instead; the
+        // filtering pass below is scalar either way.
+        let max = match self.iter().max() {
+            Some(max) => *max,
+            None => return Vec::new(),
+        };
+        self.iter()
+            .enumerate()
+            .filter(|(_, count)| **count == max)
+            .map(|(elem, _)| elem)
+            .collect()
+    }
+
+    /// Returns a reference to the largest count in the multiset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([2u8, 0, 5, 3]);
+    /// assert_eq!(multiset.count_max(), &5);
+    /// ```
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn count_max(&self) -> &N {
+        // iter cannot be empty, so it's fine to unwrap
+        self.iter().max().unwrap()
+    }
+
+    /// Returns a tuple containing the element and a reference to the smallest
+    /// count in the multiset. If several elements share the smallest count the
+    /// lowest of them is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([2u8, 0, 5, 3]);
+    /// assert_eq!(multiset.elem_count_min(), (1, &0));
+    /// ```
+    #[inline]
+    pub fn elem_count_min(&self) -> (usize, &N) {
+        // iter cannot be empty, so it's fine to unwrap
+        self.iter()
+            .enumerate()
+            .min_by_key(|(_, count)| *count)
+            .unwrap()
+    }
+
+    /// Returns the element with the smallest count in the multiset. If several
+    /// elements share the smallest count the lowest of them is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([2u8, 0, 5, 3]);
+    /// assert_eq!(multiset.elem_min(), 1);
+    /// ```
+    #[inline]
+    pub fn elem_min(&self) -> usize {
+        self.elem_count_min().0
+    }
+
+    /// Returns a reference to the smallest count in the multiset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([2u8, 0, 5, 3]);
+    /// assert_eq!(multiset.count_min(), &0);
+    /// ```
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn count_min(&self) -> &N {
+        // iter cannot be empty, so it's fine to unwrap
+        self.iter().min().unwrap()
+    }
+
+    /// Returns a tuple containing the element and a reference to the smallest
+    /// non-zero count in the multiset, or `None` if the multiset is empty. If
+    /// several elements share the smallest count the lowest of them is
+    /// returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([2u8, 0, 5, 1]);
+    /// assert_eq!(multiset.elem_count_min_non_zero(), Some((3, &1)));
+    /// assert_eq!(Multiset::<u8, 4>::empty().elem_count_min_non_zero(), None);
+    /// ```
+    #[inline]
+    pub fn elem_count_min_non_zero(&self) -> Option<(usize, &N)> {
+        self.iter()
+            .enumerate()
+            .filter(|(_, count)| **count > N::zero())
+            .min_by_key(|(_, count)| *count)
+    }
+
+    /// Set all element counts, except for the given `elem`, to zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let mut multiset = Multiset::from([2u8, 0, 5, 3]);
+    /// multiset.choose(2);
+    /// let result = Multiset::from([0, 0, 5, 0]);
+    /// assert_eq!(multiset, result);
+    /// ```
+    #[inline]
+    pub fn choose(&mut self, elem: usize) {
+        let mut res = [N::zero(); SIZE];
+        if elem < SIZE {
+            // Safety: Guaranteed by bounds check on `elem`.
+            unsafe { *res.get_unchecked_mut(elem) = *self.get_unchecked(elem) };
+        }
+        self.data = res
+    }
+
+    /// Returns a multiset with `total` members, each added to an element
+    /// chosen uniformly at random.
+    ///
+    /// Returns [`MultisetError::CountOutOfRange`] if the count drawn for an
+    /// element does not fit in the counter, which cannot happen if `total`
+    /// fits, and [`MultisetError::ElementOutOfBounds`] if `SIZE` is zero and
+    /// `total` is not.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    /// use rand::prelude::*;
+    ///
+    /// let rng = &mut StdRng::seed_from_u64(thread_rng().next_u64());
+    /// let multiset = Multiset::<u8, 4>::random_with_total(100, rng).unwrap();
+    /// assert_eq!(multiset.total(), 100);
+    /// ```
+    #[cfg(feature = "rand")]
+    #[inline]
+    pub fn random_with_total<R: Rng + ?Sized>(
+        total: usize,
+        rng: &mut R,
+    ) -> std::result::Result<Self, MultisetError> {
+        if SIZE == 0 && total > 0 {
+            return Err(MultisetError::ElementOutOfBounds { elem: 0, size: 0 });
+        }
+        let mut counts = [0usize; SIZE];
+        for _ in 0..total {
+            counts[rng.gen_range(0..SIZE)] += 1;
+        }
+        let mut res = Multiset::empty();
+        for (elem, (r, count)) in res.iter_mut().zip(counts.iter()).enumerate() {
+            *r = <N as NumCast>::from(*count).ok_or(MultisetError::CountOutOfRange { elem })?;
+        }
+        Ok(res)
+    }
+
+    /// Constructs a Multiset from a uniform random sample of at most
+    /// `max_total` of the elements yielded by an iterator, without collecting
+    /// the iterator.
+    ///
+    /// Uses reservoir sampling, so every subset of `max_total` items of the
+    /// iterator is equally likely to be counted. The total of the result is
+    /// the smaller of `max_total` and the number of items. Counts saturate at
+    /// the maximum value of the counter.
+    ///
+    /// # Panics
+    /// If any item in the iterator is out of bounds of the Multiset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    /// use rand::prelude::*;
+    ///
+    /// let rng = &mut StdRng::seed_from_u64(thread_rng().next_u64());
+    /// let stream = (0..1_000_000).map(|i| i % 4);
+    /// let multiset = Multiset::<u8, 4>::from_iter_sampled(stream, 100, rng);
+    /// assert_eq!(multiset.total(), 100);
+    /// ```
+    #[cfg(feature = "rand")]
+    #[inline]
+    pub fn from_iter_sampled<I, R>(elements: I, max_total: usize, rng: &mut R) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Borrow<usize>,
+        R: Rng + ?Sized,
+    {
+        let mut counts = [0usize; SIZE];
+        for (seen, elem) in elements.into_iter().enumerate() {
+            let elem = *elem.borrow();
+            assert!(
+                elem < SIZE,
+                "element: {} not in Multiset (element >= SIZE: {})",
+                elem,
+                SIZE
+            );
+            if seen < max_total {
+                counts[elem] += 1;
+                continue;
+            }
+            // The item replaces a random member of the reservoir with
+            // probability max_total / (seen + 1).
+            let slot = rng.gen_range(0..=seen);
+            if slot < max_total {
+                // `slot` is uniform over the reservoir, so the member it
+                // falls on is found from the cumulative counts.
+                let mut acc = 0;
+                let evicted = counts
+                    .iter()
+                    .position(|count| {
+                        acc += count;
+                        acc > slot
+                    })
+                    .unwrap();
+                counts[evicted] -= 1;
+                counts[elem] += 1;
+            }
+        }
+        let mut res = Multiset::empty();
+        for (r, count) in res.iter_mut().zip(counts.iter()) {
+            *r = <N as NumCast>::from(*count).unwrap_or_else(N::max_value);
+        }
+        res
+    }
+
+    /// Returns a multiset with each count drawn independently and uniformly
+    /// from `0..=max_count`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    /// use rand::prelude::*;
+    ///
+    /// let rng = &mut StdRng::seed_from_u64(thread_rng().next_u64());
+    /// let multiset = Multiset::<u8, 4>::random_uniform_counts(3, rng);
+    /// assert!(multiset.iter().all(|count| *count <= 3));
+    /// ```
+    #[cfg(feature = "rand")]
+    #[inline]
+    pub fn random_uniform_counts<R: Rng + ?Sized>(max_count: N, rng: &mut R) -> Self {
+        let max_count: u64 = max_count.as_();
+        let mut res = Multiset::empty();
+        for count in res.iter_mut() {
+            // The count is at most `max_count`, so always fits in `N`.
+            *count = <N as NumCast>::from(rng.gen_range(0..=max_count)).unwrap();
+        }
+        res
+    }
+
+    /// Returns a random element, chosen with probability proportional to its
+    /// count, or `None` if the multiset is empty. Unlike
+    /// [`Multiset::choose_random`] the multiset is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    /// use rand::prelude::*;
+    ///
+    /// let rng = &mut StdRng::seed_from_u64(thread_rng().next_u64());
+    /// let multiset = Multiset::from([2u8, 0, 5, 3]);
+    /// let elem = multiset.sample(rng).unwrap();
+    /// assert_ne!(multiset[elem], 0);
+    ///
+    /// assert_eq!(Multiset::<u8, 4>::empty().sample(rng), None);
+    /// ```
+    #[cfg(feature = "rand")]
+    #[inline]
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<usize> {
+        let total = self.total_wide();
+        if total == 0 {
+            return None;
+        }
+        let choice_value = rng.gen_range(1..=total);
+        let mut acc = 0;
+        self.iter().position(|elem| {
+            acc += <N as AsPrimitive<u128>>::as_(*elem);
+            acc >= choice_value
+        })
+    }
+
+    /// Returns the multiset of `k` elements drawn at random, with replacement,
+    /// each with probability proportional to its count in `self`.
+    ///
+    /// Counts in the result saturate at the maximum value of the counter.
+    /// Sampling from an empty multiset returns the empty multiset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    /// use rand::prelude::*;
+    ///
+    /// let rng = &mut StdRng::seed_from_u64(thread_rng().next_u64());
+    /// let multiset = Multiset::from([2u8, 0, 5, 3]);
+    /// let sampled = multiset.sample_counts(20, rng);
+    /// assert_eq!(sampled.total(), 20);
+    /// assert_eq!(sampled[1], 0);
+    /// ```
+    #[cfg(feature = "rand")]
+    #[inline]
+    #[must_use]
+    pub fn sample_counts<R: Rng + ?Sized>(&self, k: usize, rng: &mut R) -> Self {
+        let mut res = Multiset::empty();
+        let total = self.total_wide();
+        if total == 0 {
+            return res;
+        }
+        let cumulative: Vec<u128> = self
+            .iter()
+            .scan(0, |acc, elem| {
+                *acc += <N as AsPrimitive<u128>>::as_(*elem);
+                Some(*acc)
+            })
+            .collect();
+        for _ in 0..k {
+            let choice_value = rng.gen_range(1..=total);
+            let chosen = cumulative.partition_point(|acc| *acc < choice_value);
+            res.data[chosen] = res.data[chosen].saturating_add(&N::one());
+        }
+        res
+    }
+
+    /// Returns the multiset of `k` members drawn at random from `self`
+    /// without replacement, so that the result is always a subset of `self`.
+    ///
+    /// Each draw removes one member from the pool, so the counts of the result
+    /// follow the multivariate hypergeometric distribution. If `k` is at least
+    /// the total of `self` a copy of `self` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    /// use rand::prelude::*;
+    ///
+    /// let rng = &mut StdRng::seed_from_u64(thread_rng().next_u64());
+    /// let multiset = Multiset::from([2u8, 0, 5, 3]);
+    /// let sampled = multiset.sample_without_replacement(4, rng);
+    /// assert_eq!(sampled.total(), 4);
+    /// assert!(sampled.is_subset(&multiset));
+    /// ```
+    #[cfg(feature = "rand")]
+    #[inline]
+    #[must_use]
+    pub fn sample_without_replacement<R: Rng + ?Sized>(&self, k: usize, rng: &mut R) -> Self {
+        let total = self.total_wide();
+        let k = k as u128;
+        if k >= total {
+            return *self;
+        }
+        // Draw whichever of the sample and the members left out is smaller,
+        // what is left in the pool being the other.
+        let draws = k.min(total - k);
+        let mut pool = *self;
+        let mut drawn = Multiset::empty();
+        for remaining in (total - draws + 1..=total).rev() {
+            let choice_value = rng.gen_range(1..=remaining);
+            let mut acc = 0;
+            // The pool holds `remaining` members, so one is always found.
+            let chosen = pool
+                .iter()
+                .position(|elem| {
+                    acc += <N as AsPrimitive<u128>>::as_(*elem);
+                    acc >= choice_value
+                })
+                .unwrap();
+            pool.data[chosen] -= N::one();
+            drawn.data[chosen] += N::one();
+        }
+        if draws == k {
+            drawn
+        } else {
+            pool
+        }
+    }
+
+    /// Removes one member at random, chosen with probability proportional to
+    /// the count of its element, and returns its element, or `None` if the
+    /// multiset is empty.
+    ///
+    /// Calling this [`Multiset::total`] times empties the multiset, like
+    /// drawing balls from an urn without replacement.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    /// use rand::prelude::*;
+    ///
+    /// let rng = &mut StdRng::seed_from_u64(thread_rng().next_u64());
+    /// let mut multiset = Multiset::from([2u8, 0, 1]);
+    /// let mut drawn = Vec::new();
+    /// while let Some(elem) = multiset.drain_random(rng) {
+    ///     drawn.push(elem);
+    /// }
+    /// drawn.sort();
+    /// assert_eq!(drawn, vec![0, 0, 2]);
+    /// assert!(multiset.is_empty());
+    /// ```
+    #[cfg(feature = "rand")]
+    #[inline]
+    pub fn drain_random<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Option<usize> {
+        let chosen = self.sample(rng)?;
+        self.data[chosen] -= N::one();
+        Some(chosen)
+    }
+
+    /// Set all element counts, except for a random choice, to zero.
+    ///
+    /// The choice is weighted by the counts of the elements, and unless the
+    /// multiset is empty an element with non-zero count will always be chosen.
+    /// Returns the chosen element, or `None` if the multiset is empty, in
+    /// which case it is left untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    /// use rand::prelude::*;
+    ///
+    /// let rng = &mut StdRng::seed_from_u64(thread_rng().next_u64());
+    /// let mut multiset = Multiset::from([2u8, 0, 5, 3]);
+    /// let elem = multiset.choose_random(rng).unwrap();
+    /// assert_eq!(multiset.is_singleton(), true);
+    /// assert_ne!(multiset[elem], 0);
+    ///
+    /// let mut empty = Multiset::<u8, 4>::empty();
+    /// assert_eq!(empty.choose_random(rng), None);
+    /// ```
+    #[cfg(feature = "rand")]
+    #[inline]
+    pub fn choose_random<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Option<usize> {
+        let chosen = self.sample(rng)?;
+        let mut res = [N::zero(); SIZE];
+        res[chosen] = self.data[chosen];
+        self.data = res;
+        Some(chosen)
+    }
+
+    /// Set all element counts, except for a random choice, to zero, where the
+    /// choice is weighted by `weights` rather than by the counts.
+    ///
+    /// Only elements with a non-zero count and a positive weight can be
+    /// chosen, so NaN weights are never chosen. If any such weight is
+    /// infinite, the choice is made evenly between the infinite weights.
+    /// Returns the chosen element, or `None` if there is no such element, in
+    /// which case the multiset is left untouched.
+    ///
+    /// # Panics
+    /// Panics if the length of `weights` is not `SIZE`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    /// use rand::prelude::*;
+    ///
+    /// let rng = &mut StdRng::seed_from_u64(thread_rng().next_u64());
+    /// let weights = [0.5, 2.0, 0.0, 1.5];
+    ///
+    /// let mut multiset = Multiset::from([2u8, 0, 5, 3]);
+    /// let elem = multiset.choose_weighted(&weights, rng).unwrap();
+    /// assert!(elem == 0 || elem == 3);
+    /// assert_eq!(multiset.is_singleton(), true);
+    ///
+    /// let mut multiset = Multiset::from([0u8, 0, 5, 0]);
+    /// assert_eq!(multiset.choose_weighted(&weights, rng), None);
+    /// assert_eq!(multiset, Multiset::from([0, 0, 5, 0]));
+    /// ```
+    #[cfg(feature = "rand")]
+    #[inline]
+    pub fn choose_weighted<R: Rng + ?Sized>(
+        &mut self,
+        weights: &[f64],
+        rng: &mut R,
+    ) -> Option<usize> {
+        assert_eq!(weights.len(), SIZE, "weights must have length SIZE");
+        let effective = |(count, weight): (&N, &f64)| {
+            if *count > N::zero() && *weight > 0.0 {
+                *weight
+            } else {
+                0.0
+            }
+        };
+        let max = self.iter().zip(weights).map(effective).fold(0.0, f64::max);
+        if max == 0.0 {
+            return None;
+        }
+        // Dividing by the largest weight keeps the total finite. Next to an
+        // infinite weight every finite weight is negligible, so the infinite
+        // weights are chosen between evenly.
+        let scaled = |pair| {
+            let weight = effective(pair);
+            if max.is_infinite() {
+                if weight.is_infinite() {
+                    1.0
+                } else {
+                    0.0
+                }
+            } else {
+                weight / max
+            }
+        };
+        let total: f64 = self.iter().zip(weights).map(scaled).sum();
+        let choice_value = rng.gen_range(0.0..total);
+        let mut acc = 0.0;
+        let mut chosen = None;
+        for (i, weight) in self.iter().zip(weights).map(scaled).enumerate() {
+            if weight > 0.0 {
+                acc += weight;
+                chosen = Some(i);
+                // Rounding may leave acc just below total, in which case the
+                // last element that can be chosen is.
+                if acc > choice_value {
+                    break;
+                }
+            }
+        }
+        let chosen = chosen?;
+        let mut res = [N::zero(); SIZE];
+        res[chosen] = self.data[chosen];
+        self.data = res;
+        Some(chosen)
+    }
+
+    /// Set all element counts, except for a random choice, to zero, where the
+    /// choice is the element with the largest `log_weights[elem]` plus Gumbel
+    /// noise scaled by `temperature`.
+    ///
+    /// At a temperature of 1.0 each element is chosen with probability
+    /// proportional to the exponential of its log weight. Higher temperatures
+    /// make the choice more uniform, and at 0.0 the element with the largest
+    /// log weight is always chosen, the lowest of them in a tie.
+    ///
+    /// Only elements with a non-zero count and a log weight greater than
+    /// negative infinity can be chosen. Returns the chosen element, or `None`
+    /// if there is no such element, in which case the multiset is left
+    /// untouched.
+    ///
+    /// # Panics
+    /// Panics if the length of `log_weights` is not `SIZE`, or if
+    /// `temperature` is negative or NaN.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    /// use rand::prelude::*;
+    ///
+    /// let rng = &mut StdRng::seed_from_u64(thread_rng().next_u64());
+    /// let log_weights = [0.5, 2.0, 0.0, 1.5];
+    ///
+    /// let mut multiset = Multiset::from([2u8, 0, 5, 3]);
+    /// assert_eq!(multiset.choose_gumbel(&log_weights, 0.0, rng), Some(3));
+    /// assert_eq!(multiset, Multiset::from([0, 0, 0, 3]));
+    ///
+    /// let mut multiset = Multiset::from([2u8, 0, 5, 3]);
+    /// let elem = multiset.choose_gumbel(&log_weights, 1.0, rng).unwrap();
+    /// assert_eq!(multiset.is_singleton(), true);
+    /// assert_ne!(elem, 1);
+    /// ```
+    #[cfg(feature = "rand")]
+    #[inline]
+    pub fn choose_gumbel<R: Rng + ?Sized>(
+        &mut self,
+        log_weights: &[f64],
+        temperature: f64,
+        rng: &mut R,
+    ) -> Option<usize> {
+        assert_eq!(log_weights.len(), SIZE, "log_weights must have length SIZE");
+        assert!(
+            temperature >= 0.0,
+            "temperature must not be negative or NaN"
+        );
+        let mut chosen = None;
+        let mut best = f64::NEG_INFINITY;
+        for (i, (count, log_weight)) in self.iter().zip(log_weights).enumerate() {
+            if *count == N::zero() || log_weight.is_nan() || *log_weight == f64::NEG_INFINITY {
+                continue;
+            }
+            let score = if temperature == 0.0 {
+                *log_weight
+            } else {
+                // Excluding zero keeps the noise finite.
+                let uniform: f64 = rng.gen_range(f64::MIN_POSITIVE..1.0);
+                log_weight - temperature * (-uniform.ln()).ln()
+            };
+            if chosen.is_none() || score > best {
+                chosen = Some(i);
+                best = score;
+            }
+        }
+        let chosen = chosen?;
+        let mut res = [N::zero(); SIZE];
+        res[chosen] = self.data[chosen];
+        self.data = res;
+        Some(chosen)
+    }
+
+    /// Splits the multiset in two, with each member independently going to the
+    /// first multiset with probability `p` and to the second otherwise.
+    ///
+    /// The two multisets always sum to `self`. The count of each element in
+    /// the first is drawn from a binomial distribution, so large counts are
+    /// as fast to split as small ones.
+    ///
+    /// # Panics
+    /// Panics if `p` is not in `[0, 1]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    /// use rand::prelude::*;
+    ///
+    /// let rng = &mut StdRng::seed_from_u64(thread_rng().next_u64());
+    /// let multiset = Multiset::from([200u8, 0, 50, 3]);
+    /// let (train, test) = multiset.random_split(0.8, rng);
+    /// assert_eq!(train + test, multiset);
+    /// ```
+    #[cfg(feature = "rand")]
+    #[inline]
+    #[must_use]
+    pub fn random_split<R: Rng + ?Sized>(&self, p: f64, rng: &mut R) -> (Self, Self) {
+        assert!((0.0..=1.0).contains(&p), "p must be in [0, 1]");
+        let mut first = Multiset::empty();
+        let mut second = Multiset::empty();
+        for ((a, b), count) in first.iter_mut().zip(second.iter_mut()).zip(self.iter()) {
+            // Binomial::new only fails for p outside [0, 1].
+            let binomial = Binomial::new(count.as_(), p).unwrap();
+            // The draw is at most the count, so always fits in `N`.
+            *a = <N as NumCast>::from(binomial.sample(rng)).unwrap();
+            *b = *count - *a;
+        }
+        (first, second)
+    }
+
+    /// Returns a multiset of `target` members drawn at random from `self`
+    /// with replacement, each with probability proportional to the count of
+    /// its element.
+    ///
+    /// This is a random counterpart to [`Multiset::scale_to_total`], with the
+    /// counts of the result following the multinomial distribution. Each
+    /// count is drawn from a binomial distribution, so large targets are as
+    /// fast as small ones. An empty multiset cannot be resampled, and is
+    /// returned as is for any `target`. Counts saturate at the maximum value
+    /// of the counter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    /// use rand::prelude::*;
+    ///
+    /// let rng = &mut StdRng::seed_from_u64(thread_rng().next_u64());
+    /// let multiset = Multiset::from([1u32, 2, 0, 3]);
+    /// let resampled = multiset.resample_to_total(1_000_000, rng);
+    /// assert_eq!(resampled.total(), 1_000_000);
+    /// assert_eq!(resampled[2], 0);
+    /// ```
+    #[cfg(feature = "rand")]
+    #[must_use]
+    pub fn resample_to_total<R: Rng + ?Sized>(&self, target: usize, rng: &mut R) -> Self {
+        let mut remaining_total: u128 =
+            self.iter().map(|e| <N as AsPrimitive<u128>>::as_(*e)).sum();
+        if remaining_total == 0 {
+            return *self;
+        }
+        let mut remaining = target as u64;
+        let mut res = Multiset::empty();
+        for (r, count) in res.iter_mut().zip(self.iter()) {
+            if remaining == 0 {
+                break;
+            }
+            let count = <N as AsPrimitive<u128>>::as_(*count);
+            // The last non-zero count has a probability of exactly 1.0, so
+            // takes all that remains.
+            let p = count as f64 / remaining_total as f64;
+            let drawn = Binomial::new(remaining, p).unwrap().sample(rng);
+            *r = <N as NumCast>::from(drawn).unwrap_or_else(N::max_value);
+            remaining -= drawn;
+            remaining_total -= count;
+        }
+        res
+    }
+
+    /// Moves the counts between elements at random, applying a uniformly
+    /// random permutation to which element holds which count.
+    ///
+    /// The total and the sorted counts of the multiset are unchanged, only
+    /// the elements they belong to are randomized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    /// use rand::prelude::*;
+    ///
+    /// let rng = &mut StdRng::seed_from_u64(thread_rng().next_u64());
+    /// let mut multiset = Multiset::from([1u8, 2, 0, 3]);
+    /// multiset.shuffle_counts(rng);
+    /// assert_eq!(multiset.total(), 6);
+    /// assert_eq!(multiset.count_non_zero(), 3);
+    /// ```
+    #[cfg(feature = "rand")]
+    #[inline]
+    pub fn shuffle_counts<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.data.shuffle(rng);
+    }
+
+    /// Returns the probability of each element, its count divided by the
+    /// total of the multiset.
+    ///
+    /// `Multiset` can only hold unsigned integer counts, so the probabilities
+    /// are returned as an array. All the probabilities of an empty multiset
+    /// are zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([2u8, 1, 1, 0]);
+    /// assert_eq!(multiset.normalize(), [0.5, 0.25, 0.25, 0.0]);
+    /// assert_eq!(Multiset::<u8, 2>::empty().normalize(), [0.0, 0.0]);
+    /// ```
+    ///
+    /// # Warning
+    /// Should not be used if [`Multiset::total`] or any counter in the
+    /// multiset cannot be converted to `f64`. The conversions are handled by
+    /// [`AsPrimitive<f64>`].
+    ///
+    /// [`AsPrimitive<f64>`]: num_traits::AsPrimitive
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn normalize(&self) -> [f64; SIZE] {
+        let mut res = [0.0; SIZE];
+        let total: f64 = self.total_wide() as f64;
+        if total > 0.0 {
+            for (prob, count) in res.iter_mut().zip(self.iter()) {
+                *prob = <N as AsPrimitive<f64>>::as_(*count) / total;
+            }
+        }
+        res
+    }
+
+    /// Returns the expected value of `values` under the distribution of the
+    /// multiset, the sum of each count multiplied by the value of its element
+    /// divided by the total. The expected value under an empty multiset is
+    /// 0.0.
+    ///
+    /// # Panics
+    /// Panics if the length of `values` is not `SIZE`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([2u8, 1, 1, 0]);
+    /// assert_eq!(multiset.expected_value(&[1.0, 2.0, 4.0, 100.0]), 2.0);
+    /// ```
+    ///
+    /// # Warning
+    /// Should not be used if [`Multiset::total`] or any counter in the
+    /// multiset cannot be converted to `f64`. The conversions are handled by
+    /// [`AsPrimitive<f64>`].
+    ///
+    /// [`AsPrimitive<f64>`]: num_traits::AsPrimitive
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn expected_value(&self, values: &[f64]) -> f64 {
+        assert_eq!(values.len(), SIZE, "values must have length SIZE");
+        let total: f64 = self.total_wide() as f64;
+        if total == 0.0 {
+            return 0.0;
+        }
+        self.iter()
+            .zip(values)
+            .map(|(count, value)| <N as AsPrimitive<f64>>::as_(*count) * value)
+            .sum::<f64>()
+            / total
+    }
+
+    /// Returns the mean of the counts of the multiset, over all `SIZE`
+    /// elements. This is NaN if `SIZE` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([2u8, 1, 1, 0]);
+    /// assert_eq!(multiset.mean_count(), 1.0);
+    /// ```
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn mean_count(&self) -> f64 {
+        self.total_wide() as f64 / SIZE as f64
+    }
+
+    /// Returns the population variance of the counts of the multiset, over
+    /// all `SIZE` elements. This is NaN if `SIZE` is zero.
+    ///
+    /// The variance is found in two passes, first finding the mean and then
+    /// summing the squared deviations from it, which avoids the cancellation
+    /// of the single pass formula.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([2u8, 1, 1, 0]);
+    /// assert_eq!(multiset.variance(), 0.5);
+    /// ```
+    ///
+    /// # Warning
+    /// Should not be used if [`Multiset::total`] or any counter in the
+    /// multiset cannot be converted to `f64`. The conversions are handled by
+    /// [`AsPrimitive<f64>`].
+    ///
+    /// [`AsPrimitive<f64>`]: num_traits::AsPrimitive
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn variance(&self) -> f64 {
+        let mean = self.mean_count();
+        self.iter()
+            .map(|count| {
+                let deviation = <N as AsPrimitive<f64>>::as_(*count) - mean;
+                deviation * deviation
+            })
+            .sum::<f64>()
+            / SIZE as f64
+    }
+
+    /// Returns the population standard deviation of the counts of the
+    /// multiset, the square root of [`Multiset::variance`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([3u8, 1, 3, 1]);
+    /// assert_eq!(multiset.std_dev(), 1.0);
+    /// ```
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn std_dev(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    /// Returns the `k`th power moment of the counts of the multiset, the sum
+    /// of each count raised to the power `k`.
+    ///
+    /// The first moment is the total, and the second is the sum of squares
+    /// which underlies the collision entropy and Gini impurity. For `k` of at
+    /// most 3 the sum is accumulated exactly in `u128` when it fits, so only
+    /// the final conversion to `f64` is rounded. The zeroth moment is `SIZE`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([2u8, 1, 1, 0]);
+    /// assert_eq!(multiset.moment(0), 4.0);
+    /// assert_eq!(multiset.moment(1), 4.0);
+    /// assert_eq!(multiset.moment(2), 6.0);
+    /// assert_eq!(multiset.moment(5), 34.0);
+    /// ```
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn moment(&self, k: u32) -> f64 {
+        if k <= 3 {
+            let exact = self.iter().try_fold(0u128, |acc, count| {
+                let count = <N as AsPrimitive<u128>>::as_(*count);
+                count
+                    .checked_pow(k)
+                    .and_then(|power| acc.checked_add(power))
+            });
+            if let Some(exact) = exact {
+                return exact as f64;
+            }
+        }
+        // Any count above 1 raised to i32::MAX is already infinite.
+        let k = i32::try_from(k).unwrap_or(i32::MAX);
+        self.iter()
+            .map(|count| <N as AsPrimitive<f64>>::as_(*count).powi(k))
+            .sum()
+    }
+
+    /// Returns the median of the counts of the multiset, over all `SIZE`
+    /// elements. For an even `SIZE` this is the lower of the two middle
+    /// counts.
+    ///
+    /// This is the same as `self.count_quantile(0.5)`.
+    ///
+    /// # Panics
+    /// Panics if `SIZE` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([5u8, 1, 9, 0, 3]);
+    /// assert_eq!(multiset.median_count(), 3);
+    ///
+    /// let multiset = Multiset::from([5u8, 1, 9, 0]);
+    /// assert_eq!(multiset.median_count(), 1);
+    /// ```
+    #[inline]
+    pub fn median_count(&self) -> N {
+        self.count_quantile(0.5)
+    }
+
+    /// Returns the `q` quantile of the counts of the multiset, over all
+    /// `SIZE` elements, with `q` clamped to the range 0.0 to 1.0.
+    ///
+    /// No interpolation is done, the count at position `q * (SIZE - 1)`,
+    /// rounded down, in the sorted counts is returned. The counts are copied
+    /// and partially sorted, so this takes linear time.
+    ///
+    /// # Panics
+    /// Panics if `SIZE` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([5u8, 1, 9, 0, 3]);
+    /// assert_eq!(multiset.count_quantile(0.0), 0);
+    /// assert_eq!(multiset.count_quantile(0.9), 5);
+    /// assert_eq!(multiset.count_quantile(1.0), 9);
+    /// ```
+    #[inline]
+    pub fn count_quantile(&self, q: f64) -> N {
+        assert!(SIZE > 0, "no quantiles of a Multiset of SIZE: 0");
+        let mut counts = self.data;
+        let index = (q.clamp(0.0, 1.0) * (SIZE - 1) as f64) as usize;
+        *counts.select_nth_unstable(index).1
+    }
+
+    /// Calculate the collision entropy of the multiset.
+    ///
+    /// The entropy of an empty multiset is 0.0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([2u8, 1, 1, 0]);
+    /// let result = multiset.collision_entropy();
+    /// // approximate: result == 1.415037499278844
+    /// ```
+    ///
+    /// # Warning
+    /// Should not be used if [`Multiset::total`] or any counter in the
+    /// multiset cannot be converted to `f64`. The conversions are handled by
+    /// [`AsPrimitive<f64>`].
+    ///
+    /// [`AsPrimitive<f64>`]: num_traits::AsPrimitive
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn collision_entropy(&self) -> f64 {
+        let sum = self.sum_squared_probabilities();
+        // The probabilities of an empty multiset are all NaN, or there are
+        // none when SIZE is 0. Subtracting from 0.0 rather than negating
+        // gives 0.0 rather than -0.0 when the sum is 1.0.
+        if sum.is_nan() || sum == 0.0 {
+            0.0
+        } else {
+            0.0 - sum.log2()
+        }
+    }
+
+    /// Calculate the Gini impurity of the multiset, the probability that two
+    /// elements drawn at random with replacement are different.
+    ///
+    /// The impurity of an empty multiset is 0.0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([2u8, 1, 1, 0]);
+    /// assert_eq!(multiset.gini_impurity(), 0.625);
+    /// ```
+    ///
+    /// # Warning
+    /// Should not be used if [`Multiset::total`] or any counter in the
+    /// multiset cannot be converted to `f64`. The conversions are handled by
+    /// [`AsPrimitive<f64>`].
+    ///
+    /// [`AsPrimitive<f64>`]: num_traits::AsPrimitive
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn gini_impurity(&self) -> f64 {
+        let sum = self.sum_squared_probabilities();
+        // The probabilities of an empty multiset are all NaN, or there are
+        // none when SIZE is 0.
+        if sum.is_nan() || sum == 0.0 {
+            0.0
+        } else {
+            1.0 - sum
+        }
+    }
+
+    // The sum of the squared probabilities of the elements, shared by
+    // collision_entropy and gini_impurity.
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    fn sum_squared_probabilities(&self) -> f64 {
+        let total: f64 = self.total_wide() as f64;
+        compensated_sum(self.into_iter().map(|count| {
+            let freq_f64: f64 = count.as_();
+            (freq_f64 / total).powf(2.0)
+        }))
+    }
+
+    /// Calculate the shannon entropy of the multiset. Uses ln rather than log2.
+    ///
+    /// The entropy of an empty multiset is 0.0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([2u8, 1, 1, 0]);
+    /// let result = multiset.shannon_entropy();
+    /// // approximate: result == 1.0397207708399179
+    /// ```
+    ///
+    /// # Warning
+    /// Should not be used if [`Multiset::total`] or any counter in the
+    /// multiset cannot be converted to `f64`. The conversions are handled by
+    /// [`AsPrimitive<f64>`].
+    ///
+    /// [`AsPrimitive<f64>`]: num_traits::AsPrimitive
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn shannon_entropy(&self) -> f64 {
+        let total: f64 = self.total_wide() as f64;
+        compensated_sum(
+            self.into_iter()
+                .filter(|count| count > &&N::zero())
+                .map(|count| {
+                    let freq_f64: f64 = count.as_();
+                    let prob = freq_f64 / total;
+                    -(prob * prob.ln())
+                }),
+        )
+    }
+
+    /// Calculate the shannon entropy of the elements of the multiset, weighted
+    /// by `weights` rather than by their counts. Uses ln rather than log2.
+    ///
+    /// Only elements with a non-zero count contribute, with the weight of
+    /// each element as its probability mass, renormalized over those
+    /// elements. The entropy is 0.0 if no element with a non-zero count has a
+    /// non-zero weight.
+    ///
+    /// # Panics
+    /// Panics if the length of `weights` is not `SIZE`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([3u8, 0, 1, 1]);
+    /// let weights = [1.0, 100.0, 2.0, 1.0];
+    /// let result = multiset.weighted_shannon_entropy(&weights);
+    /// // approximate: result == 1.0397207708399179
+    ///
+    /// // Equal weights give the entropy of the elements present.
+    /// let result = multiset.weighted_shannon_entropy(&[1.0; 4]);
+    /// assert_eq!(result, 3f64.ln());
+    /// ```
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn weighted_shannon_entropy(&self, weights: &[f64]) -> f64 {
+        assert_eq!(weights.len(), SIZE, "weights must have length SIZE");
+        let (total, weighted_log) = self
+            .iter()
+            .zip(weights)
+            .filter(|(count, weight)| **count > N::zero() && **weight > 0.0)
+            .fold((0.0, 0.0), |(total, weighted_log), (_, weight)| {
+                (total + weight, weighted_log + weight * weight.ln())
+            });
+        if total == 0.0 {
+            0.0
+        } else {
+            total.ln() - weighted_log / total
+        }
+    }
+
+    /// Calculate the Kullback-Leibler divergence of `self` from `other`,
+    /// treating both as empirical distributions. Uses ln rather than log2.
+    ///
+    /// Elements with a count of zero in `self` contribute nothing. If `self`
+    /// has a non-zero count for an element which `other` does not, the
+    /// divergence is infinite. The divergence is NaN if either multiset is
+    /// empty, as there is no distribution to compare.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let p = Multiset::from([2u8, 1, 1, 0]);
+    /// let q = Multiset::from([1u8, 1, 1, 1]);
+    /// let result = p.kl_divergence(&q);
+    /// // approximate: result == 0.3465735902799727
+    ///
+    /// assert_eq!(p.kl_divergence(&p), 0.0);
+    /// assert_eq!(q.kl_divergence(&p), f64::INFINITY);
+    /// assert!(p.kl_divergence(&Multiset::empty()).is_nan());
+    /// ```
+    ///
+    /// # Warning
+    /// Should not be used if [`Multiset::total`] or any counter in either
+    /// multiset cannot be converted to `f64`. The conversions are handled by
+    /// [`AsPrimitive<f64>`].
+    ///
+    /// [`AsPrimitive<f64>`]: num_traits::AsPrimitive
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn kl_divergence(&self, other: &Self) -> f64 {
+        let total_p: f64 = self.total_wide() as f64;
+        let total_q: f64 = other.total_wide() as f64;
+        if total_p == 0.0 || total_q == 0.0 {
+            return f64::NAN;
+        }
+        self.iter().zip(other.iter()).fold(0.0, |acc, (p, q)| {
+            if *p == N::zero() {
+                acc
+            } else if *q == N::zero() {
+                f64::INFINITY
+            } else {
+                let prob_p = <N as AsPrimitive<f64>>::as_(*p) / total_p;
+                let prob_q = <N as AsPrimitive<f64>>::as_(*q) / total_q;
+                acc + prob_p * (prob_p / prob_q).ln()
+            }
+        })
+    }
+
+    /// Calculate the cross entropy of `other` relative to `self`, treating
+    /// both as empirical distributions. Uses ln rather than log2.
+    ///
+    /// This is the [`Multiset::shannon_entropy`] of `self` plus the
+    /// [`Multiset::kl_divergence`] of `self` from `other`, calculated in a
+    /// single pass. The edge cases are the same as for the KL divergence:
+    /// elements with a count of zero in `self` contribute nothing, the cross
+    /// entropy is infinite if `other` has a count of zero where `self` does
+    /// not, and NaN if either multiset is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let p = Multiset::from([2u8, 1, 1, 0]);
+    /// let q = Multiset::from([1u8, 1, 1, 1]);
+    /// let result = p.cross_entropy(&q);
+    /// // approximate: result == 1.3862943611198906
+    ///
+    /// assert_eq!(q.cross_entropy(&p), f64::INFINITY);
+    /// ```
+    ///
+    /// # Warning
+    /// Should not be used if [`Multiset::total`] or any counter in either
+    /// multiset cannot be converted to `f64`. The conversions are handled by
+    /// [`AsPrimitive<f64>`].
+    ///
+    /// [`AsPrimitive<f64>`]: num_traits::AsPrimitive
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn cross_entropy(&self, other: &Self) -> f64 {
+        let total_p: f64 = self.total_wide() as f64;
+        let total_q: f64 = other.total_wide() as f64;
+        if total_p == 0.0 || total_q == 0.0 {
+            return f64::NAN;
+        }
+        -self.iter().zip(other.iter()).fold(0.0, |acc, (p, q)| {
+            if *p == N::zero() {
+                acc
+            } else if *q == N::zero() {
+                f64::NEG_INFINITY
+            } else {
+                let prob_p = <N as AsPrimitive<f64>>::as_(*p) / total_p;
+                let prob_q = <N as AsPrimitive<f64>>::as_(*q) / total_q;
+                acc + prob_p * prob_q.ln()
+            }
+        })
+    }
+
+    /// Calculate the cross entropy of `other` relative to `self` in bits,
+    /// using log2. See [`Multiset::cross_entropy`] for the edge cases.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let p = Multiset::from([2u8, 1, 1, 0]);
+    /// let q = Multiset::from([1u8, 1, 1, 1]);
+    /// assert_eq!(p.cross_entropy_bits(&q), 2.0);
+    /// ```
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn cross_entropy_bits(&self, other: &Self) -> f64 {
+        self.cross_entropy(other) / std::f64::consts::LN_2
+    }
+
+    /// Calculate the Jaccard index of `self` and `other`, the total of their
+    /// intersection divided by the total of their union.
+    ///
+    /// Both totals are found in a single pass without constructing either
+    /// multiset, and are accumulated in `u128` so they cannot overflow. Two
+    /// empty multisets are identical, so have an index of 1.0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let a = Multiset::from([1u8, 2, 0, 4]);
+    /// let b = Multiset::from([0u8, 3, 1, 4]);
+    /// assert_eq!(a.jaccard_index(&b), 6.0 / 9.0);
+    /// assert_eq!(Multiset::<u8, 4>::empty().jaccard_index(&Multiset::empty()), 1.0);
+    /// ```
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn jaccard_index(&self, other: &Self) -> f64 {
+        let (intersection, union) =
+            self.iter()
+                .zip(other.iter())
+                .fold((0u128, 0u128), |(min_acc, max_acc), (a, b)| {
+                    (
+                        min_acc + <N as AsPrimitive<u128>>::as_(*a.min(b)),
+                        max_acc + <N as AsPrimitive<u128>>::as_(*a.max(b)),
+                    )
+                });
+        if union == 0 {
+            1.0
+        } else {
+            intersection as f64 / union as f64
+        }
+    }
+
+    /// Calculate the overlap coefficient of `self` and `other`, the total of
+    /// their intersection divided by the smaller of their totals.
+    ///
+    /// The coefficient is exactly 1.0 whenever one non-empty multiset is a
+    /// subset of the other. If either multiset is empty the coefficient is
+    /// 0.0, even though the empty multiset is a subset of every multiset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let a = Multiset::from([1u8, 2, 0, 4]);
+    /// let b = Multiset::from([0u8, 3, 1, 4]);
+    /// assert_eq!(a.overlap_coefficient(&b), 6.0 / 7.0);
+    ///
+    /// let c = Multiset::from([1u8, 3, 1, 9]);
+    /// assert_eq!(a.overlap_coefficient(&c), 1.0);
+    /// assert_eq!(Multiset::empty().overlap_coefficient(&c), 0.0);
+    /// ```
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn overlap_coefficient(&self, other: &Self) -> f64 {
+        let total = self.total_wide().min(other.total_wide());
+        if total == 0 {
+            0.0
+        } else {
+            self.count_intersection(other) as f64 / total as f64
+        }
+    }
+
+    /// Calculate the Tversky index of `self` and `other`, weighting the
+    /// total of `self` not in `other` by `alpha` and the total of `other` not
+    /// in `self` by `beta`.
+    ///
+    /// With `alpha` and `beta` both 1.0 this is the Jaccard index, and with
+    /// both 0.5 it is the Sørensen–Dice coefficient. The three totals are
+    /// found in a single pass, accumulated in `u128`. Two empty multisets have
+    /// an index of 1.0, and multisets with nothing in common have an index of
+    /// 0.0 whatever the weights.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let a = Multiset::from([1u8, 2, 0, 4]);
+    /// let b = Multiset::from([0u8, 3, 1, 4]);
+    /// assert_eq!(a.tversky_index(&b, 1.0, 1.0), a.jaccard_index(&b));
+    /// assert_eq!(a.tversky_index(&b, 0.5, 0.5), 12.0 / 15.0);
+    /// // Only elements of `a` missing from `b` count against it.
+    /// assert_eq!(a.tversky_index(&b, 1.0, 0.0), 6.0 / 7.0);
+    /// ```
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn tversky_index(&self, other: &Self, alpha: f64, beta: f64) -> f64 {
+        let (common, self_only, other_only) = self.iter().zip(other.iter()).fold(
+            (0u128, 0u128, 0u128),
+            |(common, self_only, other_only), (a, b)| {
+                let min = *a.min(b);
+                (
+                    common + <N as AsPrimitive<u128>>::as_(min),
+                    self_only + <N as AsPrimitive<u128>>::as_(*a - min),
+                    other_only + <N as AsPrimitive<u128>>::as_(*b - min),
+                )
+            },
+        );
+        if common == 0 {
+            return if self_only == 0 && other_only == 0 {
+                1.0
+            } else {
+                0.0
+            };
+        }
+        let common = common as f64;
+        common / (common + alpha * self_only as f64 + beta * other_only as f64)
+    }
+
+    /// Returns the dot product of the counts of `self` and `other`.
+    ///
+    /// The products are accumulated in `u128`, so the result is exact unless
+    /// it is larger than `u64::MAX`, where it saturates. This can only happen
+    /// with `u32` or wider counters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let a = Multiset::from([1u16, 2, 0, 4]);
+    /// let b = Multiset::from([5u16, 3, 9, 1]);
+    /// assert_eq!(a.dot(&b), 15);
+    ///
+    /// let max = Multiset::<u16, 64>::repeat(u16::MAX);
+    /// assert_eq!(max.dot(&max), 64 * (u16::MAX as u64).pow(2));
+    /// ```
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn dot(&self, other: &Self) -> u64 {
+        let dot: u128 = self
+            .iter()
+            .zip(other.iter())
+            .map(|(a, b)| <N as AsPrimitive<u128>>::as_(*a) * <N as AsPrimitive<u128>>::as_(*b))
+            .fold(0, u128::saturating_add);
+        u64::try_from(dot).unwrap_or(u64::MAX)
+    }
+
+    /// Returns the Manhattan (L1) distance between the counts of `self` and
+    /// `other`, the sum of the absolute differences of each pair of counts.
+    ///
+    /// This is equal to `self.abs_diff(other).total()`, but the sum is
+    /// accumulated in `u64` so cannot overflow the counter. It saturates at
+    /// `u64::MAX`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let a = Multiset::from([1u8, 5, 0, 4]);
+    /// let b = Multiset::from([3u8, 1, 0, 4]);
+    /// assert_eq!(a.l1_distance(&b), 6);
+    /// ```
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn l1_distance(&self, other: &Self) -> u64 {
+        self.iter().zip(other.iter()).fold(0, |acc, (a, b)| {
+            acc.saturating_add(<N as AsPrimitive<u64>>::as_(*a.max(b) - *a.min(b)))
+        })
+    }
+
+    /// Returns the squared Euclidean (L2) distance between the counts of
+    /// `self` and `other`.
+    ///
+    /// Each difference is widened before it is squared, and the squares are
+    /// accumulated in `u128`, so the result is exact unless it is larger than
+    /// `u64::MAX`, where it saturates. Use this rather than
+    /// [`Multiset::l2_distance`] when only comparing distances.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let a = Multiset::from([1u8, 5, 0, 4]);
+    /// let b = Multiset::from([3u8, 1, 0, 4]);
+    /// assert_eq!(a.l2_distance_squared(&b), 20);
+    /// ```
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn l2_distance_squared(&self, other: &Self) -> u64 {
+        u64::try_from(self.l2_distance_squared_wide(other)).unwrap_or(u64::MAX)
+    }
+
+    // The squared L2 distance before it is capped to u64, shared by
+    // l2_distance_squared in both builds and by l2_distance.
+    #[inline]
+    pub(crate) fn l2_distance_squared_wide(&self, other: &Self) -> u128 {
+        self.iter().zip(other.iter()).fold(0u128, |acc, (a, b)| {
+            let diff = <N as AsPrimitive<u128>>::as_(*a.max(b) - *a.min(b));
+            acc.saturating_add(diff * diff)
+        })
+    }
+
+    /// Returns the Euclidean (L2) distance between the counts of `self` and
+    /// `other`, the square root of [`Multiset::l2_distance_squared`].
+    ///
+    /// The root is taken of the sum before it saturates at `u64::MAX`, so
+    /// large distances between `u32`, `u64` and `usize` counts stay accurate.
+    /// If even the `u128` sum saturates, which takes 64-bit counts, the
+    /// squares are summed as `f64` instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let a = Multiset::from([1u8, 5, 0, 4]);
+    /// let b = Multiset::from([4u8, 1, 0, 4]);
+    /// assert_eq!(a.l2_distance(&b), 5.0);
+    /// ```
+    #[inline]
+    pub fn l2_distance(&self, other: &Self) -> f64 {
+        match self.l2_distance_squared_wide(other) {
+            u128::MAX => self
+                .iter()
+                .zip(other.iter())
+                .map(|(a, b)| {
+                    let diff: f64 = (*a.max(b) - *a.min(b)).as_();
+                    diff * diff
+                })
+                .sum::<f64>()
+                .sqrt(),
+            squared => (squared as f64).sqrt(),
+        }
+    }
+
+    /// Calculate the cosine similarity of `self` and `other`, treating their
+    /// counts as vectors.
+    ///
+    /// The dot product and both norms are accumulated as `f64` in a single
+    /// pass. If either multiset is empty the similarity is 0.0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let a = Multiset::from([1u16, 2, 0, 2]);
+    /// let b = Multiset::from([2u16, 0, 0, 0]);
+    /// assert_eq!(a.cosine_similarity(&b), 1.0 / 3.0);
+    /// assert_eq!(a.cosine_similarity(&(a * 7)), 1.0);
+    /// assert_eq!(a.cosine_similarity(&Multiset::empty()), 0.0);
+    /// ```
+    ///
+    /// # Warning
+    /// Should not be used if any counter in either multiset cannot be
+    /// converted to `f64`. The conversions are handled by
+    /// [`AsPrimitive<f64>`].
+    ///
+    /// [`AsPrimitive<f64>`]: num_traits::AsPrimitive
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn cosine_similarity(&self, other: &Self) -> f64 {
+        let (dot, norm_a, norm_b) =
+            self.iter()
+                .zip(other.iter())
+                .fold((0.0, 0.0, 0.0), |(dot, norm_a, norm_b), (a, b)| {
+                    let a: f64 = a.as_();
+                    let b: f64 = b.as_();
+                    (dot + a * b, norm_a + a * a, norm_b + b * b)
+                });
+        if norm_a == 0.0 || norm_b == 0.0 {
+            0.0
+        } else {
+            dot / (norm_a.sqrt() * norm_b.sqrt())
+        }
+    }
+
+    /// Calculate the Hellinger distance between `self` and `other`, treating
+    /// both as empirical distributions.
+    ///
+    /// The distance is always in the range 0.0 to 1.0. Two empty multisets
+    /// have a distance of 0.0, and an empty multiset has a distance of 1.0
+    /// from any other.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let a = Multiset::from([1u8, 1, 0, 0]);
+    /// let b = Multiset::from([0u8, 0, 3, 1]);
+    /// assert_eq!(a.hellinger_distance(&b), 1.0);
+    /// assert_eq!(a.hellinger_distance(&(a * 3)), 0.0);
+    ///
+    /// let c = Multiset::from([1u8, 0, 0, 0]);
+    /// let result = a.hellinger_distance(&c);
+    /// // approximate: result == 0.5411961001461969
+    /// ```
+    ///
+    /// # Warning
+    /// Should not be used if [`Multiset::total`] or any counter in either
+    /// multiset cannot be converted to `f64`. The conversions are handled by
+    /// [`AsPrimitive<f64>`].
+    ///
+    /// [`AsPrimitive<f64>`]: num_traits::AsPrimitive
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn hellinger_distance(&self, other: &Self) -> f64 {
+        let total_p: f64 = self.total_wide() as f64;
+        let total_q: f64 = other.total_wide() as f64;
+        if total_p == 0.0 || total_q == 0.0 {
+            return if total_p == total_q { 0.0 } else { 1.0 };
+        }
+        let coefficient: f64 = self
+            .iter()
+            .zip(other.iter())
+            .map(|(p, q)| {
+                let prob_p = <N as AsPrimitive<f64>>::as_(*p) / total_p;
+                let prob_q = <N as AsPrimitive<f64>>::as_(*q) / total_q;
+                (prob_p * prob_q).sqrt()
+            })
+            .sum();
+        // Rounding can push the coefficient just over 1.0 for equal
+        // distributions.
+        (1.0 - coefficient).max(0.0).sqrt()
+    }
+
+    /// Calculate the one dimensional earth mover's distance between `self` and
+    /// `other`, treating both as empirical distributions over the elements in
+    /// order.
+    ///
+    /// This is the amount of probability mass which must be moved, multiplied
+    /// by the number of elements it is moved across, to turn one distribution
+    /// into the other. It is found as the sum of the absolute differences of
+    /// the cumulative distributions. As for [`Multiset::hellinger_distance`],
+    /// two empty multisets have a distance of 0.0, and an empty multiset has a
+    /// distance of 1.0 from any other.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let a = Multiset::from([1u8, 1, 0, 0]);
+    /// let b = Multiset::from([0u8, 1, 0, 1]);
+    /// // Half the mass moves across three elements.
+    /// assert_eq!(a.emd_1d(&b), 1.5);
+    /// ```
+    ///
+    /// # Warning
+    /// Should not be used if [`Multiset::total`] or any counter in either
+    /// multiset cannot be converted to `f64`. The conversions are handled by
+    /// [`AsPrimitive<f64>`].
+    ///
+    /// [`AsPrimitive<f64>`]: num_traits::AsPrimitive
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn emd_1d(&self, other: &Self) -> f64 {
+        let total_p: f64 = self.total_wide() as f64;
+        let total_q: f64 = other.total_wide() as f64;
+        if total_p == 0.0 || total_q == 0.0 {
+            return if total_p == total_q { 0.0 } else { 1.0 };
+        }
+        // The running difference of the cumulative distributions is a prefix
+        // sum of the differences of the probabilities.
+        let (_, res) =
+            self.iter()
+                .zip(other.iter())
+                .fold((0.0, 0.0), |(cumulative, acc), (p, q)| {
+                    let prob_p = <N as AsPrimitive<f64>>::as_(*p) / total_p;
+                    let prob_q = <N as AsPrimitive<f64>>::as_(*q) / total_q;
+                    let cumulative: f64 = cumulative + prob_p - prob_q;
+                    (cumulative, acc + cumulative.abs())
+                });
+        res
+    }
+
+    /// Calculate the chi-squared distance between the counts of `self` and
+    /// `other`, half the sum of `(a - b)² / (a + b)` over each pair of
+    /// counts. Elements with a count of zero in both are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let a = Multiset::from([1u8, 3, 0, 0]);
+    /// let b = Multiset::from([1u8, 1, 2, 0]);
+    /// assert_eq!(a.chi_squared_distance(&b), 1.5);
+    /// assert_eq!(a.chi_squared_distance(&a), 0.0);
+    /// ```
+    ///
+    /// # Warning
+    /// Should not be used if any counter in either multiset cannot be
+    /// converted to `f64`. The conversions are handled by
+    /// [`AsPrimitive<f64>`].
+    ///
+    /// [`AsPrimitive<f64>`]: num_traits::AsPrimitive
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    pub fn chi_squared_distance(&self, other: &Self) -> f64 {
+        self.iter()
+            .zip(other.iter())
+            .filter(|(a, b)| **a > N::zero() || **b > N::zero())
+            .map(|(a, b)| {
+                let a: f64 = a.as_();
+                let b: f64 = b.as_();
+                (a - b).powi(2) / (a + b)
+            })
+            .sum::<f64>()
+            / 2.0
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Byte counting
+////////////////////////////////////////////////////////////////////////////////
+
+impl<N: Counter> Multiset<N, 256> {
+    /// Constructs a Multiset over the byte alphabet, counting the occurrences
+    /// of each byte value in `bytes`. Counts saturate at the maximum value of
+    /// the counter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::<u16, 256>::from_bytes(b"hello");
+    /// assert_eq!(multiset[b'l' as usize], 2);
+    /// assert_eq!(multiset.total(), 5);
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        // Counting into several interleaved tables avoids consecutive
+        // increments of the same counter depending on each other, which is
+        // the bottleneck for low-entropy input.
+        let mut tables = [[0usize; 256]; 4];
+        let chunks = bytes.chunks_exact(4);
+        let remainder = chunks.remainder();
+        for chunk in chunks {
+            tables[0][chunk[0] as usize] += 1;
+            tables[1][chunk[1] as usize] += 1;
+            tables[2][chunk[2] as usize] += 1;
+            tables[3][chunk[3] as usize] += 1;
+        }
+        for &b in remainder {
+            tables[0][b as usize] += 1;
+        }
+        let mut res = Multiset::empty();
+        for (i, r) in res.iter_mut().enumerate() {
+            let count = tables[0][i] + tables[1][i] + tables[2][i] + tables[3][i];
+            *r = <N as NumCast>::from(count).unwrap_or_else(N::max_value);
+        }
+        res
+    }
+}
+
+/// Counts the occurrences of each byte value in `bytes`.
+///
+/// This is equivalent to [`Multiset::from_bytes`] with `u32` counters.
+///
+/// # Examples
+///
+/// ```
+/// use utote::count_bytes;
+///
+/// let multiset = count_bytes(&[0, 0, 0, 0]);
+/// assert_eq!(multiset[0], 4);
+/// assert_eq!(multiset.shannon_entropy(), 0.0);
+/// ```
+pub fn count_bytes(bytes: &[u8]) -> Multiset<u32, 256> {
+    Multiset::from_bytes(bytes)
+}
+
+// Adds up `terms` with Neumaier's variant of Kahan summation, so that many
+// small terms are not lost when added to a much larger sum. The sum of no
+// terms, or only zeros, is 0.0 rather than -0.0.
+#[inline]
+pub(crate) fn compensated_sum<I: IntoIterator<Item = f64>>(terms: I) -> f64 {
+    let (sum, compensation) =
+        terms
+            .into_iter()
+            .fold((0.0, 0.0), |(sum, compensation), term: f64| {
+                let next = sum + term;
+                let lost = if f64::abs(sum) >= term.abs() {
+                    (sum - next) + term
+                } else {
+                    (term - next) + sum
+                };
+                (next, compensation + lost)
+            });
+    sum + compensation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ord::ByTotal;
+    use crate::strategy::counts_up_to;
+    use approx::assert_relative_eq;
+    #[cfg(feature = "rand")]
+    use proptest::arbitrary::any;
+    use proptest::arbitrary::Arbitrary;
+    use proptest::{prop_assert, prop_assert_eq, proptest};
+    #[cfg(feature = "rand")]
+    use rand::prelude::*;
+    use std::panic;
+
+    fn catch_unwind_silent<F: FnOnce() -> R + panic::UnwindSafe, R>(
+        f: F,
+    ) -> std::thread::Result<R> {
+        let prev_hook = panic::take_hook();
+        panic::set_hook(Box::new(|_| {}));
+        let result = panic::catch_unwind(f);
+        panic::set_hook(prev_hook);
+        result
+    }
+
+    // Deterministic pseudo-random counts in `0..=max`, for comparing methods
+    // against reference implementations.
+    fn pseudo_random<N: Counter, const SIZE: usize>(seed: u64, max: u64) -> Multiset<N, SIZE> {
+        let mut state = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (0..SIZE)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                // A max of u64::MAX allows every value of the state.
+                let value = match max.checked_add(1) {
+                    Some(bound) => state % bound,
+                    None => state,
+                };
+                <N as NumCast>::from(value).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Multiset::<u8, 4>::empty().to_string(), "{}");
+        assert_eq!(Multiset::from([0u8, 0, 5, 0]).to_string(), "{2: 5}");
+        assert_eq!(Multiset::from([0u16, 0, 0, 300]).to_string(), "{3: 300}");
+        assert_eq!(
+            Multiset::from([1u32, 0, 2, 9]).to_string(),
+            "{0: 1, 2: 2, 3: 9}"
+        );
+    }
+
+    #[test]
+    fn test_debug() {
+        assert_eq!(
+            format!("{:?}", Multiset::<u8, 4>::empty()),
+            "Multiset { size: 4, total: 0, counts: {} }"
+        );
+        assert_eq!(
+            format!("{:?}", Multiset::from([0u16, 3, 0, 2])),
+            "Multiset { size: 4, total: 5, counts: {1: 3, 3: 2} }"
+        );
+
+        let large = Multiset::<u8, 20>::repeat(1);
+        assert_eq!(
+            format!("{:?}", large),
+            "Multiset { size: 20, total: 20, counts: {0: 1, 1: 1, 2: 1, 3: 1, \
+             4: 1, 5: 1, 6: 1, 7: 1, 8: 1, 9: 1, 10: 1, 11: 1, 12: 1, 13: 1, \
+             14: 1, 15: 1, ... (4 more)} }"
+        );
+
+        let max = u64::MAX;
+        assert_eq!(
+            format!("{:?}", Multiset::<u64, 3>::repeat(max)),
+            format!(
+                "Multiset {{ size: 3, total: {}, counts: {{0: {}, 1: {}, 2: {}}} }}",
+                max as u128 * 3,
+                max,
+                max,
+                max
+            )
+        );
+    }
+
+    #[test]
+    fn test_from_str() {
+        let set: Multiset<u8, 20> = "0:3,4:1,17:2".parse().unwrap();
+        assert_eq!(set.get(0), Some(&3));
+        assert_eq!(set.get(4), Some(&1));
+        assert_eq!(set.get(17), Some(&2));
+        assert_eq!(set.total(), 6);
+
+        let spaced: Multiset<u8, 20> = " 0 : 3 ,4:1 , 17: 2 ".parse().unwrap();
+        assert_eq!(spaced, set);
+
+        let empty: Multiset<u8, 20> = "".parse().unwrap();
+        assert_eq!(empty, Multiset::empty());
+        let empty_braces: Multiset<u8, 20> = "{}".parse().unwrap();
+        assert_eq!(empty_braces, Multiset::empty());
+    }
+
+    #[test]
+    fn test_from_str_errors() {
+        assert_eq!(
+            "0:3,4".parse::<Multiset<u8, 5>>(),
+            Err(MultisetError::InvalidPair {
+                pair: "4".to_string()
+            })
+        );
+        assert_eq!(
+            "a:1".parse::<Multiset<u8, 5>>(),
+            Err(MultisetError::InvalidPair {
+                pair: "a:1".to_string()
+            })
+        );
+        assert_eq!(
+            "1:2,1:2".parse::<Multiset<u8, 5>>(),
+            Err(MultisetError::DuplicateElement { elem: 1 })
+        );
+        assert_eq!(
+            "5:1".parse::<Multiset<u8, 5>>(),
+            Err(MultisetError::ElementOutOfBounds { elem: 5, size: 5 })
+        );
+        assert_eq!(
+            "2:256".parse::<Multiset<u8, 5>>(),
+            Err(MultisetError::CountOutOfRange { elem: 2 })
+        );
+    }
+
+    #[test]
+    fn test_sparse_string_round_trip() {
+        let set = Multiset::from([0u16, 7, 0, 0, 1000, 1]);
+        assert_eq!(set.to_sparse_string(), "1:7,4:1000,5:1");
+        assert_eq!(set.to_sparse_string().parse(), Ok(set));
+        assert_eq!(set.to_string().parse(), Ok(set));
+
+        let empty = Multiset::<u16, 6>::empty();
+        assert_eq!(empty.to_sparse_string().parse(), Ok(empty));
+        assert_eq!(empty.to_string().parse(), Ok(empty));
+    }
+
+    #[test]
+    fn test_index() {
+        let set = Multiset::from([1u8, 2, 3, 4]);
+        assert_eq!(set.index(1), &2);
+        assert_eq!(set.index(2..), &[3, 4]);
+    }
+
+    #[test]
+    fn test_index_mut() {
+        let mut set = Multiset::from([1u8, 2, 3, 4]);
+        *set.index_mut(1) = 3;
+        assert_eq!(set, Multiset::from([1u8, 3, 3, 4]));
+        set.index_mut(2..).iter_mut().for_each(|e| *e = 5);
+        assert_eq!(set, Multiset::from([1u8, 3, 5, 5]))
+    }
+
+    #[test]
+    fn test_add() {
+        let set = Multiset::from([2u8, 2, 2, 2]);
+        assert_eq!(set + set, Multiset::from([4u8, 4, 4, 4]));
+        assert_eq!(set + 3, Multiset::from([5u8, 5, 5, 5]));
+
+        let mut set_assign = Multiset::from([3u8, 3, 3, 3]);
+        set_assign += set;
+        assert_eq!(set_assign, Multiset::from([5u8, 5, 5, 5]));
+        set_assign += 2;
+        assert_eq!(set_assign, Multiset::from([7u8, 7, 7, 7]));
+    }
+
+    #[test]
+    #[allow(clippy::op_ref)]
+    fn test_add_saturating() {
+        let set = Multiset::from([200u8, 100, 0, 255]);
+        assert_eq!(set + set, Multiset::from([255u8, 200, 0, 255]));
+        assert_eq!(set + 100, Multiset::from([255u8, 200, 100, 255]));
+
+        proptest!(|(a in counts_up_to::<u8, 37>(255), b in counts_up_to::<u8, 37>(255))| {
+            prop_assert_eq!(a + b, a.sum(&b));
+            prop_assert_eq!(&a + &b, a.sum(&b));
+            let mut assign = a;
+            assign += &b;
+            prop_assert_eq!(assign, a.sum(&b));
+        });
+    }
+
+    #[test]
+    fn test_iter_sum() {
+        let sets = [
+            Multiset::from([1u8, 0, 2, 0]),
+            Multiset::from([3u8, 1, 0, 0]),
+            Multiset::from([0u8, 4, 250, 0]),
+        ];
+        let expected = Multiset::from([4u8, 5, 252, 0]);
+        assert_eq!(sets.iter().sum::<Multiset<u8, 4>>(), expected);
+        assert_eq!(
+            IntoIterator::into_iter(sets).sum::<Multiset<u8, 4>>(),
+            expected
+        );
+        assert_eq!(
+            sets.iter().chain(sets.iter()).sum::<Multiset<u8, 4>>(),
+            Multiset::from([8u8, 10, 255, 0])
+        );
+        assert_eq!(
+            std::iter::empty::<Multiset<u8, 4>>().sum::<Multiset<u8, 4>>(),
+            Multiset::empty()
+        );
+    }
+
+    #[test]
+    fn test_merge_from() {
+        let sets = [
+            pseudo_random::<u16, 37>(1, 1000),
+            pseudo_random::<u16, 37>(2, 1000),
+            pseudo_random::<u16, 37>(3, 1000),
+        ];
+        let mut merged = Multiset::empty();
+        merged.merge_from(&sets);
+        assert_eq!(merged, sets[0].sum(&sets[1]).sum(&sets[2]));
+        assert_eq!(merged, sets.iter().sum());
+
+        let before = merged;
+        merged.merge_from(std::iter::empty());
+        assert_eq!(merged, before);
+
+        let mut saturated = Multiset::from([u8::MAX - 1, 0]);
+        saturated.merge_from(&[Multiset::from([1, 1]), Multiset::from([1, 1])]);
+        assert_eq!(saturated, Multiset::from([u8::MAX, 2]));
+    }
+
+    #[test]
+    fn test_div() {
+        let set = Multiset::from([10u8, 10, 10, 10]);
+        assert_eq!(set / set, Multiset::from([1u8, 1, 1, 1]));
+        assert_eq!(set / 2, Multiset::from([5u8, 5, 5, 5]));
+
+        let mut set_assign = Multiset::from([20u8, 20, 20, 20]);
+        set_assign /= set;
+        assert_eq!(set_assign, Multiset::from([2u8, 2, 2, 2]));
+        set_assign /= 2;
+        assert_eq!(set_assign, Multiset::from([1u8, 1, 1, 1]));
+    }
+
+    #[test]
+    fn test_mul() {
+        let set = Multiset::from([2u8, 2, 2, 2]);
+        assert_eq!(set * set, Multiset::from([4u8, 4, 4, 4]));
+        assert_eq!(set * 3, Multiset::from([6u8, 6, 6, 6]));
+
+        let mut set_assign = Multiset::from([3u8, 3, 3, 3]);
+        set_assign *= set;
+        assert_eq!(set_assign, Multiset::from([6u8, 6, 6, 6]));
+        set_assign *= 2;
+        assert_eq!(set_assign, Multiset::from([12u8, 12, 12, 12]));
+    }
+
+    #[test]
+    fn test_mul_scalar_saturating() {
+        let mut set = Multiset::from([1u8, 3, 0, 7]);
+        for _ in 0..8 {
+            set *= 2;
+        }
+        assert_eq!(set, Multiset::from([255u8, 255, 0, 255]));
+        assert_eq!(
+            Multiset::from([1u16, 3, 0, 7]) * 1000,
+            Multiset::from([1000u16, 3000, 0, 7000])
+        );
+
+        let max = Multiset::<u64, 3>::repeat(u64::MAX);
+        assert_eq!(max * max, max);
+        let mut set = Multiset::from([16u8, 15, 0, 1]);
+        set *= Multiset::from([16u8, 17, 255, 255]);
+        assert_eq!(set, Multiset::from([255u8, 255, 0, 255]));
+    }
+
+    #[test]
+    fn test_div_scalar() {
+        proptest!(|(set in counts_up_to::<u32, 37>(1000))| {
+            for d in 1..10u32 {
+                let total = set.total() as u32;
+                let scaled = (set / d).total() as u32;
+                // Each of the 37 counts loses less than 1 to rounding.
+                prop_assert!(scaled <= total / d);
+                prop_assert!(scaled + 37 > total / d);
+            }
+        });
+    }
+
+    #[test]
+    fn test_div_scalar_by_zero() {
+        let res = catch_unwind_silent(|| Multiset::from([1u8, 2, 3]) / 0);
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_rem() {
+        let set = Multiset::from([10u8, 10, 10, 10]);
+        assert_eq!(set % set, Multiset::from([0u8, 0, 0, 0]));
+        assert_eq!(set % 3, Multiset::from([1u8, 1, 1, 1]));
+
+        let mut set_assign = Multiset::from([3u8, 3, 3, 3]);
+        set_assign %= set;
+        assert_eq!(set_assign, Multiset::from([3u8, 3, 3, 3]));
+        set_assign %= 2;
+        assert_eq!(set_assign, Multiset::from([1u8, 1, 1, 1]));
+    }
+
+    #[test]
+    fn test_sub() {
+        let set = Multiset::from([5u8, 5, 5, 5]);
+        assert_eq!(set - set, Multiset::from([0u8, 0, 0, 0]));
+        assert_eq!(set - 3, Multiset::from([2u8, 2, 2, 2]));
+
+        let mut set_assign = Multiset::from([8u8, 8, 8, 8]);
+        set_assign -= set;
+        assert_eq!(set_assign, Multiset::from([3u8, 3, 3, 3]));
+        set_assign -= 2;
+        assert_eq!(set_assign, Multiset::from([1u8, 1, 1, 1]));
+    }
+
+    #[test]
+    #[allow(clippy::op_ref)]
+    fn test_sub_saturating() {
+        let set = Multiset::from([5u8, 1, 0, 3]);
+        assert_eq!(set - 2, Multiset::from([3u8, 0, 0, 1]));
+        assert_eq!(set - set, Multiset::empty());
+        assert_eq!(set - Multiset::from([9u8, 9, 9, 9]), Multiset::empty());
+
+        proptest!(|(a in counts_up_to::<u8, 37>(255), b in counts_up_to::<u8, 37>(255))| {
+            prop_assert_eq!(a - a, Multiset::empty());
+            prop_assert_eq!(a - a.union(&b), Multiset::empty());
+            prop_assert_eq!(a - b, a.difference(&b));
+            prop_assert_eq!(&a - &b, a.difference(&b));
+            let mut assign = a;
+            assign -= &b;
+            prop_assert_eq!(assign, a.difference(&b));
+        });
+    }
+
+    #[test]
+    #[allow(clippy::op_ref)]
+    fn test_bitand_bitor() {
+        proptest!(|(a in counts_up_to::<u16, 37>(10), b in counts_up_to::<u16, 37>(10))| {
+            prop_assert_eq!(a & b, a.intersection(&b));
+            prop_assert_eq!(&a & &b, a.intersection(&b));
+            prop_assert_eq!(a | b, a.union(&b));
+            prop_assert_eq!(&a | &b, a.union(&b));
+
+            let mut and_assign = a;
+            and_assign &= &b;
+            prop_assert_eq!(and_assign, a.intersection(&b));
+            let mut or_assign = a;
+            or_assign |= b;
+            prop_assert_eq!(or_assign, a.union(&b));
+        });
+    }
+
+    #[test]
+    fn test_eq() {
+        // 37 is not a multiple of any SIMD lane count.
+        let a = pseudo_random::<u16, 37>(1, 10);
+        let mut b = a;
+        assert_eq!(a, b);
+        b[36] += 1;
+        assert_ne!(a, b);
+        b[36] -= 1;
+        b[0] += 1;
+        assert_ne!(a, b);
+
+        assert_eq!(Multiset::<u8, 0>::empty(), Multiset::empty());
+        assert_eq!(Multiset::<u64, 1>::from([3]), Multiset::from([3]));
+        assert_ne!(Multiset::<u64, 1>::from([3]), Multiset::from([4]));
+    }
+
+    #[test]
+    #[allow(
+        clippy::neg_cmp_op_on_partial_ord,
+        clippy::double_comparisons,
+        clippy::eq_op
+    )]
+    fn test_partial_cmp() {
+        let a = Multiset::from([1u8, 2, 0, 3]);
+        let sub = Multiset::from([1u8, 1, 0, 3]);
+        let other = Multiset::from([0u8, 3, 0, 3]);
+
+        assert_eq!(a.partial_cmp(&a), Some(Ordering::Equal));
+        assert_eq!(sub.partial_cmp(&a), Some(Ordering::Less));
+        assert_eq!(a.partial_cmp(&sub), Some(Ordering::Greater));
+        assert_eq!(a.partial_cmp(&other), None);
+        assert_eq!(other.partial_cmp(&a), None);
+
+        assert!(sub < a && sub <= a && a > sub && a >= sub);
+        assert!(a <= a && a >= a && !(a < a) && !(a > a));
+        assert!(!(a < other) && !(a <= other) && !(a > other) && !(a >= other));
+
+        proptest!(|(x in counts_up_to::<u16, 37>(10), y in counts_up_to::<u16, 37>(10))| {
+            let expected = match (x.is_subset(&y), x.is_superset(&y)) {
+                (true, true) => Some(Ordering::Equal),
+                (true, false) => Some(Ordering::Less),
+                (false, true) => Some(Ordering::Greater),
+                (false, false) => None,
+            };
+            prop_assert_eq!(x.partial_cmp(&y), expected);
+            prop_assert!(x.intersection(&y) <= x.union(&y));
+        });
+    }
+
+    #[test]
+    fn test_cmp_lexicographic() {
+        proptest!(|(a in counts_up_to::<u16, 37>(2), b in counts_up_to::<u16, 37>(2))| {
+            let expected = a.as_slice().cmp(b.as_slice());
+            prop_assert_eq!(a.cmp_lexicographic(&b), expected);
+            prop_assert_eq!(b.cmp_lexicographic(&a), expected.reverse());
+            prop_assert_eq!(Lex(a).cmp(&Lex(b)), expected);
+            prop_assert_eq!(a.cmp_lexicographic(&a), Ordering::Equal);
+        });
+
+        let mut sets: Vec<_> = (0..10)
+            .map(|seed| Lex(pseudo_random::<u8, 5>(seed, 3)))
+            .collect();
+        sets.sort();
+        assert!(sets
+            .windows(2)
+            .all(|w| w[0].0.as_slice() <= w[1].0.as_slice()));
+    }
+
+    // A multiset is exactly as large as its array of counts.
+    const _: () = assert!(std::mem::size_of::<Multiset<u16, 4096>>() == 8192);
+
+    #[test]
+    #[allow(clippy::clone_on_copy)]
+    fn test_clone() {
+        let a = pseudo_random::<u16, 4096>(1, 10);
+        let b = a.clone();
+        let c = a;
+        assert_eq!(a, b);
+        assert_eq!(a, c);
+
+        let mut d = b.clone();
+        d.insert(0, 1);
+        assert_ne!(d, b);
+    }
+
+    #[test]
+    fn test_hash() {
+        use std::collections::hash_map::DefaultHasher;
+
+        fn hash_of<T: Hash>(value: &T) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let a = pseudo_random::<u16, 37>(1, 10);
+        let b = Multiset::from(a.to_array());
+        let c = pseudo_random::<u16, 37>(2, 10);
+        assert_eq!(hash_of(&a), hash_of(&b));
+        assert_ne!(hash_of(&a), hash_of(&c));
+
+        let mut memo = HashMap::new();
+        memo.insert(a, "a");
+        memo.insert(c, "c");
+        assert_eq!(memo.get(&b), Some(&"a"));
+        assert_eq!(memo.get(&c), Some(&"c"));
+        assert_eq!(memo.get(&Multiset::empty()), None);
+    }
+
+    #[test]
+    fn test_zip_map() {
+        let set1: Multiset<u8, 4> = Multiset::from([1, 5, 2, 8]);
+        let set2: Multiset<u8, 4> = Multiset::from([1, 5, 2, 8]);
+        let result = set1.zip_map(&set2, |e1, e2| e1 + e2);
+        let expected = Multiset::from([2, 10, 4, 16]);
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn test_len() {
+        assert_eq!(Multiset::<u16, 9>::len(), 9)
+    }
+
+    #[test]
+    fn test_new() {
+        assert_eq!(Multiset::new([1u8, 3]), Multiset::from([1u8, 3]))
+    }
+
+    #[test]
+    fn test_empty() {
+        let result = Multiset::<u32, 7>::empty();
+        let expected = Multiset::from([0u32; 7]);
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn test_default() {
+        let result = Multiset::<u16, 8>::default();
+        assert!(result.is_empty());
+        assert_eq!(result, Multiset::empty());
+
+        let mut taken = Multiset::from([1u8, 2, 3]);
+        assert_eq!(std::mem::take(&mut taken), Multiset::from([1, 2, 3]));
+        assert!(taken.is_empty());
+    }
+
+    #[test]
+    fn test_repeat() {
+        let result = Multiset::<u8, 4>::repeat(3);
+        let expected = Multiset::from([3u8; 4]);
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn test_from_elements() {
+        let into_it = &[0, 1, 1, 2, 2, 2];
+        let result = Multiset::<u8, 4>::from_elements(into_it);
+        assert_eq!(result, Multiset::from([1, 2, 3, 0]))
+    }
+
+    #[test]
+    fn test_from_elements_owned() {
+        let result = Multiset::<u16, 3>::from_elements(vec![2, 0, 2, 2]);
+        assert_eq!(result, Multiset::from([1, 0, 3]));
+
+        let saturated = Multiset::<u8, 2>::from_elements(vec![1; 300]);
+        assert_eq!(saturated, Multiset::from([0, 255]));
+
+        let empty = Multiset::<u8, 2>::from_elements(std::iter::empty::<usize>());
+        assert_eq!(empty, Multiset::empty());
+    }
+
+    #[test]
+    fn test_from_elements_panic() {
+        let into_it = &[9]; // contains a value larger than the multiset size
+        let res = catch_unwind_silent(|| Multiset::<u8, 4>::from_elements(into_it));
+        assert!(res.is_err())
+    }
+
+    #[test]
+    fn test_try_from_iter() {
+        assert_eq!(
+            Multiset::<u8, 4>::try_from_iter(vec![1, 2, 3]),
+            Err(MultisetError::WrongIterLength {
+                expected: 4,
+                consumed: 3
+            })
+        );
+        assert_eq!(
+            Multiset::<u8, 4>::try_from_iter(vec![1, 2, 3, 4]),
+            Ok(Multiset::from([1, 2, 3, 4]))
+        );
+        assert_eq!(
+            Multiset::<u8, 4>::try_from_iter(vec![1, 2, 3, 4, 5]),
+            Err(MultisetError::WrongIterLength {
+                expected: 4,
+                consumed: 5
+            })
+        );
+    }
+
+    #[test]
+    fn test_from_iter_lenient() {
+        let short: Multiset<u8, 4> = vec![1, 2, 3].into_iter().collect();
+        assert_eq!(short, Multiset::from([1, 2, 3, 0]));
+
+        let long: Multiset<u8, 4> = vec![1, 2, 3, 4, 5].into_iter().collect();
+        assert_eq!(long, Multiset::from([1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn test_from_iter_pairs() {
+        let result: Multiset<u8, 4> = vec![(1, 2), (3, 1), (1, 3)].into_iter().collect();
+        assert_eq!(result, Multiset::from([0, 5, 0, 1]));
+
+        let saturated: Multiset<u8, 2> = vec![(0, 200), (0, 100)].into_iter().collect();
+        assert_eq!(saturated, Multiset::from([255, 0]));
+
+        let empty: Multiset<u8, 3> = std::iter::empty::<(usize, u8)>().collect();
+        assert_eq!(empty, Multiset::empty());
+    }
+
+    #[test]
+    fn test_from_iter_pairs_panic() {
+        let res = catch_unwind_silent(|| vec![(4, 1u8)].into_iter().collect::<Multiset<u8, 4>>());
+        assert!(res.is_err())
+    }
+
+    #[test]
+    fn test_extend() {
+        let mut set = Multiset::<u8, 5>::empty();
+        set.extend(vec![4, 1, 1, 0, 4, 4, 1, 4]);
+        assert_eq!(set, Multiset::from([1, 3, 0, 0, 4]));
+
+        set.extend(vec![(2, 2), (0, 1)]);
+        assert_eq!(set, Multiset::from([2, 3, 2, 0, 4]));
+
+        let mut saturated = Multiset::<u8, 2>::from([254, 0]);
+        saturated.extend(vec![0, 0, 0]);
+        saturated.extend(vec![(1, 255), (1, 1)]);
+        assert_eq!(saturated, Multiset::from([255, 255]));
+    }
+
+    #[test]
+    fn test_extend_panic() {
+        let res = catch_unwind_silent(|| {
+            let mut set = Multiset::<u8, 4>::empty();
+            set.extend(vec![1, 4]);
+            set
+        });
+        assert!(res.is_err())
+    }
+
+    #[test]
+    fn test_extend_unchecked() {
+        let mut set = Multiset::<u16, 4>::empty();
+        unsafe { set.extend_unchecked(vec![3, 3, 0, 3]) };
+        assert_eq!(set, Multiset::from([1, 0, 0, 3]))
+    }
+
+    #[test]
+    fn test_try_from_pairs() {
+        let result = Multiset::<u16, 3>::try_from_pairs(vec![(2, 7), (2, 1), (0, 1)]);
+        assert_eq!(result, Ok(Multiset::from([1, 0, 8])));
+
+        let empty = Multiset::<u16, 3>::try_from_pairs(std::iter::empty());
+        assert_eq!(empty, Ok(Multiset::empty()));
+
+        let err = Multiset::<u16, 3>::try_from_pairs(vec![(0, 1), (3, 1)]);
+        assert_eq!(
+            err,
+            Err(MultisetError::ElementOutOfBounds { elem: 3, size: 3 })
+        );
+    }
+
+    #[test]
+    fn from_array() {
+        let set: Multiset<u16, 3> = Multiset::from([5, 4, 3]);
+        assert_eq!(set.get(1), Some(&4))
+    }
+
+    #[test]
+    fn from_slice() {
+        let set: Multiset<u16, 3> = Multiset::from(&[5, 4, 3]);
+        assert_eq!(set.get(1), Some(&4))
+    }
+
+    #[test]
+    fn try_from_slice() {
+        let exact: &[u16] = &[5, 4, 3];
+        assert_eq!(
+            Multiset::<u16, 3>::try_from(exact),
+            Ok(Multiset::from([5, 4, 3]))
+        );
+
+        let short: &[u16] = &[5, 4];
+        assert_eq!(
+            Multiset::<u16, 3>::try_from(short),
+            Err(MultisetError::WrongLength {
+                expected: 3,
+                got: 2
+            })
+        );
+
+        let long: &[u16] = &[5, 4, 3, 2];
+        assert_eq!(
+            Multiset::<u16, 3>::try_from(long),
+            Err(MultisetError::WrongLength {
+                expected: 3,
+                got: 4
+            })
+        );
+    }
+
+    #[test]
+    fn test_from_slice() {
+        let set = Multiset::<u16, 3>::from_slice(&[5, 4, 3]);
+        assert_eq!(set, Multiset::from([5, 4, 3]));
+
+        let res = catch_unwind_silent(|| Multiset::<u16, 3>::from_slice(&[5, 4]));
+        assert!(res.is_err())
+    }
+
+    #[test]
+    fn to_array() {
+        let set: Multiset<u16, 3> = Multiset::from([5, 4, 3]);
+        let arr: [u16; 3] = set.into();
+        assert_eq!(arr, [5, 4, 3])
+    }
+
+    #[test]
+    fn test_to_array() {
+        let arr = [7u16, 0, 65535, 1];
+        assert_eq!(Multiset::from(arr).to_array(), arr);
+    }
+
+    #[test]
+    fn test_as_slice() {
+        let set = Multiset::from([3u32, 1, 4, 1, 5]);
+        assert_eq!(set.as_slice(), &[3, 1, 4, 1, 5]);
+    }
+
+    #[test]
+    fn test_as_mut_slice() {
+        let mut set = Multiset::from([3u32, 1, 4, 1, 5]);
+        set.as_mut_slice().iter_mut().for_each(|c| *c *= 2);
+        assert_eq!(set.total(), 28);
+        assert_eq!(set, Multiset::from([6, 2, 8, 2, 10]));
+    }
+
+    #[test]
+    fn test_as_ref_as_mut_borrow() {
+        use std::collections::HashSet;
+
+        fn checksum<T: AsRef<[u16]> + ?Sized>(counts: &T) -> u64 {
+            counts
+                .as_ref()
+                .iter()
+                .enumerate()
+                .map(|(i, c)| (i as u64 + 1) * *c as u64)
+                .sum()
+        }
+
+        fn double<T: AsMut<[u16]>>(counts: &mut T) {
+            counts.as_mut().iter_mut().for_each(|c| *c *= 2);
+        }
+
+        let array = [3u16, 1, 4, 1, 5];
+        let mut set = Multiset::from(array);
+        assert_eq!(checksum(&set), checksum(&array));
+        assert_eq!(checksum(&set), 3 + 2 + 12 + 4 + 25);
+
+        double(&mut set);
+        assert_eq!(set, Multiset::from([6, 2, 8, 2, 10]));
+
+        let mut sets = HashSet::new();
+        sets.insert(set);
+        assert!(sets.contains(&[6u16, 2, 8, 2, 10][..]));
+        assert!(!sets.contains(&array[..]));
+    }
+
+    #[test]
+    fn test_to_counts_map() {
+        let set = Multiset::from([0u32, 4, 0, 0, 9]);
+        let map = set.to_counts_map();
+        let mut expected = HashMap::new();
+        expected.insert(1, 4);
+        expected.insert(4, 9);
+        assert_eq!(map, expected);
+
+        let btree = set.to_counts_btree_map();
+        assert_eq!(btree.into_iter().collect::<Vec<_>>(), vec![(1, 4), (4, 9)]);
+
+        assert!(Multiset::<u32, 5>::empty().to_counts_map().is_empty());
+    }
+
+    #[test]
+    fn test_try_from_counts_map() {
+        let set = Multiset::from([0u32, 4, 0, 0, 9]);
+        let map = set.to_counts_map();
+        let imported = Multiset::try_from_counts_map(&map);
+        assert_eq!(imported, Ok(set));
+        assert_eq!(imported.unwrap().to_counts_map(), map);
+
+        let btree = set.to_counts_btree_map();
+        assert_eq!(Multiset::try_from_counts_map(&btree), Ok(set));
+
+        let mut out_of_bounds = BTreeMap::new();
+        out_of_bounds.insert(5, 1u32);
+        assert_eq!(
+            Multiset::<u32, 5>::try_from_counts_map(&out_of_bounds),
+            Err(MultisetError::ElementOutOfBounds { elem: 5, size: 5 })
+        );
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut set = Multiset::<u8, 4>::repeat(3);
+        set.clear();
+        let expected = Multiset::<u8, 4>::empty();
+        assert_eq!(set, expected)
+    }
+
+    #[test]
+    fn test_contains() {
+        let set = Multiset::<u8, 5>::from([1, 0, 1, 0, 1]);
+        [0, 2, 4].iter().for_each(|elem| {
+            assert!(set.contains(*elem));
+        });
+
+        [1, 3].iter().for_each(|elem| {
+            assert!(!set.contains(*elem));
+        });
+
+        [5, 10].iter().for_each(|elem| {
+            assert!(!set.contains(*elem));
+        });
+    }
+
+    #[test]
+    fn test_contains_unchecked() {
+        let set = Multiset::<u8, 5>::from([1, 0, 1, 0, 1]);
+        unsafe {
+            [0, 2, 4].iter().for_each(|elem| {
+                assert!(set.contains_unchecked(*elem));
+            });
+
+            [1, 3].iter().for_each(|elem| {
+                assert!(!set.contains_unchecked(*elem));
+            });
+        }
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut set = Multiset::<u8, 4>::from([1, 1, 1, 1]);
+        assert_eq!(set.get(2), Some(&1));
+        set.insert(2, 5);
+        assert_eq!(set.get(2), Some(&5))
+    }
+
+    #[test]
+    fn test_insert_unchecked() {
+        let mut set = Multiset::<u8, 4>::from([1, 1, 1, 1]);
+        assert_eq!(set.get(2), Some(&1));
+        unsafe { set.insert_unchecked(2, 5) };
+        assert_eq!(set.get(2), Some(&5));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut set = Multiset::<u64, 3>::from([2, 3, 4]);
+        assert_eq!(set.get(1), Some(&3));
+        set.remove(1);
+        assert_eq!(set.get(1), Some(&0))
+    }
+
+    #[test]
+    fn test_remove_unchecked() {
+        let mut set = Multiset::<u64, 3>::from([2, 3, 4]);
+        assert_eq!(set.get(1), Some(&3));
+        unsafe { set.remove_unchecked(1) };
+        assert_eq!(set.get(1), Some(&0))
+    }
+
+    #[test]
+    fn test_get() {
+        let set = Multiset::<usize, 4>::from([6, 7, 8, 9]);
+        assert_eq!(set.get(1), Some(&7));
+        assert_eq!(set.get(9), None)
+    }
+
+    #[test]
+    fn test_get_unchecked() {
+        let set = Multiset::<usize, 4>::from([6, 7, 8, 9]);
+        unsafe { assert_eq!(set.get_unchecked(1), &7) }
+    }
+
+    #[test]
+    fn test_intersection() {
+        let a = Multiset::<u8, 4>::from([1, 2, 5, 6]);
+        let b = Multiset::from([0, 1, 8, 9]);
+        let c = Multiset::from([0, 1, 5, 6]);
+        assert_eq!(c, a.intersection(&b))
+    }
+
+    #[test]
+    fn test_union() {
+        let a = Multiset::<u8, 4>::from([1, 2, 5, 6]);
+        let b = Multiset::from([0, 1, 8, 9]);
+        let c = Multiset::from([1, 2, 8, 9]);
+        assert_eq!(c, a.union(&b))
+    }
+
+    #[test]
+    fn test_intersection_mut() {
+        proptest!(|(a in counts_up_to::<u16, 37>(10), b in counts_up_to::<u16, 37>(10))| {
+            let mut result = a;
+            result.intersection_mut(&b);
+            prop_assert_eq!(result, a.intersection(&b));
+        });
+    }
+
+    #[test]
+    fn test_union_mut() {
+        proptest!(|(a in counts_up_to::<u16, 37>(10), b in counts_up_to::<u16, 37>(10))| {
+            let mut result = a;
+            result.union_mut(&b);
+            prop_assert_eq!(result, a.union(&b));
+        });
+    }
+
+    #[test]
+    fn test_intersection_union_into() {
+        proptest!(|(a in counts_up_to::<u16, 37>(10), b in counts_up_to::<u16, 37>(10), mut out in counts_up_to::<u16, 37>(10))| {
+            // The output is fully overwritten, whatever it held before.
+            a.intersection_into(&b, &mut out);
+            prop_assert_eq!(out, a.intersection(&b));
+            a.union_into(&b, &mut out);
+            prop_assert_eq!(out, a.union(&b));
+        });
+    }
+
+    #[test]
+    fn test_intersection_union_all() {
+        let sets: Vec<Multiset<u16, 37>> = (0..5).map(|seed| pseudo_random(seed, 10)).collect();
+        assert_eq!(Multiset::<u16, 37>::intersection_all(&[]), None);
+        assert_eq!(Multiset::<u16, 37>::union_all(&[]), None);
+        assert_eq!(Multiset::intersection_all(&sets[..1]), Some(sets[0]));
+        assert_eq!(Multiset::union_all(&sets[..1]), Some(sets[0]));
+
+        let intersection = sets[1..]
+            .iter()
+            .fold(sets[0], |acc, set| acc.intersection(set));
+        let union = sets[1..].iter().fold(sets[0], |acc, set| acc.union(set));
+        assert_eq!(Multiset::intersection_all(&sets), Some(intersection));
+        assert_eq!(Multiset::union_all(&sets), Some(union));
+    }
+
+    #[test]
+    fn test_min_max_scalar() {
+        proptest!(|(set in counts_up_to::<u16, 37>(10))| {
+            let low = set.min_scalar(4);
+            let high = set.max_scalar(4);
+            prop_assert!(low.iter().all(|c| *c <= 4));
+            prop_assert!(high.iter().all(|c| *c >= 4));
+            prop_assert_eq!(low.union(&high), set.max_scalar(4));
+            prop_assert_eq!(low.intersection(&Multiset::repeat(4)), low);
+
+            let support = set.to_support();
+            prop_assert_eq!(support.total(), set.count_non_zero());
+            prop_assert_eq!(support, set.min_scalar(1));
+        });
+        assert_eq!(Multiset::<u8, 4>::empty().to_support(), Multiset::empty());
+    }
+
+    #[test]
+    fn test_subtract_retain_support() {
+        proptest!(|(a in counts_up_to::<u16, 37>(10), b in counts_up_to::<u16, 37>(3))| {
+            let removed = a.subtract_support(&b);
+            let retained = a.retain_support(&b);
+            prop_assert_eq!(removed.sum(&retained), a);
+            prop_assert!(removed.is_disjoint(&b));
+
+            // Only the support of other matters, not its counts.
+            let b_large = b * 1000;
+            prop_assert_eq!(a.subtract_support(&b_large), removed);
+            prop_assert_eq!(a.retain_support(&b_large), retained);
+            prop_assert_eq!(a.subtract_support(&b.to_support()), removed);
+        });
+    }
+
+    #[test]
+    fn test_count_intersection_union() {
+        let empty = Multiset::<u8, 37>::empty();
+        assert_eq!(empty.count_intersection(&empty), 0);
+        assert_eq!(empty.count_union(&empty), 0);
+        proptest!(|(a in counts_up_to::<u8, 37>(255), b in counts_up_to::<u8, 37>(255))| {
+            prop_assert_eq!(a.count_intersection(&b), a.intersection(&b).total_wide());
+            prop_assert_eq!(a.count_union(&b), a.union(&b).total_wide());
+            prop_assert_eq!(a.count_intersection(&empty), 0);
+            prop_assert_eq!(a.count_union(&empty), a.total_wide());
+        });
+    }
+
+    #[test]
+    fn test_partition() {
+        proptest!(|(set in counts_up_to::<u16, 37>(10))| {
+            let (even, odd) = set.partition(|elem, _| elem % 2 == 0);
+            prop_assert_eq!(even.sum(&odd), set);
+            prop_assert!(even.is_disjoint(&odd));
+            prop_assert!(even.iter().skip(1).step_by(2).all(|c| *c == 0));
+
+            prop_assert_eq!(set.partition(|_, _| true), (set, Multiset::empty()));
+            prop_assert_eq!(set.partition(|_, _| false), (Multiset::empty(), set));
+        });
+    }
+
+    #[test]
+    fn test_split_at() {
+        let set = pseudo_random::<u16, 37>(7, 10);
+        assert_eq!(set.split_at(0), (Multiset::empty(), set));
+        assert_eq!(set.split_at(37), (set, Multiset::empty()));
+
+        for mid in [1, 5, 17, 36] {
+            let (prefix, suffix) = set.split_at(mid);
+            assert_eq!(prefix.sum(&suffix), set);
+            assert_eq!(prefix, set.partition(|elem, _| elem < mid).0);
+            assert_eq!(suffix, set.partition(|elem, _| elem < mid).1);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_split_at_out_of_bounds() {
+        let _ = Multiset::<u8, 4>::empty().split_at(5);
+    }
+
+    #[test]
+    fn test_scale_to_total() {
+        let empty = Multiset::<u16, 37>::empty();
+        assert_eq!(empty.scale_to_total(0), empty);
+        assert_eq!(empty.scale_to_total(100), empty);
+
+        proptest!(|(set in counts_up_to::<u16, 37>(50))| {
+            prop_assert_eq!(set.scale_to_total(0), empty);
+            prop_assert_eq!(set.scale_to_total(set.total()), set);
+
+            for target in [1, 7, 100, 999, 12345] {
+                let scaled = set.scale_to_total(target);
+                prop_assert_eq!(scaled.total(), target);
+                for (i, a) in set.iter().enumerate() {
+                    for (j, b) in set.iter().enumerate() {
+                        if a > b {
+                            prop_assert!(scaled[i] >= scaled[j]);
+                        }
+                    }
+                }
+            }
+        });
+
+        // Counts too large for the counter saturate.
+        let set = Multiset::from([1u8, 0]);
+        assert_eq!(set.scale_to_total(1000), Multiset::from([255, 0]));
+    }
+
+    #[test]
+    fn test_difference() {
+        let a = Multiset::<u8, 4>::from([0, 2, 5, 6]);
+        let b = Multiset::from([1, 1, 8, 0]);
+        let c = Multiset::from([0, 1, 0, 6]);
+        assert_eq!(c, a.difference(&b));
+
+        let dominating = Multiset::from([1, 3, 5, 255]);
+        assert_eq!(a.difference(&dominating), Multiset::empty());
+        assert_eq!(a.difference(&a), Multiset::empty());
+        assert_eq!(a.difference(&Multiset::empty()), a);
+    }
+
+    #[test]
+    fn test_complement() {
+        proptest!(|(a in counts_up_to::<u16, 37>(10), b in counts_up_to::<u16, 37>(10))| {
+            let universe = a.union(&b);
+            let complement = a.complement(&universe);
+            prop_assert_eq!(complement, universe.difference(&a));
+            prop_assert_eq!(a.sum(&complement), universe);
+
+            // For set-like subsets, taking either all or none of each count,
+            // the union with the complement is the universe.
+            let part = universe.zip_map(&a, |u, e| if e % 2 == 0 { u } else { 0 });
+            prop_assert_eq!(part.union(&part.complement(&universe)), universe);
+        });
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "not a subset of universe")]
+    fn test_complement_not_subset() {
+        let a = Multiset::from([1u8, 2, 0]);
+        let _ = a.complement(&Multiset::from([1u8, 1, 1]));
+    }
+
+    #[test]
+    fn test_symmetric_difference() {
+        let a = Multiset::<u8, 4>::from([0, 2, 5, 6]);
+        let b = Multiset::from([1, 1, 8, 0]);
+        let c = Multiset::from([1, 1, 3, 6]);
+        assert_eq!(c, a.symmetric_difference(&b));
+        assert_eq!(a.symmetric_difference(&a), Multiset::empty());
+
+        proptest!(|(a in counts_up_to::<u16, 37>(10), b in counts_up_to::<u16, 37>(10))| {
+            let expected = a.union(&b).difference(&a.intersection(&b));
+            prop_assert_eq!(a.symmetric_difference(&b), expected);
+            prop_assert_eq!(b.symmetric_difference(&a), expected);
+        });
+    }
+
+    #[test]
+    fn test_abs_diff() {
+        proptest!(|(a in counts_up_to::<u8, 37>(255), b in counts_up_to::<u8, 37>(255))| {
+            let expected: Multiset<u8, 37> = a
+                .iter()
+                .zip(b.iter())
+                .map(|(x, y)| x.abs_diff(*y))
+                .collect();
+            prop_assert_eq!(a.abs_diff(&b), expected);
+            prop_assert_eq!(b.abs_diff(&a), expected);
+            prop_assert_eq!(a.abs_diff(&a), Multiset::empty());
+        });
+    }
+
+    #[test]
+    fn test_sum() {
+        let a = Multiset::<u8, 4>::from([0, 2, 5, 6]);
+        let b = Multiset::from([1, 1, 8, 0]);
+        assert_eq!(a.sum(&b), Multiset::from([1, 3, 13, 6]));
+        assert_eq!(a.sum(&Multiset::empty()), a);
+
+        let c = Multiset::<u16, 3>::from([u16::MAX - 1, 7, 40000]);
+        let d = Multiset::from([5, 3, 40000]);
+        assert_eq!(c.sum(&d), Multiset::from([u16::MAX, 10, u16::MAX]));
+    }
+
+    #[test]
+    fn test_checked_sum() {
+        let a = pseudo_random::<u16, 37>(1, 1000);
+        let b = pseudo_random::<u16, 37>(2, 1000);
+        assert_eq!(a.checked_sum(&b), Some(a.sum(&b)));
+
+        let mut first = Multiset::<u16, 37>::empty();
+        first[0] = u16::MAX;
+        assert_eq!(first.checked_sum(&a.sum(&Multiset::repeat(1))), None);
+
+        let mut last = Multiset::<u16, 37>::repeat(1);
+        last[36] = u16::MAX;
+        assert_eq!(last.checked_sum(&last), None);
+        assert_eq!(last.checked_sum(&Multiset::empty()), Some(last));
+    }
+
+    #[test]
+    fn test_count_zero() {
+        let set = Multiset::<u16, 7>::from([0, 1, 3, 0, 8, 0, 0]);
+        assert_eq!(set.count_zero(), 4)
+    }
+
+    #[test]
+    fn test_count_non_zero() {
+        let set = Multiset::<u16, 7>::from([0, 1, 3, 0, 8, 0, 0]);
+        assert_eq!(set.count_non_zero(), 3)
+    }
+
+    #[test]
+    fn test_is_empty() {
+        let a = Multiset::from([1u8; 3]);
+        let b = Multiset::<u8, 3>::empty();
+        assert!(!a.is_empty());
+        assert!(b.is_empty());
+    }
+
+    #[test]
+    fn test_is_singleton() {
+        let a = Multiset::<u16, 4>::from([0, 0, 8, 0]);
+        assert!(a.is_singleton());
+
+        let b = Multiset::<u16, 4>::from([0, 2, 8, 0]);
+        assert!(!b.is_singleton());
+
+        let c = Multiset::<u16, 4>::empty();
+        assert!(!c.is_singleton());
+    }
+
+    #[test]
+    fn test_is_disjoint() {
+        let a = Multiset::<u8, 4>::from([1, 1, 0, 0]);
+        let b = Multiset::from([0, 0, 1, 1]);
+        let c = Multiset::from([0, 1, 1, 0]);
+
+        assert!(a.is_disjoint(&b));
+        assert!(!a.is_disjoint(&c));
+    }
+
+    #[test]
+    fn test_is_disjoint_edge_cases() {
+        let a = Multiset::<u8, 4>::from([1, 1, 0, 0]);
+        let empty = Multiset::<u8, 4>::empty();
+        assert!(a.is_disjoint(&empty));
+        assert!(empty.is_disjoint(&a));
+        assert!(empty.is_disjoint(&empty));
+        assert!(!a.is_disjoint(&a));
+
+        // Overlap only in the last element, past any whole SIMD chunk.
+        let mut d = Multiset::<u16, 37>::empty();
+        let mut e = Multiset::<u16, 37>::empty();
+        d.insert(0, 1);
+        e.insert(1, 1);
+        assert!(d.is_disjoint(&e));
+        d.insert(36, 1);
+        e.insert(36, 2);
+        assert!(!d.is_disjoint(&e));
+        assert!(!e.is_disjoint(&d));
+    }
+
+    #[test]
+    fn test_is_subset() {
+        let a = Multiset::<u8, 3>::from([1, 1, 1]);
+        let b = Multiset::from([2, 2, 2]);
+        assert!(a.is_subset(&b));
+        assert!(!b.is_subset(&a));
+
+        let c = Multiset::from([0, 1, 2]);
+        assert!(!a.is_subset(&c));
+        assert!(!c.is_subset(&a));
+
+        let d = Multiset::from([1, 1, 1]);
+        assert!(a.is_subset(&d));
+        assert!(d.is_subset(&a));
+    }
+
+    #[test]
+    fn test_is_superset() {
+        let a = Multiset::<u8, 3>::from([1, 1, 1]);
+        let b = Multiset::from([2, 2, 2]);
+        assert!(!a.is_superset(&b));
+        assert!(b.is_superset(&a));
+
+        let c = Multiset::from([0, 1, 2]);
+        assert!(!a.is_superset(&c));
+        assert!(!c.is_superset(&a));
+
+        let d = Multiset::from([1, 1, 1]);
+        assert!(a.is_superset(&d));
+        assert!(d.is_superset(&a));
+    }
+
+    #[test]
+    fn test_is_subset_of_sum() {
+        let max = u16::MAX;
+        let a = Multiset::from([max, max, 0, 1]);
+        let b = Multiset::from([max, max - 1, 0, 0]);
+        let c = Multiset::from([max, 1, 0, 1]);
+        assert!(a.is_subset_of_sum(&b, &c));
+        assert!(a.is_subset_of_sum(&c, &b));
+        assert!(!a.is_subset_of_sum(&b, &Multiset::from([max, 0, 0, 1])));
+        assert!(!a.is_subset_of_sum(&Multiset::empty(), &Multiset::empty()));
+
+        proptest!(|(a in counts_up_to::<u16, 37>(20), b in counts_up_to::<u16, 37>(10), c in counts_up_to::<u16, 37>(10))| {
+            prop_assert_eq!(a.is_subset_of_sum(&b, &c), a.is_subset(&b.sum(&c)));
+        });
+    }
+
+    #[test]
+    fn test_is_proper_subset() {
+        let a = Multiset::from([1u8; 3]);
+        let b = Multiset::from([2u8; 3]);
+        assert!(a.is_proper_subset(&b));
+        assert!(!b.is_proper_subset(&a));
+
+        let c = Multiset::from([0u8, 1, 2]);
+        assert!(!a.is_proper_subset(&c));
+        assert!(!c.is_proper_subset(&a));
+
+        let d = Multiset::from([1u8; 3]);
+        assert!(!a.is_proper_subset(&d));
+        assert!(!d.is_proper_subset(&a));
+    }
+
+    #[test]
+    fn test_is_proper_superset() {
+        let a = Multiset::from([1u8; 3]);
+        let b = Multiset::from([2u8; 3]);
+        assert!(!a.is_proper_superset(&b));
+        assert!(b.is_proper_superset(&a));
+
+        let c = Multiset::from([0u8, 1, 2]);
+        assert!(!a.is_proper_superset(&c));
+        assert!(!c.is_proper_superset(&a));
+
+        let d = Multiset::from([1u8; 3]);
+        assert!(!a.is_proper_superset(&d));
+        assert!(!d.is_proper_superset(&a));
+    }
+
+    #[test]
+    fn test_is_any_lesser() {
+        let a = Multiset::from([1u8; 3]);
+        let b = Multiset::from([2u8; 3]);
+        assert!(a.is_any_lesser(&b));
+        assert!(!b.is_any_lesser(&a));
+
+        let c = Multiset::from([0u8, 1, 2]);
+        assert!(a.is_any_lesser(&c));
+        assert!(c.is_any_lesser(&a));
+
+        let d = Multiset::from([1u8; 3]);
+        assert!(!a.is_any_lesser(&d));
+        assert!(!d.is_any_lesser(&a));
+    }
+
+    #[test]
+    fn test_is_any_greater() {
+        let a = Multiset::from([1u8; 3]);
+        let b = Multiset::from([2u8; 3]);
+        assert!(!a.is_any_greater(&b));
+        assert!(b.is_any_greater(&a));
+
+        let c = Multiset::from([0u8, 1, 2]);
+        assert!(a.is_any_greater(&c));
+        assert!(c.is_any_greater(&a));
+
+        let d = Multiset::from([1u8; 3]);
+        assert!(!a.is_any_greater(&d));
+        assert!(!d.is_any_greater(&a));
+    }
+
+    #[test]
+    fn test_permute_counts() {
+        let original = pseudo_random::<u16, 37>(1, 10);
+        let perm: Vec<usize> = (0..37).map(|i| (i * 5) % 37).collect();
+        let mut set = original;
+        set.permute_counts(&perm).unwrap();
+        for (elem, &from) in perm.iter().enumerate() {
+            assert_eq!(set[elem], original[from]);
+        }
+        assert_eq!(set.total(), original.total());
+
+        let identity: Vec<usize> = (0..37).collect();
+        let mut unchanged = original;
+        unchanged.permute_counts(&identity).unwrap();
+        assert_eq!(unchanged, original);
+
+        let mut set = Multiset::from([1u8, 2, 3]);
+        assert_eq!(
+            set.permute_counts(&[0, 1]),
+            Err(MultisetError::WrongLength {
+                expected: 3,
+                got: 2
+            })
+        );
+        assert_eq!(
+            set.permute_counts(&[0, 1, 3]),
+            Err(MultisetError::ElementOutOfBounds { elem: 3, size: 3 })
+        );
+        assert_eq!(
+            set.permute_counts(&[2, 1, 2]),
+            Err(MultisetError::DuplicateElement { elem: 2 })
+        );
+        assert_eq!(set, Multiset::from([1, 2, 3]));
+        assert_eq!(Multiset::<u8, 0>::empty().permute_counts(&[]), Ok(()));
+    }
+
+    #[test]
+    fn test_total() {
+        let set = Multiset::from([1u8, 2, 3, 4]);
+        assert_eq!(set.total(), 10)
+    }
+
+    #[test]
+    fn test_total_checked_wide() {
+        let set = Multiset::from([200u8, 100, 0, 1]);
+        assert_eq!(set.total(), 301);
+        assert_eq!(set.total_checked(), None);
+        assert_eq!(set.total_wide(), 301);
+        assert_eq!(Multiset::from([200u8, 55]).total_checked(), Some(255));
+
+        let set = Multiset::<u32, 37>::repeat(u32::MAX);
+        assert_eq!(set.total_checked(), None);
+        assert_eq!(set.total_wide(), 37 * u32::MAX as u128);
+        assert_eq!(set.total() as u128, set.total_wide());
+
+        let set = Multiset::<u64, 3>::repeat(u64::MAX);
+        assert_eq!(set.total_checked(), None);
+        assert_eq!(set.total_wide(), 3 * u64::MAX as u128);
+
+        assert_eq!(Multiset::<u16, 0>::empty().total_checked(), Some(0));
+        assert_eq!(Multiset::<u16, 0>::empty().total_wide(), 0);
+        proptest!(|(set in counts_up_to::<u16, 37>(1000))| {
+            prop_assert_eq!(set.total_checked(), Some(set.total() as u16));
+            prop_assert_eq!(set.total_wide(), set.total() as u128);
+        });
+    }
+
+    #[test]
+    fn test_argmax() {
+        let set = Multiset::from([1u8, 0, 3, 1]);
+        let expected = (2, &3);
+        assert_eq!(set.elem_count_max(), expected)
+    }
+
+    #[test]
+    fn test_argmax_ties() {
+        let set = Multiset::from([1u8, 3, 0, 3, 3]);
+        assert_eq!(set.elem_count_max(), (1, &3));
+        assert_eq!(set.elem_max(), 1);
+        assert_eq!(Multiset::<u32, 7>::empty().elem_count_max(), (0, &0));
+    }
+
+    #[test]
+    fn test_argmax_final_partial_chunk() {
+        let mut set = pseudo_random::<u16, 37>(4, 1000);
+        set.insert(36, 2000);
+        assert_eq!(set.elem_count_max(), (36, &2000));
+        assert_eq!(set.count_max(), &2000);
+
+        let mut set = pseudo_random::<u8, 37>(4, 100);
+        set.insert(33, 200);
+        set.insert(35, 200);
+        assert_eq!(set.elem_count_max(), (33, &200));
+        assert_eq!(set.count_max(), &200);
+    }
+
+    #[test]
+    fn test_elem_max_all() {
+        let mut set = pseudo_random::<u16, 37>(5, 100);
+        set.insert(3, 500);
+        set.insert(20, 500);
+        set.insert(36, 500);
+        assert_eq!(set.elem_max_all(), vec![3, 20, 36]);
+
+        let set = Multiset::<u8, 5>::empty();
+        assert_eq!(set.elem_max_all(), vec![0, 1, 2, 3, 4]);
+        assert!(Multiset::<u8, 0>::empty().elem_max_all().is_empty());
+    }
+
+    #[test]
+    fn test_imax() {
+        let set = Multiset::from([1u8, 0, 3, 1]);
+        let expected = 2;
+        assert_eq!(set.elem_max(), expected)
+    }
+
+    #[test]
+    fn test_max() {
+        let set = Multiset::from([1u8, 0, 3, 1]);
+        let expected = &3;
+        assert_eq!(set.count_max(), expected)
+    }
+
+    #[test]
+    fn test_argmin() {
+        let set = Multiset::from([1u8, 0, 3, 1]);
+        let expected = (1, &0);
+        assert_eq!(set.elem_count_min(), expected)
+    }
+
+    #[test]
+    fn test_argmin_ties() {
+        let set = Multiset::from([3u8, 1, 2, 1, 1]);
+        assert_eq!(set.elem_count_min(), (1, &1));
+        assert_eq!(set.elem_min(), 1);
+    }
+
+    #[test]
+    fn test_argmin_non_zero() {
+        assert_eq!(Multiset::<u16, 37>::empty().elem_count_min_non_zero(), None);
+        assert_eq!(Multiset::<u16, 0>::empty().elem_count_min_non_zero(), None);
+
+        let set = Multiset::from([0u8, 4, 0, 2, 7]);
+        assert_eq!(set.elem_count_min_non_zero(), Some((3, &2)));
+        assert_eq!(set.elem_count_min(), (0, &0));
+
+        let set = Multiset::from([0u8, 4, 2, 0, 2]);
+        assert_eq!(set.elem_count_min_non_zero(), Some((2, &2)));
+    }
+
+    #[test]
+    fn test_min_final_partial_chunk() {
+        let set = Multiset::<u16, 37>::repeat(5);
+        assert_eq!(set.count_min(), &5);
+        let mut set = pseudo_random::<u16, 37>(4, 1000).max_scalar(10);
+        set.insert(36, 1);
+        assert_eq!(set.count_min(), &1);
+        assert_eq!(set.elem_count_min(), (36, &1));
+    }
+
+    #[test]
+    fn test_imin() {
+        let set = Multiset::from([1u8, 0, 3, 1]);
+        let expected = 1;
+        assert_eq!(set.elem_min(), expected)
+    }
+
+    #[test]
+    fn test_min() {
+        let set = Multiset::from([1u8, 0, 3, 1]);
+        let expected = &0;
+        assert_eq!(set.count_min(), expected)
+    }
+
+    #[test]
+    fn test_choose() {
+        let mut set = Multiset::from([1u8, 2, 3, 4, 5]);
+        let expected = Multiset::from([0u8, 0, 0, 4, 0]);
+        set.choose(3);
+        assert_eq!(set, expected)
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_choose_random() {
+        let mut result1 = Multiset::from([1u8, 2, 3, 4, 5]);
+        let test_rng1 = &mut StdRng::seed_from_u64(thread_rng().next_u64());
+        result1.choose_random(test_rng1);
+        assert!(result1.is_singleton() && result1.is_subset(&Multiset::from([1u8, 2, 3, 4, 5])));
+
+        let mut result2 = Multiset::from([1u8, 2, 3, 4, 5]);
+        let test_rng2 = &mut StdRng::seed_from_u64(thread_rng().next_u64());
+        result2.choose_random(test_rng2);
+        assert!(result2.is_singleton() && result2.is_subset(&Multiset::from([1u8, 2, 3, 4, 5])));
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_choose_random_any_rng() {
+        let original = Multiset::from([1u8, 2, 3, 4, 5, 0, 7]);
+        let mut rngs: Vec<Box<dyn RngCore>> = vec![
+            Box::new(StdRng::seed_from_u64(1)),
+            Box::new(SmallRng::seed_from_u64(1)),
+        ];
+        for rng in rngs.iter_mut() {
+            for _ in 0..100 {
+                let mut set = original;
+                let elem = set.choose_random(rng.as_mut()).unwrap();
+                assert!(set.is_subset(&original));
+                assert_eq!(set.count_non_zero(), 1);
+                assert_eq!(set[elem], original[elem]);
+            }
+        }
+
+        proptest!(|(seed in any::<u64>())| {
+            let mut set = original;
+            set.choose_random(&mut SmallRng::seed_from_u64(seed));
+            prop_assert!(set.is_singleton() && set.is_subset(&original));
+        });
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_choose_random_distribution() {
+        let original = Multiset::from([1u16, 0, 3, 6]);
+        let rng = &mut StdRng::seed_from_u64(7);
+        let draws = 100_000;
+        let mut chosen = [0usize; 4];
+        for _ in 0..draws {
+            let mut set = original;
+            let elem = set.choose_random(rng).unwrap();
+            assert_eq!(set.elem_count_max().0, elem);
+            chosen[elem] += 1;
+        }
+        assert_eq!(chosen[1], 0);
+        for (count, expected) in chosen.iter().zip(&[0.1, 0.0, 0.3, 0.6]) {
+            assert_relative_eq!(*count as f64 / draws as f64, expected, epsilon = 0.01);
+        }
     }
 
+    #[cfg(feature = "rand")]
     #[test]
-    fn test_add() {
-        let set = Multiset::from([2u8, 2, 2, 2]);
-        assert_eq!(set + set, Multiset::from([4u8, 4, 4, 4]));
-        assert_eq!(set + 3, Multiset::from([5u8, 5, 5, 5]));
+    fn test_sample() {
+        let original = Multiset::from([1u16, 0, 3, 6]);
+        let rng = &mut StdRng::seed_from_u64(11);
+        let draws = 100_000;
+        let mut sampled = [0usize; 4];
+        for _ in 0..draws {
+            sampled[original.sample(rng).unwrap()] += 1;
+        }
+        assert_eq!(original, Multiset::from([1, 0, 3, 6]));
+        assert_eq!(sampled[1], 0);
+        for (count, expected) in sampled.iter().zip(&[0.1, 0.0, 0.3, 0.6]) {
+            assert_relative_eq!(*count as f64 / draws as f64, expected, epsilon = 0.01);
+        }
 
-        let mut set_assign = Multiset::from([3u8, 3, 3, 3]);
-        set_assign += set;
-        assert_eq!(set_assign, Multiset::from([5u8, 5, 5, 5]));
-        set_assign += 2;
-        assert_eq!(set_assign, Multiset::from([7u8, 7, 7, 7]));
+        assert_eq!(Multiset::<u16, 4>::empty().sample(rng), None);
+        assert_eq!(Multiset::<u16, 0>::empty().sample(rng), None);
     }
 
+    #[cfg(feature = "rand")]
     #[test]
-    fn test_div() {
-        let set = Multiset::from([10u8, 10, 10, 10]);
-        assert_eq!(set / set, Multiset::from([1u8, 1, 1, 1]));
-        assert_eq!(set / 2, Multiset::from([5u8, 5, 5, 5]));
-
-        let mut set_assign = Multiset::from([20u8, 20, 20, 20]);
-        set_assign /= set;
-        assert_eq!(set_assign, Multiset::from([2u8, 2, 2, 2]));
-        set_assign /= 2;
-        assert_eq!(set_assign, Multiset::from([1u8, 1, 1, 1]));
+    fn test_sample_deterministic() {
+        let set = pseudo_random::<u16, 37>(3, 10);
+        let first: Vec<_> = {
+            let rng = &mut StdRng::seed_from_u64(5);
+            (0..100).map(|_| set.sample(rng)).collect()
+        };
+        let rng = &mut StdRng::seed_from_u64(5);
+        let second: Vec<_> = (0..100).map(|_| set.sample(rng)).collect();
+        assert_eq!(first, second);
+
+        // choose_random makes the same choice from the same rng.
+        let rng = &mut StdRng::seed_from_u64(5);
+        for elem in first {
+            let mut chosen = set;
+            assert_eq!(chosen.choose_random(rng), elem);
+        }
     }
 
+    #[cfg(feature = "rand")]
     #[test]
-    fn test_mul() {
-        let set = Multiset::from([2u8, 2, 2, 2]);
-        assert_eq!(set * set, Multiset::from([4u8, 4, 4, 4]));
-        assert_eq!(set * 3, Multiset::from([6u8, 6, 6, 6]));
+    fn test_sample_counts() {
+        let rng = &mut StdRng::seed_from_u64(13);
+        proptest!(|(set in counts_up_to::<u16, 37>(3), seed in any::<u64>())| {
+            let rng = &mut StdRng::seed_from_u64(seed);
+            let sampled = set.sample_counts(1000, rng);
+            prop_assert_eq!(sampled.total(), 1000);
+            prop_assert!(sampled
+                .iter()
+                .zip(set.iter())
+                .all(|(s, c)| *s == 0 || *c > 0));
+        });
 
-        let mut set_assign = Multiset::from([3u8, 3, 3, 3]);
-        set_assign *= set;
-        assert_eq!(set_assign, Multiset::from([6u8, 6, 6, 6]));
-        set_assign *= 2;
-        assert_eq!(set_assign, Multiset::from([12u8, 12, 12, 12]));
+        let set = Multiset::from([1u32, 0, 3, 6]);
+        let sampled = set.sample_counts(100_000, rng);
+        for (count, expected) in sampled.iter().zip(&[0.1, 0.0, 0.3, 0.6]) {
+            assert_relative_eq!(*count as f64 / 100_000.0, expected, epsilon = 0.01);
+        }
+
+        let saturated = Multiset::from([0u8, 1]).sample_counts(1000, rng);
+        assert_eq!(saturated, Multiset::from([0, u8::MAX]));
+
+        assert_eq!(set.sample_counts(0, rng), Multiset::empty());
+        let empty = Multiset::<u32, 4>::empty();
+        assert_eq!(empty.sample_counts(1000, rng), empty);
     }
 
+    #[cfg(feature = "rand")]
     #[test]
-    fn test_rem() {
-        let set = Multiset::from([10u8, 10, 10, 10]);
-        assert_eq!(set % set, Multiset::from([0u8, 0, 0, 0]));
-        assert_eq!(set % 3, Multiset::from([1u8, 1, 1, 1]));
+    fn test_sample_without_replacement() {
+        let rng = &mut StdRng::seed_from_u64(17);
+        proptest!(|(set in counts_up_to::<u16, 37>(5), seed in any::<u64>())| {
+            let rng = &mut StdRng::seed_from_u64(seed);
+            let total = set.total();
+            for &k in &[0, 1, total / 3, total / 2, total - 1, total, total + 10] {
+                let sampled = set.sample_without_replacement(k, rng);
+                prop_assert!(sampled.is_subset(&set));
+                prop_assert_eq!(sampled.total(), k.min(total));
+            }
+            prop_assert_eq!(set.sample_without_replacement(total, rng), set);
+        });
 
-        let mut set_assign = Multiset::from([3u8, 3, 3, 3]);
-        set_assign %= set;
-        assert_eq!(set_assign, Multiset::from([3u8, 3, 3, 3]));
-        set_assign %= 2;
-        assert_eq!(set_assign, Multiset::from([1u8, 1, 1, 1]));
+        // Each member is drawn with probability k / total.
+        let set = Multiset::from([10u32, 0, 30, 60]);
+        let mut drawn = [0usize; 4];
+        for _ in 0..10_000 {
+            let sampled = set.sample_without_replacement(70, rng);
+            drawn
+                .iter_mut()
+                .zip(sampled.iter())
+                .for_each(|(d, s)| *d += *s as usize);
+        }
+        for (count, expected) in drawn.iter().zip(&[7.0, 0.0, 21.0, 42.0]) {
+            assert_relative_eq!(*count as f64 / 10_000.0, expected, max_relative = 0.01);
+        }
+
+        let empty = Multiset::<u32, 4>::empty();
+        assert_eq!(empty.sample_without_replacement(5, rng), empty);
     }
 
+    #[cfg(feature = "rand")]
     #[test]
-    fn test_sub() {
-        let set = Multiset::from([5u8, 5, 5, 5]);
-        assert_eq!(set - set, Multiset::from([0u8, 0, 0, 0]));
-        assert_eq!(set - 3, Multiset::from([2u8, 2, 2, 2]));
+    fn test_choose_weighted() {
+        let original = Multiset::from([1u16, 0, 3, 6, 2]);
+        let weights = [1.0, 50.0, 3.0, 6.0, 0.0];
+        let rng = &mut StdRng::seed_from_u64(19);
+        let draws = 100_000;
+        let mut chosen = [0usize; 5];
+        for _ in 0..draws {
+            let mut set = original;
+            let elem = set.choose_weighted(&weights, rng).unwrap();
+            assert!(set.is_singleton() && set.is_subset(&original));
+            assert_eq!(set[elem], original[elem]);
+            chosen[elem] += 1;
+        }
+        for (count, expected) in chosen.iter().zip(&[0.1, 0.0, 0.3, 0.6, 0.0]) {
+            assert_relative_eq!(*count as f64 / draws as f64, expected, epsilon = 0.01);
+        }
 
-        let mut set_assign = Multiset::from([8u8, 8, 8, 8]);
-        set_assign -= set;
-        assert_eq!(set_assign, Multiset::from([3u8, 3, 3, 3]));
-        set_assign -= 2;
-        assert_eq!(set_assign, Multiset::from([1u8, 1, 1, 1]));
+        let first: Vec<_> = {
+            let rng = &mut StdRng::seed_from_u64(5);
+            (0..100)
+                .map(|_| {
+                    let mut set = original;
+                    set.choose_weighted(&weights, rng)
+                })
+                .collect()
+        };
+        let rng = &mut StdRng::seed_from_u64(5);
+        let second: Vec<_> = (0..100)
+            .map(|_| {
+                let mut set = original;
+                set.choose_weighted(&weights, rng)
+            })
+            .collect();
+        assert_eq!(first, second);
+
+        let mut set = Multiset::from([0u16, 4, 0, 0, 7]);
+        assert_eq!(set.choose_weighted(&[1.0, 0.0, 1.0, 1.0, 0.0], rng), None);
+        assert_eq!(set, Multiset::from([0, 4, 0, 0, 7]));
+        let mut empty = Multiset::<u16, 5>::empty();
+        assert_eq!(empty.choose_weighted(&weights, rng), None);
     }
 
+    #[cfg(feature = "rand")]
     #[test]
-    fn test_zip_map() {
-        let set1: Multiset<u8, 4> = Multiset::from([1, 5, 2, 8]);
-        let set2: Multiset<u8, 4> = Multiset::from([1, 5, 2, 8]);
-        let result = set1.zip_map(&set2, |e1, e2| e1 + e2);
-        let expected = Multiset::from([2, 10, 4, 16]);
-        assert_eq!(result, expected)
+    fn test_choose_weighted_extreme_weights() {
+        let rng = &mut StdRng::seed_from_u64(29);
+        let original = Multiset::from([1u8, 1, 1, 1]);
+        let mut chosen = [0usize; 4];
+        for _ in 0..4000 {
+            let mut set = original;
+            let weights = [f64::MAX, f64::MAX, 1.0, f64::NAN];
+            chosen[set.choose_weighted(&weights, rng).unwrap()] += 1;
+        }
+        assert!(chosen[0] > 1800 && chosen[1] > 1800);
+        assert_eq!(chosen[3], 0);
+
+        let mut chosen = [0usize; 4];
+        for _ in 0..4000 {
+            let mut set = original;
+            let weights = [f64::INFINITY, 1e300, f64::INFINITY, 1.0];
+            chosen[set.choose_weighted(&weights, rng).unwrap()] += 1;
+        }
+        assert_eq!(chosen[1] + chosen[3], 0);
+        assert!(chosen[0] > 1800 && chosen[2] > 1800);
+
+        let mut set = Multiset::from([1u8, 0, 0, 0]);
+        let weights = [f64::NAN, f64::INFINITY, f64::MAX, 1.0];
+        assert_eq!(set.choose_weighted(&weights, rng), None);
     }
 
+    #[cfg(feature = "rand")]
     #[test]
-    fn test_len() {
-        assert_eq!(Multiset::<u16, 9>::len(), 9)
+    #[should_panic]
+    fn test_choose_weighted_wrong_length() {
+        let rng = &mut StdRng::seed_from_u64(19);
+        let _ = Multiset::<u8, 4>::repeat(1).choose_weighted(&[1.0; 3], rng);
     }
 
+    #[cfg(feature = "rand")]
     #[test]
-    fn test_new() {
-        assert_eq!(Multiset::new([1u8, 3]), Multiset::from([1u8, 3]))
+    fn test_random_with_total() {
+        let rng = &mut StdRng::seed_from_u64(23);
+        for &total in &[0, 1, 37, 1000, 65535] {
+            let set = Multiset::<u16, 37>::random_with_total(total, rng).unwrap();
+            assert_eq!(set.total(), total);
+        }
+        let set = Multiset::<u8, 1>::random_with_total(255, rng).unwrap();
+        assert_eq!(set, Multiset::from([255]));
+        assert_eq!(
+            Multiset::<u8, 1>::random_with_total(256, rng),
+            Err(MultisetError::CountOutOfRange { elem: 0 })
+        );
+        assert_eq!(
+            Multiset::<u8, 0>::random_with_total(0, rng),
+            Ok(Multiset::empty())
+        );
+        assert_eq!(
+            Multiset::<u8, 0>::random_with_total(1, rng),
+            Err(MultisetError::ElementOutOfBounds { elem: 0, size: 0 })
+        );
+
+        let first = Multiset::<u16, 37>::random_with_total(500, &mut StdRng::seed_from_u64(5));
+        let second = Multiset::<u16, 37>::random_with_total(500, &mut StdRng::seed_from_u64(5));
+        assert_eq!(first, second);
     }
 
+    #[cfg(feature = "rand")]
     #[test]
-    fn test_empty() {
-        let result = Multiset::<u32, 7>::empty();
-        let expected = Multiset::from([0u32; 7]);
-        assert_eq!(result, expected)
+    fn test_from_iter_sampled() {
+        let rng = &mut StdRng::seed_from_u64(53);
+        for &len in &[0, 1, 50, 100, 101, 10_000] {
+            for &max_total in &[0, 1, 100] {
+                let stream = (0..len).map(|i| (i * 7) % 37);
+                let set = Multiset::<u16, 37>::from_iter_sampled(stream, max_total, rng);
+                assert_eq!(set.total(), len.min(max_total));
+                assert!(set.is_subset(&Multiset::from_elements((0..len).map(|i| (i * 7) % 37))));
+            }
+        }
+        let stream = vec![3, 1, 3];
+        assert_eq!(
+            Multiset::<u8, 4>::from_iter_sampled(&stream, 10, rng),
+            Multiset::from([0, 1, 0, 2])
+        );
+
+        // Items late in the stream are as likely to be kept as early ones.
+        let stream: Vec<usize> = (0..4000).map(|i| if i < 1000 { 0 } else { 1 }).collect();
+        let mut kept = [0usize; 2];
+        for _ in 0..400 {
+            let set = Multiset::<u32, 2>::from_iter_sampled(&stream, 100, rng);
+            kept[0] += set[0] as usize;
+            kept[1] += set[1] as usize;
+        }
+        assert_relative_eq!(kept[0] as f64 / 400.0, 25.0, max_relative = 0.03);
+        assert_relative_eq!(kept[1] as f64 / 400.0, 75.0, max_relative = 0.03);
+
+        let sampled = |seed| {
+            let rng = &mut StdRng::seed_from_u64(seed);
+            Multiset::<u16, 37>::from_iter_sampled((0..10_000).map(|i| (i * i) % 37), 500, rng)
+        };
+        assert_eq!(sampled(5), sampled(5));
     }
 
+    #[cfg(feature = "rand")]
     #[test]
-    fn test_repeat() {
-        let result = Multiset::<u8, 4>::repeat(3);
-        let expected = Multiset::from([3u8; 4]);
-        assert_eq!(result, expected)
+    #[should_panic]
+    fn test_from_iter_sampled_out_of_bounds() {
+        let rng = &mut StdRng::seed_from_u64(53);
+        let _ = Multiset::<u8, 4>::from_iter_sampled(vec![0, 1, 4], 1, rng);
     }
 
+    #[cfg(feature = "rand")]
     #[test]
-    fn test_from_elements() {
-        let into_it = &[0, 1, 1, 2, 2, 2];
-        let result = Multiset::<u8, 4>::from_elements(into_it);
-        assert_eq!(result, Multiset::from([1, 2, 3, 0]))
+    fn test_random_uniform_counts() {
+        let rng = &mut StdRng::seed_from_u64(29);
+        let mut seen = [false; 4];
+        for _ in 0..100 {
+            let set = Multiset::<u32, 37>::random_uniform_counts(3, rng);
+            set.iter().for_each(|count| seen[*count as usize] = true);
+        }
+        assert_eq!(seen, [true; 4]);
+
+        let set = Multiset::<u8, 37>::random_uniform_counts(0, rng);
+        assert_eq!(set, Multiset::empty());
+        let set = Multiset::<u64, 37>::random_uniform_counts(u64::MAX, rng);
+        assert!(!set.is_empty());
+
+        let first = Multiset::<u16, 37>::random_uniform_counts(9, &mut StdRng::seed_from_u64(5));
+        let second = Multiset::<u16, 37>::random_uniform_counts(9, &mut StdRng::seed_from_u64(5));
+        assert_eq!(first, second);
     }
 
+    #[cfg(feature = "rand")]
     #[test]
-    fn test_from_elements_panic() {
-        let into_it = &[9]; // contains a value larger than the multiset size
-        let res = catch_unwind_silent(|| Multiset::<u8, 4>::from_elements(into_it));
-        assert!(res.is_err())
+    fn test_drain_random() {
+        let rng = &mut StdRng::seed_from_u64(31);
+        proptest!(|(original in counts_up_to::<u16, 37>(5), seed in any::<u64>())| {
+            let rng = &mut StdRng::seed_from_u64(seed);
+            let mut set = original;
+            let drawn: Vec<usize> = (0..original.total())
+                .map(|_| set.drain_random(rng).unwrap())
+                .collect();
+            prop_assert!(set.is_empty());
+            prop_assert_eq!(set.drain_random(rng), None);
+            prop_assert_eq!(Multiset::from_elements(&drawn), original);
+        });
+
+        // The first member drawn is distributed like sample.
+        let original = Multiset::from([1u16, 0, 3, 6]);
+        let mut drawn = [0usize; 4];
+        for _ in 0..100_000 {
+            let mut set = original;
+            drawn[set.drain_random(rng).unwrap()] += 1;
+        }
+        for (count, expected) in drawn.iter().zip(&[0.1, 0.0, 0.3, 0.6]) {
+            assert_relative_eq!(*count as f64 / 100_000.0, expected, epsilon = 0.01);
+        }
     }
 
+    #[cfg(feature = "rand")]
     #[test]
-    fn from_array() {
-        let set: Multiset<u16, 3> = Multiset::from([5, 4, 3]);
-        assert_eq!(set.get(1), Some(&4))
+    fn test_random_split() {
+        let rng = &mut StdRng::seed_from_u64(37);
+        proptest!(|(set in counts_up_to::<u32, 37>(u32::MAX), seed in any::<u64>())| {
+            let rng = &mut StdRng::seed_from_u64(seed);
+            for &p in &[0.0, 0.1, 0.5, 0.9, 1.0] {
+                let (first, second) = set.random_split(p, rng);
+                prop_assert_eq!(first + second, set);
+            }
+            prop_assert_eq!(set.random_split(0.0, rng), (Multiset::empty(), set));
+            prop_assert_eq!(set.random_split(1.0, rng), (set, Multiset::empty()));
+        });
+
+        let set = Multiset::from([1_000_000u32, 0, 3, u32::MAX]);
+        let (first, _) = set.random_split(0.25, rng);
+        assert_relative_eq!(first[0] as f64, 250_000.0, max_relative = 0.01);
+        assert_eq!(first[1], 0);
+        assert_relative_eq!(first[3] as f64, u32::MAX as f64 / 4.0, max_relative = 0.01);
+
+        let first = set.random_split(0.5, &mut StdRng::seed_from_u64(5));
+        let second = set.random_split(0.5, &mut StdRng::seed_from_u64(5));
+        assert_eq!(first, second);
     }
 
+    #[cfg(feature = "rand")]
     #[test]
-    fn from_slice() {
-        let set: Multiset<u16, 3> = Multiset::from(&[5, 4, 3]);
-        assert_eq!(set.get(1), Some(&4))
+    #[should_panic]
+    fn test_random_split_invalid_p() {
+        let rng = &mut StdRng::seed_from_u64(37);
+        let _ = Multiset::<u8, 4>::repeat(1).random_split(1.5, rng);
     }
 
+    #[cfg(feature = "rand")]
     #[test]
-    fn to_array() {
-        let set: Multiset<u16, 3> = Multiset::from([5, 4, 3]);
-        let arr: [u16; 3] = set.into();
-        assert_eq!(arr, [5, 4, 3])
+    fn test_choose_gumbel() {
+        let original = Multiset::from([1u16, 0, 3, 6, 2]);
+        let log_weights = [
+            1f64.ln(),
+            50f64.ln(),
+            3f64.ln(),
+            6f64.ln(),
+            f64::NEG_INFINITY,
+        ];
+        let rng = &mut StdRng::seed_from_u64(47);
+
+        for _ in 0..100 {
+            let mut set = original;
+            assert_eq!(set.choose_gumbel(&log_weights, 0.0, rng), Some(3));
+            assert_eq!(set, Multiset::from([0, 0, 0, 6, 0]));
+        }
+        let mut tied = Multiset::from([0u16, 1, 1, 1]);
+        assert_eq!(tied.choose_gumbel(&[9.0, 2.0, 3.0, 3.0], 0.0, rng), Some(2));
+
+        // At temperature 1.0 the choice is proportional to the weights.
+        let draws = 100_000;
+        let mut chosen = [0usize; 5];
+        for _ in 0..draws {
+            let mut set = original;
+            let elem = set.choose_gumbel(&log_weights, 1.0, rng).unwrap();
+            assert!(set.is_singleton() && set.is_subset(&original));
+            chosen[elem] += 1;
+        }
+        for (count, expected) in chosen.iter().zip(&[0.1, 0.0, 0.3, 0.6, 0.0]) {
+            assert_relative_eq!(*count as f64 / draws as f64, expected, epsilon = 0.01);
+        }
+
+        let choices = |seed| {
+            let rng = &mut StdRng::seed_from_u64(seed);
+            (0..100)
+                .map(|_| {
+                    let mut set = original;
+                    set.choose_gumbel(&log_weights, 2.5, rng)
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(choices(5), choices(5));
+
+        let mut set = Multiset::from([0u16, 4, 0, 0, 7]);
+        let log_weights = [0.0, f64::NEG_INFINITY, 0.0, 0.0, f64::NAN];
+        assert_eq!(set.choose_gumbel(&log_weights, 1.0, rng), None);
+        assert_eq!(set, Multiset::from([0, 4, 0, 0, 7]));
     }
 
+    #[cfg(feature = "rand")]
     #[test]
-    fn test_clear() {
-        let mut set = Multiset::<u8, 4>::repeat(3);
-        set.clear();
-        let expected = Multiset::<u8, 4>::empty();
-        assert_eq!(set, expected)
+    fn test_resample_to_total() {
+        let rng = &mut StdRng::seed_from_u64(59);
+        proptest!(|(set in counts_up_to::<u16, 37>(3), seed in any::<u64>())| {
+            let rng = &mut StdRng::seed_from_u64(seed);
+            for &target in &[0, 1, 37, 1000, 65535] {
+                let resampled = set.resample_to_total(target, rng);
+                prop_assert_eq!(resampled.total(), target);
+                prop_assert!(resampled
+                    .iter()
+                    .zip(set.iter())
+                    .all(|(r, c)| *r == 0 || *c > 0));
+            }
+        });
+
+        let set = Multiset::from([1u32, 0, 3, 6]);
+        let mut resampled = [0usize; 4];
+        for _ in 0..1000 {
+            let sample = set.resample_to_total(100, rng);
+            resampled
+                .iter_mut()
+                .zip(sample.iter())
+                .for_each(|(r, s)| *r += *s as usize);
+        }
+        for (count, expected) in resampled.iter().zip(&[10.0, 0.0, 30.0, 60.0]) {
+            assert_relative_eq!(*count as f64 / 1000.0, expected, max_relative = 0.02);
+        }
+
+        assert_eq!(
+            Multiset::from([0u8, 1]).resample_to_total(1000, rng),
+            Multiset::from([0, u8::MAX])
+        );
+        let empty = Multiset::<u32, 4>::empty();
+        assert_eq!(empty.resample_to_total(1000, rng), empty);
+
+        let resampled = |seed| set.resample_to_total(500, &mut StdRng::seed_from_u64(seed));
+        assert_eq!(resampled(5), resampled(5));
     }
 
+    #[cfg(feature = "rand")]
     #[test]
-    fn test_contains() {
-        let set = Multiset::<u8, 5>::from([1, 0, 1, 0, 1]);
-        [0, 2, 4].iter().for_each(|elem| {
-            assert!(set.contains(*elem));
+    fn test_shuffle_counts() {
+        let rng = &mut StdRng::seed_from_u64(61);
+        proptest!(|(original in counts_up_to::<u16, 37>(5), seed in any::<u64>())| {
+            let rng = &mut StdRng::seed_from_u64(seed);
+            let mut sorted = original.data;
+            sorted.sort_unstable();
+            let mut set = original;
+            for _ in 0..10 {
+                set.shuffle_counts(rng);
+                prop_assert_eq!(set.total(), original.total());
+                prop_assert_eq!(set.count_non_zero(), original.count_non_zero());
+                let mut shuffled = set.data;
+                shuffled.sort_unstable();
+                prop_assert_eq!(shuffled, sorted);
+            }
         });
 
-        [1, 3].iter().for_each(|elem| {
-            assert!(!set.contains(*elem));
-        });
+        // Each count is equally likely to end up at each element.
+        let mut moved = [0usize; 4];
+        for _ in 0..40_000 {
+            let mut set = Multiset::from([1u8, 0, 0, 0]);
+            set.shuffle_counts(rng);
+            moved[set.elem_count_max().0] += 1;
+        }
+        for count in moved.iter() {
+            assert_relative_eq!(*count as f64 / 40_000.0, 0.25, epsilon = 0.01);
+        }
 
-        [5, 10].iter().for_each(|elem| {
-            assert!(!set.contains(*elem));
+        let shuffled = |seed| {
+            let mut set = pseudo_random::<u16, 37>(1, 5);
+            set.shuffle_counts(&mut StdRng::seed_from_u64(seed));
+            set
+        };
+        assert_eq!(shuffled(5), shuffled(5));
+    }
+
+    // The sampling methods over counters with the fewest and the most simd
+    // lanes, and sizes which leave a partial final chunk.
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_sampling_counter_widths() {
+        fn check<N: Counter, const SIZE: usize>(set: Multiset<N, SIZE>, rng: &mut StdRng) {
+            for _ in 0..100 {
+                let elem = set.sample(rng).unwrap();
+                assert!(set[elem] > N::zero());
+            }
+            let sampled = set.sample_counts(500, rng);
+            assert_eq!(sampled.total(), 500);
+            assert!(sampled
+                .iter()
+                .zip(set.iter())
+                .all(|(s, c)| *s == N::zero() || *c > N::zero()));
+
+            let mut drained = set;
+            let drawn: Vec<usize> = (0..set.total())
+                .map(|_| drained.drain_random(rng).unwrap())
+                .collect();
+            assert!(drained.is_empty());
+            assert_eq!(Multiset::<N, SIZE>::from_elements(drawn), set);
+        }
+
+        proptest!(|(
+            a in counts_up_to::<u8, 35>(5),
+            b in counts_up_to::<u8, 67>(5),
+            c in counts_up_to::<u64, 5>(5),
+            d in counts_up_to::<u64, 11>(5),
+            seed in any::<u64>(),
+        )| {
+            let rng = &mut StdRng::seed_from_u64(seed);
+            check(a, rng);
+            check(b, rng);
+            check(c, rng);
+            check(d, rng);
         });
     }
 
+    #[cfg(feature = "rand")]
     #[test]
-    fn test_contains_unchecked() {
-        let set = Multiset::<u8, 5>::from([1, 0, 1, 0, 1]);
-        unsafe {
-            [0, 2, 4].iter().for_each(|elem| {
-                assert!(set.contains_unchecked(*elem));
-            });
+    fn test_choose_random_empty() {
+        let mut result = Multiset::<u32, 5>::empty();
+        let test_rng = &mut StdRng::seed_from_u64(thread_rng().next_u64());
+        assert_eq!(result.choose_random(test_rng), None);
+        assert_eq!(result.data, [0; 5]);
 
-            [1, 3].iter().for_each(|elem| {
-                assert!(!set.contains_unchecked(*elem));
-            });
-        }
+        let mut result = Multiset::<u32, 0>::empty();
+        assert_eq!(result.choose_random(test_rng), None);
     }
 
     #[test]
-    fn test_insert() {
-        let mut set = Multiset::<u8, 4>::from([1, 1, 1, 1]);
-        assert_eq!(set.get(2), Some(&1));
-        set.insert(2, 5);
-        assert_eq!(set.get(2), Some(&5))
+    fn test_normalize() {
+        assert_eq!(Multiset::<u16, 37>::empty().normalize(), [0.0; 37]);
+        assert_eq!(Multiset::<u16, 0>::empty().normalize(), [0.0; 0]);
+
+        proptest!(|(set in counts_up_to::<u16, 37>(u16::MAX))| {
+            let probs = set.normalize();
+            assert_relative_eq!(probs.iter().sum::<f64>(), 1.0, epsilon = 1e-12);
+            let entropy: f64 = -probs
+                .iter()
+                .filter(|p| **p > 0.0)
+                .map(|p| p * p.ln())
+                .sum::<f64>();
+            assert_relative_eq!(entropy, set.shannon_entropy(), epsilon = 1e-12);
+        });
     }
 
     #[test]
-    fn test_insert_unchecked() {
-        let mut set = Multiset::<u8, 4>::from([1, 1, 1, 1]);
-        assert_eq!(set.get(2), Some(&1));
-        unsafe { set.insert_unchecked(2, 5) };
-        assert_eq!(set.get(2), Some(&5));
+    fn test_expected_value() {
+        let set = Multiset::from([2u8, 1, 1, 0]);
+        assert_eq!(set.expected_value(&[1.0, 2.0, 4.0, 100.0]), 2.0);
+        assert_eq!(set.expected_value(&[-3.0, 1.0, 1.0, 0.0]), -1.0);
+        assert_eq!(Multiset::<u8, 4>::empty().expected_value(&[1.0; 4]), 0.0);
+
+        let values: Vec<f64> = (0..37).map(|i| i as f64 / 4.0).collect();
+        proptest!(|(set in counts_up_to::<u16, 37>(1000))| {
+            assert_relative_eq!(set.expected_value(&[1.0; 37]), 1.0, epsilon = 1e-12);
+            let expected = set
+                .iter()
+                .zip(&values)
+                .map(|(count, value)| *count as f64 * value)
+                .sum::<f64>()
+                / set.total() as f64;
+            assert_relative_eq!(set.expected_value(&values), expected, epsilon = 1e-12);
+        });
     }
 
     #[test]
-    fn test_remove() {
-        let mut set = Multiset::<u64, 3>::from([2, 3, 4]);
-        assert_eq!(set.get(1), Some(&3));
-        set.remove(1);
-        assert_eq!(set.get(1), Some(&0))
+    #[should_panic]
+    fn test_expected_value_wrong_length() {
+        let _ = Multiset::<u8, 4>::repeat(1).expected_value(&[1.0; 3]);
     }
 
     #[test]
-    fn test_remove_unchecked() {
-        let mut set = Multiset::<u64, 3>::from([2, 3, 4]);
-        assert_eq!(set.get(1), Some(&3));
-        unsafe { set.remove_unchecked(1) };
-        assert_eq!(set.get(1), Some(&0))
+    fn test_moment() {
+        assert_eq!(Multiset::<u8, 0>::empty().moment(2), 0.0);
+        assert_eq!(Multiset::<u8, 4>::empty().moment(0), 4.0);
+        assert_eq!(Multiset::<u8, 4>::empty().moment(3), 0.0);
+
+        proptest!(|(set in counts_up_to::<u16, 37>(1000))| {
+            prop_assert_eq!(set.moment(0), 37.0);
+            prop_assert_eq!(set.moment(1), set.total() as f64);
+            let total = set.total() as f64;
+            assert_relative_eq!(
+                set.moment(2),
+                set.sum_squared_probabilities() * total * total,
+                max_relative = 1e-12
+            );
+            let cubes: u128 = set.iter().map(|&count| (count as u128).pow(3)).sum();
+            prop_assert_eq!(set.moment(3), cubes as f64);
+            let fourth: f64 = set.iter().map(|&count| (count as f64).powi(4)).sum();
+            assert_relative_eq!(set.moment(4), fourth, max_relative = 1e-12);
+        });
+
+        // The u128 sum overflows, so the moment is accumulated in f64.
+        let max = Multiset::<u64, 4>::repeat(u64::MAX);
+        assert_relative_eq!(
+            max.moment(3),
+            4.0 * (u64::MAX as f64).powi(3),
+            max_relative = 1e-12
+        );
+        assert_eq!(max.moment(u32::MAX), f64::INFINITY);
+        assert_eq!(Multiset::<u8, 4>::repeat(1).moment(u32::MAX), 4.0);
     }
 
     #[test]
-    fn test_get() {
-        let set = Multiset::<usize, 4>::from([6, 7, 8, 9]);
-        assert_eq!(set.get(1), Some(&7));
-        assert_eq!(set.get(9), None)
+    fn test_mean_variance_std_dev() {
+        fn welford(counts: &[u32]) -> (f64, f64) {
+            let mut mean = 0.0;
+            let mut m2 = 0.0;
+            for (i, count) in counts.iter().enumerate() {
+                let x = *count as f64;
+                let delta = x - mean;
+                mean += delta / (i + 1) as f64;
+                m2 += delta * (x - mean);
+            }
+            (mean, m2 / counts.len() as f64)
+        }
+
+        let constant = Multiset::<u32, 37>::repeat(1_000_003);
+        assert_eq!(constant.mean_count(), 1_000_003.0);
+        assert_eq!(constant.variance(), 0.0);
+        assert_eq!(constant.std_dev(), 0.0);
+        assert_eq!(Multiset::<u32, 37>::empty().variance(), 0.0);
+        assert!(Multiset::<u32, 0>::empty().mean_count().is_nan());
+        assert!(Multiset::<u32, 0>::empty().variance().is_nan());
+
+        proptest!(|(set in counts_up_to::<u32, 37>(u32::MAX))| {
+            let (mean, variance) = welford(set.as_slice());
+            assert_relative_eq!(set.mean_count(), mean, max_relative = 1e-9);
+            assert_relative_eq!(set.variance(), variance, max_relative = 1e-9);
+            assert_relative_eq!(set.std_dev(), variance.sqrt(), max_relative = 1e-9);
+        });
     }
 
     #[test]
-    fn test_get_unchecked() {
-        let set = Multiset::<usize, 4>::from([6, 7, 8, 9]);
-        unsafe { assert_eq!(set.get_unchecked(1), &7) }
+    fn test_median_quantile() {
+        let odd = Multiset::from([5u8, 1, 9, 0, 3, 3, 7]);
+        assert_eq!(odd.median_count(), 3);
+        assert_eq!(odd.count_quantile(0.0), 0);
+        assert_eq!(odd.count_quantile(-1.0), 0);
+        assert_eq!(odd.count_quantile(1.0), 9);
+        assert_eq!(odd.count_quantile(2.0), 9);
+        assert_eq!(odd.count_quantile(0.9), 7);
+
+        let even = Multiset::from([5u8, 1, 9, 0, 3, 7]);
+        assert_eq!(even.median_count(), 3);
+
+        let duplicates = Multiset::from([2u16, 0, 2, 2, 0, 2, 2, 5]);
+        assert_eq!(duplicates.median_count(), 2);
+        assert_eq!(duplicates.count_quantile(0.2), 0);
+        assert_eq!(duplicates.count_quantile(0.9), 2);
+        assert_eq!(Multiset::<u16, 37>::repeat(4).count_quantile(0.5), 4);
+
+        proptest!(|(set in counts_up_to::<u16, 37>(20))| {
+            let mut sorted = set.to_array();
+            sorted.sort_unstable();
+            for q in [0.0, 0.1, 0.25, 0.5, 0.75, 0.9, 1.0] {
+                let index = (q * 36.0) as usize;
+                prop_assert_eq!(set.count_quantile(q), sorted[index]);
+            }
+            prop_assert_eq!(set.median_count(), sorted[18]);
+        });
     }
 
     #[test]
-    fn test_intersection() {
-        let a = Multiset::<u8, 4>::from([1, 2, 5, 6]);
-        let b = Multiset::from([0, 1, 8, 9]);
-        let c = Multiset::from([0, 1, 5, 6]);
-        assert_eq!(c, a.intersection(&b))
+    #[should_panic]
+    fn test_median_empty_size() {
+        let _ = Multiset::<u8, 0>::empty().median_count();
     }
 
     #[test]
-    fn test_union() {
-        let a = Multiset::<u8, 4>::from([1, 2, 5, 6]);
-        let b = Multiset::from([0, 1, 8, 9]);
-        let c = Multiset::from([1, 2, 8, 9]);
-        assert_eq!(c, a.union(&b))
+    fn test_count_histogram() {
+        let set = Multiset::from([1u8, 4, 1, 0, 1]);
+        let mut expected = HashMap::new();
+        expected.insert(0, 1);
+        expected.insert(1, 3);
+        expected.insert(4, 1);
+        assert_eq!(set.count_histogram(), expected);
+
+        let empty = Multiset::<u16, 37>::empty();
+        assert_eq!(empty.count_histogram().get(&0), Some(&37));
+
+        proptest!(|(set in counts_up_to::<u16, 37>(10))| {
+            let histogram = set.count_histogram();
+            prop_assert_eq!(histogram.values().sum::<usize>(), 37);
+            for n in 0..=11 {
+                let greater: usize = histogram
+                    .iter()
+                    .filter(|(count, _)| **count > n)
+                    .map(|(_, elems)| elems)
+                    .sum();
+                prop_assert_eq!(set.count_greater_than(n), greater);
+                prop_assert_eq!(set.count_at_most(n), 37 - greater);
+            }
+            prop_assert_eq!(set.count_greater_than(0), set.count_non_zero());
+            prop_assert_eq!(set.count_at_most(0), set.count_zero());
+        });
+    }
+
+    #[test]
+    fn test_collision_entropy() {
+        let simple: Multiset<u8, 4> = Multiset::from([200, 0, 0, 0]);
+        assert_eq!(simple.collision_entropy(), 0.0);
+
+        let set: Multiset<u8, 4> = Multiset::from([2, 1, 1, 0]);
+        assert_relative_eq!(
+            set.collision_entropy(),
+            1.415037499278844,
+            epsilon = f64::EPSILON
+        );
     }
 
     #[test]
-    fn test_difference() {
-        let a = Multiset::<u8, 4>::from([0, 2, 5, 6]);
-        let b = Multiset::from([1, 1, 8, 0]);
-        let c = Multiset::from([0, 1, 5, 6]);
-        assert_eq!(c, a.difference(&b))
-    }
+    fn test_entropy_empty() {
+        fn assert_positive_zero(x: f64) {
+            assert_eq!(x.to_bits(), 0f64.to_bits(), "{} is not 0.0", x);
+        }
 
-    #[test]
-    fn test_symmetric_difference() {
-        let a = Multiset::<u8, 4>::from([0, 2, 5, 6]);
-        let b = Multiset::from([1, 1, 8, 0]);
-        let c = Multiset::from([1, 1, 5, 6]);
-        assert_eq!(c, a.symmetric_difference(&b))
-    }
+        assert_positive_zero(Multiset::<u8, 0>::empty().shannon_entropy());
+        assert_positive_zero(Multiset::<u8, 0>::empty().collision_entropy());
+        assert_positive_zero(Multiset::<u8, 4>::empty().shannon_entropy());
+        assert_positive_zero(Multiset::<u8, 4>::empty().collision_entropy());
+        assert_positive_zero(Multiset::<u32, 37>::empty().shannon_entropy());
+        assert_positive_zero(Multiset::<u32, 37>::empty().collision_entropy());
 
-    #[test]
-    fn test_count_zero() {
-        let set = Multiset::<u16, 7>::from([0, 1, 3, 0, 8, 0, 0]);
-        assert_eq!(set.count_zero(), 4)
+        let mut singleton = Multiset::<u16, 37>::empty();
+        singleton.insert(36, 9);
+        assert_positive_zero(singleton.shannon_entropy());
+        assert_positive_zero(singleton.collision_entropy());
     }
 
     #[test]
-    fn test_count_non_zero() {
-        let set = Multiset::<u16, 7>::from([0, 1, 3, 0, 8, 0, 0]);
-        assert_eq!(set.count_non_zero(), 3)
+    fn test_compensated_sum() {
+        assert_eq!(compensated_sum(vec![]).to_bits(), 0f64.to_bits());
+        assert_eq!(compensated_sum(vec![-0.0, -0.0]).to_bits(), 0f64.to_bits());
+        assert_eq!(compensated_sum(vec![1.0, 1e100, 1.0, -1e100]), 2.0);
+        let tiny = std::iter::once(1.0).chain(std::iter::repeat_n(1e-17, 100_000));
+        assert_relative_eq!(compensated_sum(tiny), 1.0 + 1e-12, max_relative = 1e-15);
+        assert!(compensated_sum(vec![1.0, f64::NAN]).is_nan());
     }
 
+    // One huge count and many ones, so that the terms of the ones are tiny
+    // next to the term of the huge count.
     #[test]
-    fn test_is_empty() {
-        let a = Multiset::from([1u8; 3]);
-        let b = Multiset::<u8, 3>::empty();
-        assert!(!a.is_empty());
-        assert!(b.is_empty());
+    fn test_entropy_precision() {
+        const SIZE: usize = 40_000;
+        let mut set = Multiset::<u32, SIZE>::repeat(1);
+        for &huge in &[4_000_000_000u32, 1_000_000, 40_000] {
+            set.data[0] = huge;
+            let ones = (SIZE - 1) as f64;
+            let total = huge as f64 + ones;
+            let prob_huge = huge as f64 / total;
+            // ln(prob_huge) and prob_huge^2 are computed from ln_1p to avoid
+            // cancellation when prob_huge is close to 1.
+            let shannon = -prob_huge * (-ones / total).ln_1p() + ones * total.ln() / total;
+            let sum_squares = -2.0 * ones / total + (ones * ones + ones) / (total * total);
+            let collision = -sum_squares.ln_1p() / 2f64.ln();
+            assert_relative_eq!(set.shannon_entropy(), shannon, max_relative = 1e-12);
+            assert_relative_eq!(set.collision_entropy(), collision, max_relative = 1e-12);
+        }
     }
 
     #[test]
-    fn test_is_singleton() {
-        let a = Multiset::<u16, 4>::from([0, 0, 8, 0]);
-        assert!(a.is_singleton());
+    fn test_weighted_shannon_entropy() {
+        fn reference(counts: &[u16], weights: &[f64]) -> f64 {
+            let support: Vec<f64> = counts
+                .iter()
+                .zip(weights)
+                .filter(|(c, _)| **c > 0)
+                .map(|(_, w)| *w)
+                .collect();
+            let total: f64 = support.iter().sum();
+            -support
+                .iter()
+                .filter(|w| **w > 0.0)
+                .map(|w| (w / total) * (w / total).ln())
+                .sum::<f64>()
+        }
 
-        let b = Multiset::<u16, 4>::from([0, 2, 8, 0]);
-        assert!(!b.is_singleton());
+        let set = Multiset::from([3u8, 0, 1, 1]);
+        assert_relative_eq!(
+            set.weighted_shannon_entropy(&[1.0, 100.0, 2.0, 1.0]),
+            Multiset::from([2u8, 1, 1, 0]).shannon_entropy(),
+            epsilon = 1e-12
+        );
+        assert_eq!(set.weighted_shannon_entropy(&[0.0, 1.0, 0.0, 0.0]), 0.0);
+        assert_eq!(
+            Multiset::<u8, 4>::empty().weighted_shannon_entropy(&[1.0; 4]),
+            0.0
+        );
 
-        let c = Multiset::<u16, 4>::empty();
-        assert!(!c.is_singleton());
+        let weights: Vec<f64> = (0..37).map(|i| ((i * 7) % 11) as f64 / 3.0).collect();
+        proptest!(|(set in counts_up_to::<u16, 37>(3))| {
+            assert_relative_eq!(
+                set.weighted_shannon_entropy(&weights),
+                reference(set.as_slice(), &weights),
+                epsilon = 1e-12
+            );
+            assert_relative_eq!(
+                set.weighted_shannon_entropy(&[0.25; 37]),
+                (set.count_non_zero() as f64).ln(),
+                epsilon = 1e-12
+            );
+        });
     }
 
     #[test]
-    fn test_is_disjoint() {
-        let a = Multiset::<u8, 4>::from([1, 1, 0, 0]);
-        let b = Multiset::from([0, 0, 1, 1]);
-        let c = Multiset::from([0, 1, 1, 0]);
-
-        assert!(a.is_disjoint(&b));
-        assert!(!a.is_disjoint(&c));
+    #[should_panic]
+    fn test_weighted_shannon_entropy_wrong_length() {
+        let _ = Multiset::<u8, 4>::repeat(1).weighted_shannon_entropy(&[1.0; 5]);
     }
 
     #[test]
-    fn test_is_subset() {
-        let a = Multiset::<u8, 3>::from([1, 1, 1]);
-        let b = Multiset::from([2, 2, 2]);
-        assert!(a.is_subset(&b));
-        assert!(!b.is_subset(&a));
-
-        let c = Multiset::from([0, 1, 2]);
-        assert!(!a.is_subset(&c));
-        assert!(!c.is_subset(&a));
+    fn test_kl_divergence() {
+        let p = Multiset::from([2u8, 1, 1, 0]);
+        let q = Multiset::from([1u8, 1, 1, 1]);
+        assert_relative_eq!(
+            p.kl_divergence(&q),
+            0.3465735902799727,
+            epsilon = f64::EPSILON
+        );
+        assert_eq!(p.kl_divergence(&p), 0.0);
+        assert_eq!(p.kl_divergence(&(p * 3)), 0.0);
+        assert_eq!(q.kl_divergence(&p), f64::INFINITY);
 
-        let d = Multiset::from([1, 1, 1]);
-        assert!(a.is_subset(&d));
-        assert!(d.is_subset(&a));
+        assert!(p.kl_divergence(&Multiset::empty()).is_nan());
+        assert!(Multiset::<u8, 4>::empty().kl_divergence(&q).is_nan());
+        assert!(Multiset::<u8, 0>::empty()
+            .kl_divergence(&Multiset::empty())
+            .is_nan());
     }
 
     #[test]
-    fn test_is_superset() {
-        let a = Multiset::<u8, 3>::from([1, 1, 1]);
-        let b = Multiset::from([2, 2, 2]);
-        assert!(!a.is_superset(&b));
-        assert!(b.is_superset(&a));
-
-        let c = Multiset::from([0, 1, 2]);
-        assert!(!a.is_superset(&c));
-        assert!(!c.is_superset(&a));
+    fn test_kl_divergence_reference() {
+        fn reference(p: &[u32], q: &[u32]) -> f64 {
+            let total_p = p.iter().sum::<u32>() as f64;
+            let total_q = q.iter().sum::<u32>() as f64;
+            p.iter()
+                .zip(q)
+                .filter(|(a, _)| **a > 0)
+                .map(|(a, b)| {
+                    let prob_p = *a as f64 / total_p;
+                    prob_p * (prob_p / (*b as f64 / total_q)).ln()
+                })
+                .sum()
+        }
 
-        let d = Multiset::from([1, 1, 1]);
-        assert!(a.is_superset(&d));
-        assert!(d.is_superset(&a));
+        proptest!(|(p in counts_up_to::<u32, 37>(1000), q in counts_up_to::<u32, 37>(1000))| {
+            let q = q.max_scalar(1);
+            assert_relative_eq!(
+                p.kl_divergence(&q),
+                reference(p.as_slice(), q.as_slice()),
+                epsilon = 1e-12
+            );
+        });
     }
 
     #[test]
-    fn test_is_proper_subset() {
-        let a = Multiset::from([1u8; 3]);
-        let b = Multiset::from([2u8; 3]);
-        assert!(a.is_proper_subset(&b));
-        assert!(!b.is_proper_subset(&a));
+    fn test_cross_entropy() {
+        let p = Multiset::from([2u8, 1, 1, 0]);
+        let q = Multiset::from([1u8, 1, 1, 1]);
+        assert_relative_eq!(
+            p.cross_entropy(&q),
+            p.shannon_entropy() + p.kl_divergence(&q),
+            epsilon = 1e-12
+        );
+        assert_relative_eq!(p.cross_entropy_bits(&q), 2.0, epsilon = 1e-12);
+        assert_eq!(q.cross_entropy(&p), f64::INFINITY);
+        assert_eq!(q.cross_entropy_bits(&p), f64::INFINITY);
 
-        let c = Multiset::from([0u8, 1, 2]);
-        assert!(!a.is_proper_subset(&c));
-        assert!(!c.is_proper_subset(&a));
+        assert!(p.cross_entropy(&Multiset::empty()).is_nan());
+        assert!(Multiset::<u8, 4>::empty().cross_entropy(&q).is_nan());
+    }
 
-        let d = Multiset::from([1u8; 3]);
-        assert!(!a.is_proper_subset(&d));
-        assert!(!d.is_proper_subset(&a));
+    #[test]
+    fn test_cross_entropy_identical() {
+        proptest!(|(p in counts_up_to::<u16, 37>(1000), q in counts_up_to::<u16, 37>(1000))| {
+            assert_relative_eq!(p.cross_entropy(&p), p.shannon_entropy(), epsilon = 1e-12);
+            let q = q.max_scalar(1);
+            assert_relative_eq!(
+                p.cross_entropy(&q),
+                p.shannon_entropy() + p.kl_divergence(&q),
+                epsilon = 1e-12
+            );
+        });
     }
 
     #[test]
-    fn test_is_proper_superset() {
-        let a = Multiset::from([1u8; 3]);
-        let b = Multiset::from([2u8; 3]);
-        assert!(!a.is_proper_superset(&b));
-        assert!(b.is_proper_superset(&a));
+    fn test_jaccard_index() {
+        let a = Multiset::from([1u8, 2, 0, 4]);
+        let b = Multiset::from([0u8, 3, 1, 4]);
+        assert_eq!(a.jaccard_index(&b), 6.0 / 9.0);
+        assert_eq!(a.jaccard_index(&b), b.jaccard_index(&a));
+        assert_eq!(a.jaccard_index(&a), 1.0);
+        assert_eq!(a.jaccard_index(&Multiset::empty()), 0.0);
+        assert_eq!(
+            Multiset::<u8, 4>::empty().jaccard_index(&Multiset::empty()),
+            1.0
+        );
 
-        let c = Multiset::from([0u8, 1, 2]);
-        assert!(!a.is_proper_superset(&c));
-        assert!(!c.is_proper_superset(&a));
+        proptest!(|(mut a in counts_up_to::<u16, 37>(u16::MAX), mut b in counts_up_to::<u16, 37>(u16::MAX))| {
+            a.insert(3, u16::MAX);
+            b.insert(3, u16::MAX);
+            b.insert(36, u16::MAX);
+            let expected = a.intersection(&b).total() as f64 / a.union(&b).total() as f64;
+            prop_assert_eq!(a.jaccard_index(&b), expected);
+        });
 
-        let d = Multiset::from([1u8; 3]);
-        assert!(!a.is_proper_superset(&d));
-        assert!(!d.is_proper_superset(&a));
+        let full = Multiset::<u64, 3>::repeat(u64::MAX);
+        let mut half = full;
+        half.insert(0, u64::MAX / 2);
+        assert_eq!(full.jaccard_index(&full), 1.0);
+        assert_relative_eq!(full.jaccard_index(&half), 2.5 / 3.0, max_relative = 1e-12);
     }
 
     #[test]
-    fn test_is_any_lesser() {
-        let a = Multiset::from([1u8; 3]);
-        let b = Multiset::from([2u8; 3]);
-        assert!(a.is_any_lesser(&b));
-        assert!(!b.is_any_lesser(&a));
-
-        let c = Multiset::from([0u8, 1, 2]);
-        assert!(a.is_any_lesser(&c));
-        assert!(c.is_any_lesser(&a));
+    fn test_overlap_coefficient() {
+        let a = Multiset::from([1u8, 2, 0, 4]);
+        let b = Multiset::from([0u8, 3, 1, 4]);
+        assert_eq!(a.overlap_coefficient(&b), 6.0 / 7.0);
+        assert_eq!(b.overlap_coefficient(&a), 6.0 / 7.0);
+        assert_eq!(a.overlap_coefficient(&Multiset::empty()), 0.0);
+        assert_eq!(
+            Multiset::<u8, 4>::empty().overlap_coefficient(&Multiset::empty()),
+            0.0
+        );
 
-        let d = Multiset::from([1u8; 3]);
-        assert!(!a.is_any_lesser(&d));
-        assert!(!d.is_any_lesser(&a));
+        // The empty case wins over the subset case.
+        let empty = Multiset::<u8, 4>::empty();
+        assert!(empty.is_subset(&a));
+        assert_eq!(empty.overlap_coefficient(&a), 0.0);
+        assert_eq!(a.overlap_coefficient(&empty), 0.0);
+
+        proptest!(|(a in counts_up_to::<u16, 37>(1000), b in counts_up_to::<u16, 37>(1000))| {
+            let sub = a.intersection(&b);
+            prop_assert!(sub.is_subset(&a));
+            if !sub.is_empty() {
+                prop_assert_eq!(sub.overlap_coefficient(&a), 1.0);
+                prop_assert_eq!(a.overlap_coefficient(&sub), 1.0);
+            }
+            let coefficient = a.overlap_coefficient(&b);
+            prop_assert!((0.0..=1.0).contains(&coefficient));
+            prop_assert!(coefficient >= a.jaccard_index(&b));
+        });
     }
 
     #[test]
-    fn test_is_any_greater() {
-        let a = Multiset::from([1u8; 3]);
-        let b = Multiset::from([2u8; 3]);
-        assert!(!a.is_any_greater(&b));
-        assert!(b.is_any_greater(&a));
+    fn test_tversky_index() {
+        let a = Multiset::from([1u8, 2, 0, 4]);
+        let b = Multiset::from([0u8, 3, 1, 4]);
+        assert_eq!(a.tversky_index(&b, 1.0, 0.0), 6.0 / 7.0);
+        assert_eq!(a.tversky_index(&b, 0.0, 1.0), 6.0 / 8.0);
+        assert_eq!(a.tversky_index(&a, 0.3, 0.7), 1.0);
+        assert_eq!(
+            Multiset::<u8, 4>::empty().tversky_index(&Multiset::empty(), 0.5, 0.5),
+            1.0
+        );
 
-        let c = Multiset::from([0u8, 1, 2]);
-        assert!(a.is_any_greater(&c));
-        assert!(c.is_any_greater(&a));
+        // Disjoint multisets share nothing, even when the differences are not
+        // weighted at all.
+        let c = Multiset::from([0u8, 0, 5, 0]);
+        assert_eq!(a.tversky_index(&c, 0.0, 0.0), 0.0);
+        assert_eq!(a.tversky_index(&c, 1.0, 1.0), 0.0);
+        assert_eq!(a.tversky_index(&Multiset::empty(), 0.0, 0.0), 0.0);
+
+        let full = Multiset::<u64, 3>::repeat(u64::MAX);
+        let mut half = full;
+        half.insert(0, u64::MAX / 2);
+        assert_eq!(full.tversky_index(&full, 0.5, 0.5), 1.0);
+        assert_relative_eq!(
+            full.tversky_index(&half, 1.0, 1.0),
+            full.jaccard_index(&half),
+            max_relative = 1e-12
+        );
 
-        let d = Multiset::from([1u8; 3]);
-        assert!(!a.is_any_greater(&d));
-        assert!(!d.is_any_greater(&a));
+        proptest!(|(a in counts_up_to::<u16, 37>(1000), b in counts_up_to::<u16, 37>(1000))| {
+            let dice = 2.0 * a.count_intersection(&b) as f64 / (a.total() + b.total()) as f64;
+            assert_relative_eq!(a.tversky_index(&b, 0.5, 0.5), dice, epsilon = 1e-12);
+            assert_relative_eq!(
+                a.tversky_index(&b, 1.0, 1.0),
+                a.jaccard_index(&b),
+                epsilon = 1e-12
+            );
+            assert_relative_eq!(
+                a.tversky_index(&b, 0.2, 0.8),
+                b.tversky_index(&a, 0.8, 0.2),
+                epsilon = 1e-12
+            );
+        });
     }
 
     #[test]
-    fn test_total() {
-        let set = Multiset::from([1u8, 2, 3, 4]);
-        assert_eq!(set.total(), 10)
+    fn test_dot() {
+        let a = Multiset::from([1u16, 2, 0, 4]);
+        let b = Multiset::from([5u16, 3, 9, 1]);
+        assert_eq!(a.dot(&b), 15);
+        assert_eq!(a.dot(&Multiset::empty()), 0);
+
+        let max = Multiset::<u16, 37>::repeat(u16::MAX);
+        assert_eq!(max.dot(&max), 37 * (u16::MAX as u64).pow(2));
+        let max = Multiset::<u32, 1>::repeat(u32::MAX);
+        assert_eq!(max.dot(&max), (u32::MAX as u64).pow(2));
+        let max = Multiset::<u32, 5>::repeat(u32::MAX);
+        assert_eq!(max.dot(&max), u64::MAX);
+        let max = Multiset::<u64, 2>::repeat(u64::MAX);
+        assert_eq!(max.dot(&max), u64::MAX);
     }
 
     #[test]
-    fn test_argmax() {
-        let set = Multiset::from([1u8, 0, 3, 1]);
-        let expected = (2, &3);
-        assert_eq!(set.elem_count_max(), expected)
+    fn test_l1_distance() {
+        let a = pseudo_random::<u16, 37>(1, u16::MAX as u64);
+        assert_eq!(a.l1_distance(&a), 0);
+
+        let b = Multiset::from([0u16, 7, 0, 2]);
+        let c = Multiset::from([4u16, 0, 1, 0]);
+        assert_eq!(b.l1_distance(&c), (b.total() + c.total()) as u64);
+
+        let max = Multiset::<u16, 37>::repeat(u16::MAX);
+        assert_eq!(max.l1_distance(&Multiset::empty()), 37 * u16::MAX as u64);
+
+        proptest!(|(a in counts_up_to::<u16, 37>(u16::MAX), b in counts_up_to::<u16, 37>(u16::MAX))| {
+            let expected = a
+                .iter()
+                .zip(b.iter())
+                .fold(0u64, |acc, (x, y)| acc + x.abs_diff(*y) as u64);
+            prop_assert_eq!(a.l1_distance(&b), expected);
+            prop_assert_eq!(a.l1_distance(&b), b.l1_distance(&a));
+        });
     }
 
     #[test]
-    fn test_imax() {
-        let set = Multiset::from([1u8, 0, 3, 1]);
-        let expected = 2;
-        assert_eq!(set.elem_max(), expected)
-    }
+    fn test_l2_distance() {
+        let a = pseudo_random::<u16, 37>(1, u16::MAX as u64);
+        assert_eq!(a.l2_distance_squared(&a), 0);
+        assert_eq!(a.l2_distance(&a), 0.0);
+
+        // Maximal differences on adjacent elements.
+        let mut max = Multiset::<u16, 37>::empty();
+        max.insert(15, u16::MAX);
+        max.insert(16, u16::MAX);
+        max.insert(17, u16::MAX);
+        let expected = 3 * (u16::MAX as u64).pow(2);
+        assert_eq!(max.l2_distance_squared(&Multiset::empty()), expected);
+        assert_eq!(Multiset::empty().l2_distance_squared(&max), expected);
+        let max = Multiset::<u16, 37>::repeat(u16::MAX);
+        assert_eq!(
+            max.l2_distance_squared(&Multiset::empty()),
+            37 * (u16::MAX as u64).pow(2)
+        );
+        let max = Multiset::<u64, 2>::repeat(u64::MAX);
+        assert_eq!(max.l2_distance_squared(&Multiset::empty()), u64::MAX);
 
-    #[test]
-    fn test_max() {
-        let set = Multiset::from([1u8, 0, 3, 1]);
-        let expected = &3;
-        assert_eq!(set.count_max(), expected)
-    }
+        // Past u64::MAX the squared distance saturates, but the distance is
+        // still the root of the exact sum.
+        let max = Multiset::<u32, 4>::repeat(u32::MAX);
+        assert_eq!(max.l2_distance_squared(&Multiset::empty()), u64::MAX);
+        assert_relative_eq!(
+            max.l2_distance(&Multiset::empty()),
+            2.0 * u32::MAX as f64,
+            max_relative = 1e-15
+        );
+        let max = Multiset::<u64, 4>::repeat(u64::MAX);
+        assert_relative_eq!(
+            Multiset::empty().l2_distance(&max),
+            2.0 * u64::MAX as f64,
+            max_relative = 1e-15
+        );
 
-    #[test]
-    fn test_argmin() {
-        let set = Multiset::from([1u8, 0, 3, 1]);
-        let expected = (1, &0);
-        assert_eq!(set.elem_count_min(), expected)
+        proptest!(|(a in counts_up_to::<u16, 37>(u16::MAX), b in counts_up_to::<u16, 37>(u16::MAX))| {
+            let expected = a
+                .iter()
+                .zip(b.iter())
+                .fold(0u64, |acc, (x, y)| acc + (x.abs_diff(*y) as u64).pow(2));
+            prop_assert_eq!(a.l2_distance_squared(&b), expected);
+            prop_assert_eq!(a.l2_distance(&b), (expected as f64).sqrt());
+        });
     }
 
     #[test]
-    fn test_imin() {
-        let set = Multiset::from([1u8, 0, 3, 1]);
-        let expected = 1;
-        assert_eq!(set.elem_min(), expected)
+    fn test_dot_reference() {
+        fn reference<N: Counter, const SIZE: usize>(
+            a: &Multiset<N, SIZE>,
+            b: &Multiset<N, SIZE>,
+        ) -> u128 {
+            a.iter()
+                .zip(b.iter())
+                .map(|(x, y)| <N as AsPrimitive<u128>>::as_(*x) * <N as AsPrimitive<u128>>::as_(*y))
+                .sum()
+        }
+
+        let max = Multiset::<u8, 1000>::repeat(u8::MAX);
+        assert_eq!(max.dot(&max) as u128, reference(&max, &max));
+        let max = Multiset::<u16, 1000>::repeat(u16::MAX);
+        assert_eq!(max.dot(&max) as u128, reference(&max, &max));
+        let max = Multiset::<u32, 1>::repeat(u32::MAX);
+        assert_eq!(max.dot(&max) as u128, reference(&max, &max));
+
+        proptest!(|(a in counts_up_to::<u8, 37>(u8::MAX), b in counts_up_to::<u8, 37>(u8::MAX))| {
+            prop_assert_eq!(a.dot(&b) as u128, reference(&a, &b));
+        });
+        proptest!(|(a in counts_up_to::<u16, 37>(u16::MAX), b in counts_up_to::<u16, 37>(u16::MAX))| {
+            prop_assert_eq!(a.dot(&b) as u128, reference(&a, &b));
+        });
+        proptest!(|(a in counts_up_to::<u32, 37>(u16::MAX as u32), b in counts_up_to::<u32, 37>(u32::MAX))| {
+            prop_assert_eq!(a.dot(&b) as u128, reference(&a, &b));
+        });
     }
 
     #[test]
-    fn test_min() {
-        let set = Multiset::from([1u8, 0, 3, 1]);
-        let expected = &0;
-        assert_eq!(set.count_min(), expected)
+    fn test_cosine_similarity() {
+        fn reference(a: &[u16], b: &[u16]) -> f64 {
+            let dot: f64 = a.iter().zip(b).map(|(x, y)| *x as f64 * *y as f64).sum();
+            let norm_a: f64 = a.iter().map(|x| (*x as f64).powi(2)).sum::<f64>().sqrt();
+            let norm_b: f64 = b.iter().map(|x| (*x as f64).powi(2)).sum::<f64>().sqrt();
+            dot / (norm_a * norm_b)
+        }
+
+        let a = Multiset::from([1u16, 2, 0, 2]);
+        let b = Multiset::from([0u16, 0, 3, 0]);
+        assert_eq!(a.cosine_similarity(&b), 0.0);
+        assert_eq!(a.cosine_similarity(&a), 1.0);
+        assert_eq!(Multiset::<u16, 4>::empty().cosine_similarity(&a), 0.0);
+        assert_eq!(
+            Multiset::<u16, 4>::empty().cosine_similarity(&Multiset::empty()),
+            0.0
+        );
+
+        proptest!(|(a in counts_up_to::<u16, 37>(u16::MAX), b in counts_up_to::<u16, 37>(u16::MAX))| {
+            let a = a.max_scalar(u16::MAX - 10);
+            let similarity = a.cosine_similarity(&b);
+            assert_relative_eq!(
+                similarity,
+                reference(a.as_slice(), b.as_slice()),
+                epsilon = 1e-12
+            );
+            prop_assert!((0.0..=1.0).contains(&similarity));
+        });
     }
 
     #[test]
-    fn test_choose() {
-        let mut set = Multiset::from([1u8, 2, 3, 4, 5]);
-        let expected = Multiset::from([0u8, 0, 0, 4, 0]);
-        set.choose(3);
-        assert_eq!(set, expected)
+    fn test_hellinger_distance() {
+        fn reference(p: &[u32], q: &[u32]) -> f64 {
+            let total_p = p.iter().sum::<u32>() as f64;
+            let total_q = q.iter().sum::<u32>() as f64;
+            let coefficient: f64 = p
+                .iter()
+                .zip(q)
+                .map(|(a, b)| (*a as f64 / total_p * *b as f64 / total_q).sqrt())
+                .sum();
+            (1.0 - coefficient).sqrt()
+        }
+
+        let a = Multiset::from([1u8, 1, 0, 0]);
+        let b = Multiset::from([0u8, 0, 3, 1]);
+        assert_eq!(a.hellinger_distance(&b), 1.0);
+        assert_eq!(a.hellinger_distance(&a), 0.0);
+        assert_eq!(a.hellinger_distance(&Multiset::empty()), 1.0);
+        assert_eq!(Multiset::<u8, 4>::empty().hellinger_distance(&a), 1.0);
+        assert_eq!(
+            Multiset::<u8, 4>::empty().hellinger_distance(&Multiset::empty()),
+            0.0
+        );
+
+        proptest!(|(p in counts_up_to::<u32, 37>(1000), q in counts_up_to::<u32, 37>(1000))| {
+            let distance = p.hellinger_distance(&q);
+            prop_assert!((0.0..=1.0).contains(&distance));
+            assert_relative_eq!(
+                distance,
+                reference(p.as_slice(), q.as_slice()),
+                epsilon = 1e-12
+            );
+            assert_relative_eq!(distance, q.hellinger_distance(&p), epsilon = 1e-12);
+            prop_assert!((0.0..=1.0).contains(&p.hellinger_distance(&p)));
+        });
     }
 
-    #[cfg(feature = "rand")]
     #[test]
-    fn test_choose_random() {
-        let mut result1 = Multiset::from([1u8, 2, 3, 4, 5]);
-        let test_rng1 = &mut StdRng::seed_from_u64(thread_rng().next_u64());
-        result1.choose_random(test_rng1);
-        assert!(result1.is_singleton() && result1.is_subset(&Multiset::from([1u8, 2, 3, 4, 5])));
+    fn test_emd_1d() {
+        let a = Multiset::from([1u8, 0, 0, 0, 0]);
+        for k in 0..5 {
+            let mut b = Multiset::empty();
+            b.insert(k, 3);
+            assert_eq!(a.emd_1d(&b), k as f64);
+            assert_eq!(b.emd_1d(&a), k as f64);
+        }
 
-        let mut result2 = Multiset::from([1u8, 2, 3, 4, 5]);
-        let test_rng2 = &mut StdRng::seed_from_u64(thread_rng().next_u64());
-        result2.choose_random(test_rng2);
-        assert!(result2.is_singleton() && result2.is_subset(&Multiset::from([1u8, 2, 3, 4, 5])));
+        // Shifting one unit of mass by k elements.
+        let a = Multiset::from([2u16, 0, 3, 0, 0, 5]);
+        for k in 1..4 {
+            let mut b = a;
+            b.insert(2, 2);
+            b.insert(2 + k, b.get(2 + k).unwrap() + 1);
+            assert_relative_eq!(a.emd_1d(&b), k as f64 / 10.0, epsilon = 1e-12);
+        }
+
+        assert_eq!(a.emd_1d(&(a * 4)), 0.0);
+
+        let empty = Multiset::<u16, 6>::empty();
+        assert_eq!(a.emd_1d(&empty), 1.0);
+        assert_eq!(empty.emd_1d(&a), 1.0);
+        assert_eq!(empty.emd_1d(&empty), 0.0);
     }
 
-    #[cfg(feature = "rand")]
     #[test]
-    fn test_choose_random_empty() {
-        let mut result = Multiset::<u32, 5>::empty();
-        let test_rng = &mut StdRng::seed_from_u64(thread_rng().next_u64());
-        result.choose_random(test_rng);
-        let expected = Multiset::empty();
-        assert_eq!(result, expected);
+    fn test_chi_squared_distance() {
+        fn reference(a: &[u16], b: &[u16]) -> f64 {
+            let mut res = 0.0;
+            for (x, y) in a.iter().zip(b) {
+                let (x, y) = (*x as f64, *y as f64);
+                if x + y > 0.0 {
+                    res += (x - y) * (x - y) / (x + y);
+                }
+            }
+            res / 2.0
+        }
+
+        let a = Multiset::from([1u8, 3, 0, 0]);
+        let b = Multiset::from([1u8, 1, 2, 0]);
+        assert_eq!(a.chi_squared_distance(&b), 1.5);
+        assert_eq!(b.chi_squared_distance(&a), 1.5);
+        assert_eq!(a.chi_squared_distance(&Multiset::empty()), 2.0);
+        assert_eq!(
+            Multiset::<u8, 4>::empty().chi_squared_distance(&Multiset::empty()),
+            0.0
+        );
+
+        proptest!(|(mut a in counts_up_to::<u16, 37>(1000), mut b in counts_up_to::<u16, 37>(1000))| {
+            prop_assert_eq!(a.chi_squared_distance(&a), 0.0);
+            // Zero whole chunks in both.
+            for elem in 0..16 {
+                a.insert(elem, 0);
+                b.insert(elem, 0);
+            }
+            assert_relative_eq!(
+                a.chi_squared_distance(&b),
+                reference(a.as_slice(), b.as_slice()),
+                epsilon = 1e-12
+            );
+            prop_assert_eq!(a.chi_squared_distance(&a), 0.0);
+        });
     }
 
     #[test]
-    fn test_collision_entropy() {
-        let simple: Multiset<u8, 4> = Multiset::from([200, 0, 0, 0]);
-        assert_eq!(simple.collision_entropy(), 0.0);
+    fn test_gini_impurity() {
+        let singleton = Multiset::from([0u8, 200, 0, 0]);
+        assert_eq!(singleton.gini_impurity(), 0.0);
+        assert_eq!(Multiset::<u8, 4>::empty().gini_impurity(), 0.0);
+        assert_eq!(Multiset::<u8, 0>::empty().gini_impurity(), 0.0);
+
+        for k in 1..=37 {
+            let mut uniform = Multiset::<u16, 37>::empty();
+            (0..k).for_each(|elem| uniform.insert(elem * 7 % 37, 9));
+            assert_relative_eq!(
+                uniform.gini_impurity(),
+                1.0 - 1.0 / k as f64,
+                epsilon = 1e-12
+            );
+        }
 
-        let set: Multiset<u8, 4> = Multiset::from([2, 1, 1, 0]);
+        let set = pseudo_random::<u16, 37>(3, 1000);
         assert_relative_eq!(
-            set.collision_entropy(),
-            1.415037499278844,
-            epsilon = f64::EPSILON
+            set.gini_impurity(),
+            1.0 - 2f64.powf(-set.collision_entropy()),
+            epsilon = 1e-12
         );
     }
 
@@ -2110,6 +7508,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_count_bytes() {
+        let zeros = count_bytes(&[0; 1000]);
+        assert_eq!(zeros[0], 1000);
+        assert_eq!(zeros.total(), 1000);
+        assert_eq!(zeros.shannon_entropy(), 0.0);
+
+        let uniform: Vec<u8> = (0..=255u8).cycle().take(256 * 5 + 3).collect();
+        let set = count_bytes(&uniform[..256 * 5]);
+        assert_eq!(set, Multiset::repeat(5));
+        assert_relative_eq!(set.shannon_entropy(), 256f64.ln(), epsilon = 1e-12);
+
+        let with_remainder = count_bytes(&uniform);
+        assert_eq!(with_remainder.total(), 256 * 5 + 3);
+        assert_eq!(with_remainder[0..3], [6, 6, 6]);
+        assert_eq!(with_remainder[3], 5);
+
+        assert!(count_bytes(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_from_bytes_saturating() {
+        let set = Multiset::<u8, 256>::from_bytes(&[7; 300]);
+        assert_eq!(set[7], 255);
+        assert_eq!(set.count_non_zero(), 1);
+    }
+
     #[test]
     fn test_generic() {
         #[cfg(feature = "simd")]
@@ -2150,4 +7575,154 @@ mod tests {
         let b = Multiset::from([1u8, 1]);
         assert_eq!(generic_union(a, b), Multiset::from([1, 1]));
     }
+
+    // Checks the core set operations and relations of multisets with counts
+    // in `0..=max` against the counts themselves.
+    fn check_set_operations<N: Counter + Arbitrary, const SIZE: usize>(max: N) {
+        proptest!(|(a in counts_up_to::<N, SIZE>(max), b in counts_up_to::<N, SIZE>(max))| {
+            let pairs = || a.iter().zip(b.iter());
+
+            let min: Multiset<N, SIZE> = pairs().map(|(x, y)| *x.min(y)).collect();
+            let max: Multiset<N, SIZE> = pairs().map(|(x, y)| *x.max(y)).collect();
+            prop_assert_eq!(a.intersection(&b), min);
+            prop_assert_eq!(a.union(&b), max);
+            prop_assert_eq!(
+                a.count_non_zero(),
+                a.iter().filter(|x| **x > N::zero()).count()
+            );
+            let total: u128 = a.iter().map(|x| <N as AsPrimitive<u128>>::as_(*x)).sum();
+            prop_assert_eq!(a.total_wide(), total);
+            if let Ok(total) = usize::try_from(total) {
+                prop_assert_eq!(a.total(), total);
+            }
+
+            prop_assert_eq!(a.is_subset(&b), pairs().all(|(x, y)| x <= y));
+            prop_assert_eq!(a.is_superset(&b), pairs().all(|(x, y)| x >= y));
+            prop_assert_eq!(
+                a.is_disjoint(&b),
+                pairs().all(|(x, y)| x.is_zero() || y.is_zero())
+            );
+            prop_assert_eq!(a.is_any_lesser(&b), pairs().any(|(x, y)| x < y));
+            prop_assert_eq!(a.is_any_greater(&b), pairs().any(|(x, y)| x > y));
+            prop_assert!(min.is_subset(&a) && min.is_subset(&b));
+            prop_assert!(max.is_superset(&a) && max.is_superset(&b));
+        });
+    }
+
+    fn check_entropies<N: Counter + Arbitrary, const SIZE: usize>(max: N) {
+        proptest!(|(a in counts_up_to::<N, SIZE>(max))| {
+            let total = a.total_wide() as f64;
+            let probs = a
+                .iter()
+                .map(|x| <N as AsPrimitive<f64>>::as_(*x) / total)
+                .filter(|p| *p > 0.0);
+            let shannon: f64 = -probs.clone().map(|p| p * p.ln()).sum::<f64>();
+            let collision = -probs.map(|p| p * p).sum::<f64>().log2();
+            if a.is_empty() {
+                prop_assert_eq!(a.shannon_entropy(), 0.0);
+                prop_assert_eq!(a.collision_entropy(), 0.0);
+            } else {
+                assert_relative_eq!(a.shannon_entropy(), shannon.max(0.0), epsilon = 1e-12);
+                assert_relative_eq!(a.collision_entropy(), collision.max(0.0), epsilon = 1e-12);
+            }
+        });
+    }
+
+    // Totals of large counts must not wrap in the counter type, whichever
+    // simd width they are summed in.
+    fn check_total_widens<N: Counter, const SIZE: usize>() {
+        let set = Multiset::<N, SIZE>::repeat(N::max_value());
+        let max: u128 = N::max_value().as_();
+        assert_eq!(set.total_wide(), max * SIZE as u128);
+        if let Ok(total) = usize::try_from(max * SIZE as u128) {
+            assert_eq!(set.total(), total);
+        }
+        assert_eq!(set.total_checked().is_none(), SIZE > 1);
+
+        // Everything built on the total must stay exact, or at least not
+        // overflow, at the largest counts.
+        let wide = max * SIZE as u128;
+        let mut smaller = set;
+        smaller.insert(0, N::max_value() / (N::one() + N::one()));
+        assert_eq!(set.count_intersection(&set), wide);
+        assert_eq!(set.count_union(&smaller), wide);
+        assert_eq!(set.count_intersection(&smaller), smaller.total_wide());
+        assert_eq!(set.jaccard_index(&set), 1.0);
+        assert_eq!(set.tversky_index(&set, 0.5, 0.5), 1.0);
+        assert_eq!(smaller.overlap_coefficient(&set), 1.0);
+        assert!(set.jaccard_index(&smaller) < 1.0);
+        assert!(ByTotal(smaller) < ByTotal(set));
+        assert_eq!(ByTotal(set), ByTotal(set));
+        assert!(format!("{:?}", set).contains(&format!("total: {}", wide)));
+    }
+
+    #[cfg(feature = "rand")]
+    fn check_choose_random<N: Counter + Arbitrary, const SIZE: usize>(max: N) {
+        proptest!(|(original in counts_up_to::<N, SIZE>(max), seed in any::<u64>())| {
+            let rng = &mut StdRng::seed_from_u64(seed);
+            let mut set = original;
+            match set.choose_random(rng) {
+                Some(elem) => {
+                    prop_assert!(set.is_singleton() && set.is_subset(&original));
+                    prop_assert_eq!(set[elem], original[elem]);
+                }
+                None => prop_assert!(original.is_empty() && set.is_empty()),
+            }
+        });
+    }
+
+    // Tests generated for each counter type, at sizes smaller than a simd
+    // vector, with a partial final chunk, and of whole chunks, so that every
+    // width of simd kernel is covered.
+    macro_rules! counter_tests {
+        ($($module:ident: $counter:ty,)*) => {$(
+            mod $module {
+                use super::*;
+
+                #[test]
+                fn test_set_operations() {
+                    for &max in &[1, 3, <$counter>::MAX] {
+                        check_set_operations::<$counter, 3>(max);
+                        check_set_operations::<$counter, 37>(max);
+                        check_set_operations::<$counter, 64>(max);
+                    }
+                }
+
+                #[test]
+                fn test_entropies() {
+                    for &max in &[1, 3, <$counter>::MAX] {
+                        check_entropies::<$counter, 3>(max);
+                        check_entropies::<$counter, 37>(max);
+                        check_entropies::<$counter, 64>(max);
+                    }
+                }
+
+                #[test]
+                fn test_total_widens() {
+                    check_total_widens::<$counter, 1>();
+                    check_total_widens::<$counter, 3>();
+                    check_total_widens::<$counter, 37>();
+                    check_total_widens::<$counter, 64>();
+                }
+
+                #[cfg(feature = "rand")]
+                #[test]
+                fn test_choose_random() {
+                    for &max in &[1, 3, <$counter>::MAX] {
+                        check_choose_random::<$counter, 3>(max);
+                        check_choose_random::<$counter, 37>(max);
+                        check_choose_random::<$counter, 64>(max);
+                    }
+                }
+            }
+        )*};
+    }
+
+    counter_tests! {
+        counter_u8: u8,
+        counter_u16: u16,
+        counter_u32: u32,
+        counter_u64: u64,
+        counter_usize: usize,
+    }
 }
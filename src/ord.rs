@@ -0,0 +1,225 @@
+//! Wrappers which order multisets by a single statistic.
+//!
+//! `Multiset` only has a partial order, by inclusion, so it cannot be used
+//! directly in ordered collections such as [`BinaryHeap`]. The wrappers here
+//! order multisets by their total or their entropy instead. Two wrapped
+//! multisets compare equal when the statistic is equal, even if their counts
+//! differ.
+//!
+//! `BinaryHeap` is a max-heap, so wrap the values in [`Reverse`] to pop the
+//! smallest first.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::cmp::Reverse;
+//! use std::collections::BinaryHeap;
+//! use utote::ord::ByEntropy;
+//! use utote::Multiset;
+//!
+//! let mut heap = BinaryHeap::new();
+//! heap.push(Reverse(ByEntropy::new(Multiset::from([1u8, 1, 1, 1]))));
+//! heap.push(Reverse(ByEntropy::new(Multiset::from([0u8, 3, 1, 0]))));
+//! heap.push(Reverse(ByEntropy::new(Multiset::from([0u8, 4, 0, 0]))));
+//!
+//! let Reverse(lowest) = heap.pop().unwrap();
+//! assert_eq!(lowest.get(), &Multiset::from([0, 4, 0, 0]));
+//! ```
+//!
+//! [`BinaryHeap`]: std::collections::BinaryHeap
+//! [`Reverse`]: std::cmp::Reverse
+
+use crate::{Counter, Multiset};
+use std::cmp::Ordering;
+
+/// Orders a multiset by its [`total`](crate::Multiset::total_wide).
+///
+/// The total is computed on every comparison, so the wrapped multiset can be
+/// changed freely. It is summed in `u128`, so wide counters cannot overflow.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BinaryHeap;
+/// use utote::ord::ByTotal;
+/// use utote::Multiset;
+///
+/// let mut heap = BinaryHeap::new();
+/// heap.push(ByTotal(Multiset::from([1u8, 2, 0])));
+/// heap.push(ByTotal(Multiset::from([5u8, 0, 0])));
+/// heap.push(ByTotal(Multiset::from([0u8, 0, 4])));
+///
+/// assert_eq!(heap.pop().unwrap().0, Multiset::from([5, 0, 0]));
+/// assert_eq!(heap.pop().unwrap().0, Multiset::from([0, 0, 4]));
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ByTotal<T>(pub T);
+
+impl<N: Counter, const SIZE: usize> PartialEq for ByTotal<Multiset<N, SIZE>> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.0.total_wide() == other.0.total_wide()
+    }
+}
+
+impl<N: Counter, const SIZE: usize> Eq for ByTotal<Multiset<N, SIZE>> {}
+
+impl<N: Counter, const SIZE: usize> PartialOrd for ByTotal<Multiset<N, SIZE>> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N: Counter, const SIZE: usize> Ord for ByTotal<Multiset<N, SIZE>> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_wide().cmp(&other.0.total_wide())
+    }
+}
+
+/// Orders a multiset by its
+/// [`shannon_entropy`](crate::Multiset::shannon_entropy).
+///
+/// The entropy is computed once, when the wrapper is created, so the wrapped
+/// multiset can only be borrowed immutably. Entropies are compared with
+/// [`f64::total_cmp`], which gives a total order.
+#[derive(Debug, Clone, Copy)]
+pub struct ByEntropy<T> {
+    pub(crate) entropy: f64,
+    pub(crate) inner: T,
+}
+
+#[cfg(not(feature = "simd"))]
+impl<N: Counter, const SIZE: usize> ByEntropy<Multiset<N, SIZE>> {
+    /// Wraps `multiset`, computing its entropy.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utote::ord::ByEntropy;
+    /// use utote::Multiset;
+    ///
+    /// let multiset = Multiset::from([2u8, 2, 0]);
+    /// let wrapped = ByEntropy::new(multiset);
+    /// assert_eq!(wrapped.entropy(), multiset.shannon_entropy());
+    /// ```
+    #[inline]
+    pub fn new(multiset: Multiset<N, SIZE>) -> Self {
+        ByEntropy {
+            entropy: multiset.shannon_entropy(),
+            inner: multiset,
+        }
+    }
+}
+
+impl<T> ByEntropy<T> {
+    /// Returns the entropy computed when the wrapper was created.
+    #[inline]
+    pub fn entropy(&self) -> f64 {
+        self.entropy
+    }
+
+    /// Returns a reference to the wrapped value.
+    #[inline]
+    pub fn get(&self) -> &T {
+        &self.inner
+    }
+
+    /// Unwraps the value.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> PartialEq for ByEntropy<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for ByEntropy<T> {}
+
+impl<T> PartialOrd for ByEntropy<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for ByEntropy<T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.entropy.total_cmp(&other.entropy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap;
+
+    #[test]
+    fn test_by_total_heap() {
+        let sets = [
+            Multiset::from([1u16, 2, 3, 0]),
+            Multiset::from([0u16, 0, 0, 0]),
+            Multiset::from([10u16, 0, 0, 0]),
+            Multiset::from([2u16, 2, 2, 2]),
+            Multiset::from([0u16, 0, 1, 0]),
+        ];
+
+        let mut heap: BinaryHeap<_> = sets.iter().copied().map(ByTotal).collect();
+        let totals: Vec<usize> = std::iter::from_fn(|| heap.pop())
+            .map(|ByTotal(set)| set.total())
+            .collect();
+        assert_eq!(totals, vec![10, 8, 6, 1, 0]);
+
+        let mut heap: BinaryHeap<_> = sets.iter().copied().map(|s| Reverse(ByTotal(s))).collect();
+        assert_eq!(heap.pop().unwrap().0 .0, Multiset::empty());
+        assert_eq!(heap.pop().unwrap().0 .0, Multiset::from([0, 0, 1, 0]));
+
+        assert_eq!(ByTotal(sets[0]), ByTotal(Multiset::from([0, 6, 0, 0])));
+        assert!(ByTotal(sets[1]) < ByTotal(sets[4]));
+    }
+
+    #[test]
+    fn test_by_entropy_heap() {
+        let sets = [
+            Multiset::from([1u8, 1, 1, 1]),
+            Multiset::from([0u8, 5, 0, 0]),
+            Multiset::from([2u8, 1, 1, 0]),
+            Multiset::from([0u8, 0, 0, 0]),
+            Multiset::from([3u8, 3, 0, 0]),
+        ];
+
+        let mut heap: BinaryHeap<_> = sets
+            .iter()
+            .copied()
+            .map(|s| Reverse(ByEntropy::new(s)))
+            .collect();
+        let mut popped = Vec::new();
+        while let Some(Reverse(wrapped)) = heap.pop() {
+            assert_eq!(wrapped.entropy(), wrapped.get().shannon_entropy());
+            popped.push(wrapped.into_inner());
+        }
+        // The empty and singleton multisets both have zero entropy, so either
+        // may come first.
+        assert!(popped[..2].contains(&sets[1]));
+        assert!(popped[..2].contains(&sets[3]));
+        assert_eq!(popped[2..], [sets[4], sets[2], sets[0]]);
+    }
+
+    #[test]
+    fn test_by_entropy_total_order() {
+        let low = ByEntropy::new(Multiset::from([9u32, 1]));
+        let high = ByEntropy::new(Multiset::from([5u32, 5]));
+        assert!(low < high);
+        assert_eq!(low.cmp(&high), Ordering::Less);
+        assert_eq!(high, ByEntropy::new(Multiset::from([7u32, 7])));
+        assert_eq!(low.max(high).entropy(), 2f64.ln());
+    }
+}
@@ -0,0 +1,185 @@
+use crate::{Counter, Multiset, MultisetError};
+use num_traits::AsPrimitive;
+use rand::distributions::{Distribution, WeightedError, WeightedIndex};
+use rand::Rng;
+use std::convert::TryFrom;
+
+/// A [`Distribution`] over the elements of a [`Multiset`], each with
+/// probability proportional to its count.
+///
+/// The cumulative counts are computed once, when the distribution is created,
+/// so drawing many samples is faster than calling [`Multiset::sample`] for
+/// each.
+///
+/// # Examples
+///
+/// ```
+/// use rand::prelude::*;
+/// use utote::{Multiset, MultisetDistribution};
+///
+/// let rng = &mut StdRng::seed_from_u64(thread_rng().next_u64());
+/// let multiset = Multiset::from([2u8, 0, 5, 3]);
+/// let dist = MultisetDistribution::new(&multiset).unwrap();
+/// let elems: Vec<usize> = rng.sample_iter(&dist).take(10).collect();
+/// assert!(elems.iter().all(|elem| multiset[*elem] > 0));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MultisetDistribution<'a, N: Counter, const SIZE: usize> {
+    multiset: &'a Multiset<N, SIZE>,
+    cumulative: Vec<u128>,
+}
+
+impl<'a, N: Counter, const SIZE: usize> MultisetDistribution<'a, N, SIZE> {
+    /// Creates the distribution of the elements of `multiset`.
+    ///
+    /// Returns [`MultisetError::Empty`] if the multiset is empty, as there is
+    /// nothing to sample.
+    pub fn new(multiset: &'a Multiset<N, SIZE>) -> Result<Self, MultisetError> {
+        let cumulative: Vec<u128> = multiset
+            .iter()
+            .scan(0, |acc, count| {
+                *acc += <N as AsPrimitive<u128>>::as_(*count);
+                Some(*acc)
+            })
+            .collect();
+        if cumulative.last().copied().unwrap_or(0) == 0 {
+            return Err(MultisetError::Empty);
+        }
+        Ok(MultisetDistribution {
+            multiset,
+            cumulative,
+        })
+    }
+
+    /// Returns the multiset the distribution was created from.
+    pub fn multiset(&self) -> &'a Multiset<N, SIZE> {
+        self.multiset
+    }
+}
+
+impl<'a, N: Counter, const SIZE: usize> Distribution<usize> for MultisetDistribution<'a, N, SIZE> {
+    // Draws the same element as `Multiset::sample` would from the same rng.
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> usize {
+        // `new` checks that the total is not zero.
+        let total = self.cumulative[SIZE - 1];
+        let choice_value = rng.gen_range(1..=total);
+        self.cumulative.partition_point(|acc| *acc < choice_value)
+    }
+}
+
+/// Creates a [`WeightedIndex`] with the counts of the multiset as weights.
+///
+/// The weights are widened to `u128`, so counts whose total does not fit in
+/// the counter type are still weighted correctly.
+///
+/// Fails with [`WeightedError::AllWeightsZero`] for an empty multiset, and
+/// with [`WeightedError::NoItem`] if `SIZE` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use rand::distributions::WeightedIndex;
+/// use rand::prelude::*;
+/// use std::convert::TryFrom;
+/// use utote::Multiset;
+///
+/// let rng = &mut StdRng::seed_from_u64(thread_rng().next_u64());
+/// let multiset = Multiset::from([2u8, 0, 5, 3]);
+/// let dist = WeightedIndex::try_from(&multiset).unwrap();
+/// assert_ne!(rng.sample(&dist), 1);
+/// ```
+impl<'a, N: Counter, const SIZE: usize> TryFrom<&'a Multiset<N, SIZE>> for WeightedIndex<u128> {
+    type Error = WeightedError;
+
+    fn try_from(multiset: &'a Multiset<N, SIZE>) -> Result<Self, Self::Error> {
+        WeightedIndex::new(
+            multiset
+                .iter()
+                .map(|count| <N as AsPrimitive<u128>>::as_(*count)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_multiset_distribution() {
+        let multiset = Multiset::from([1u16, 0, 3, 6]);
+        let dist = MultisetDistribution::new(&multiset).unwrap();
+        assert_eq!(dist.multiset(), &multiset);
+
+        let draws = 100_000;
+        let mut sampled = [0usize; 4];
+        for elem in StdRng::seed_from_u64(41).sample_iter(&dist).take(draws) {
+            sampled[elem] += 1;
+        }
+        for (count, expected) in sampled.iter().zip(&[0.1, 0.0, 0.3, 0.6]) {
+            assert_relative_eq!(*count as f64 / draws as f64, expected, epsilon = 0.01);
+        }
+
+        let rng_dist = &mut StdRng::seed_from_u64(5);
+        let rng_sample = &mut StdRng::seed_from_u64(5);
+        for _ in 0..1000 {
+            assert_eq!(Some(dist.sample(rng_dist)), multiset.sample(rng_sample));
+        }
+    }
+
+    #[test]
+    fn test_multiset_distribution_empty() {
+        let empty = Multiset::<u8, 4>::empty();
+        assert_eq!(
+            MultisetDistribution::new(&empty).unwrap_err(),
+            MultisetError::Empty
+        );
+        let empty = Multiset::<u8, 0>::empty();
+        assert_eq!(
+            MultisetDistribution::new(&empty).unwrap_err(),
+            MultisetError::Empty
+        );
+    }
+
+    #[test]
+    fn test_weighted_index() {
+        let multiset = Multiset::from([1u32, 0, 3, 6]);
+        let dist = WeightedIndex::try_from(&multiset).unwrap();
+        let draws = 100_000;
+        let mut sampled = [0usize; 4];
+        for elem in StdRng::seed_from_u64(43).sample_iter(&dist).take(draws) {
+            sampled[elem] += 1;
+        }
+        for (count, expected) in sampled.iter().zip(&[0.1, 0.0, 0.3, 0.6]) {
+            assert_relative_eq!(*count as f64 / draws as f64, expected, epsilon = 0.01);
+        }
+
+        assert_eq!(
+            WeightedIndex::try_from(&Multiset::<u8, 4>::empty()).unwrap_err(),
+            WeightedError::AllWeightsZero
+        );
+        assert_eq!(
+            WeightedIndex::try_from(&Multiset::<u8, 0>::empty()).unwrap_err(),
+            WeightedError::NoItem
+        );
+    }
+
+    #[test]
+    fn test_weighted_index_total_past_counter() {
+        let multiset = Multiset::from([200u8, 100, 0]);
+        let dist = WeightedIndex::try_from(&multiset).unwrap();
+        let draws = 30_000;
+        let mut sampled = [0usize; 3];
+        for elem in StdRng::seed_from_u64(47).sample_iter(&dist).take(draws) {
+            sampled[elem] += 1;
+        }
+        for (count, expected) in sampled.iter().zip(&[2.0 / 3.0, 1.0 / 3.0, 0.0]) {
+            assert_relative_eq!(*count as f64 / draws as f64, expected, epsilon = 0.01);
+        }
+
+        let wide = Multiset::<u64, 3>::repeat(u64::MAX);
+        assert!(WeightedIndex::try_from(&wide).is_ok());
+    }
+}
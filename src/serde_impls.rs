@@ -0,0 +1,242 @@
+use crate::{Counter, Multiset};
+use serde::de::{Deserialize, Deserializer, Error, IgnoredAny, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeTuple, Serializer};
+use std::fmt::{self, Formatter};
+use std::marker::PhantomData;
+
+// A Multiset is serialized as a tuple of exactly SIZE counts, which formats
+// such as bincode encode without a length prefix.
+impl<N: Counter + Serialize, const SIZE: usize> Serialize for Multiset<N, SIZE> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut tuple = serializer.serialize_tuple(SIZE)?;
+        for count in self.iter() {
+            tuple.serialize_element(count)?;
+        }
+        tuple.end()
+    }
+}
+
+struct MultisetVisitor<N, const SIZE: usize>(PhantomData<N>);
+
+impl<'de, N, const SIZE: usize> Visitor<'de> for MultisetVisitor<N, SIZE>
+where
+    N: Counter + Deserialize<'de>,
+{
+    type Value = Multiset<N, SIZE>;
+
+    fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "a sequence of {} counts", SIZE)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut res = Multiset::empty();
+        for (i, count) in res.iter_mut().enumerate() {
+            *count = seq
+                .next_element()?
+                .ok_or_else(|| A::Error::invalid_length(i, &self))?;
+        }
+        // Self-describing formats may hold more counts than the multiset.
+        if seq.next_element::<IgnoredAny>()?.is_some() {
+            let mut len = SIZE + 1;
+            while seq.next_element::<IgnoredAny>()?.is_some() {
+                len += 1;
+            }
+            return Err(A::Error::invalid_length(len, &self));
+        }
+        Ok(res)
+    }
+}
+
+impl<'de, N, const SIZE: usize> Deserialize<'de> for Multiset<N, SIZE>
+where
+    N: Counter + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_tuple(SIZE, MultisetVisitor(PhantomData))
+    }
+}
+
+/// Serializes a [`Multiset`] as a map from element to count, skipping zero
+/// counts.
+///
+/// This is much more compact than the default representation for large
+/// multisets with few non-zero counts. Use it with
+/// `#[serde(with = "utote::serde_sparse")]` on a `Multiset` field.
+/// Deserializing fails on elements outside the multiset and on elements given
+/// more than once.
+///
+/// # Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use utote::Multiset;
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// struct Histogram {
+///     #[serde(with = "utote::serde_sparse")]
+///     counts: Multiset<u32, 4096>,
+/// }
+///
+/// let mut counts = Multiset::empty();
+/// counts.insert(7, 2);
+/// counts.insert(4000, 1);
+/// let histogram = Histogram { counts };
+///
+/// let json = serde_json::to_string(&histogram).unwrap();
+/// assert_eq!(json, r#"{"counts":{"7":2,"4000":1}}"#);
+/// assert_eq!(serde_json::from_str::<Histogram>(&json).unwrap(), histogram);
+/// ```
+pub mod serde_sparse {
+    use crate::{Counter, Multiset, MultisetError};
+    use serde::de::{Deserialize, Deserializer, Error, MapAccess, Visitor};
+    use serde::ser::{Serialize, SerializeMap, Serializer};
+    use std::fmt::{self, Formatter};
+    use std::marker::PhantomData;
+
+    /// Serializes the non-zero counts of `multiset` as a map from element to
+    /// count, in element order.
+    pub fn serialize<S, N, const SIZE: usize>(
+        multiset: &Multiset<N, SIZE>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        N: Counter + Serialize,
+    {
+        let len = multiset.iter().filter(|count| **count > N::zero()).count();
+        let mut map = serializer.serialize_map(Some(len))?;
+        for (elem, count) in multiset.iter().enumerate() {
+            if *count > N::zero() {
+                map.serialize_entry(&elem, count)?;
+            }
+        }
+        map.end()
+    }
+
+    /// Deserializes a multiset from a map from element to count. Elements
+    /// which are not in the map have a count of zero.
+    pub fn deserialize<'de, D, N, const SIZE: usize>(
+        deserializer: D,
+    ) -> Result<Multiset<N, SIZE>, D::Error>
+    where
+        D: Deserializer<'de>,
+        N: Counter + Deserialize<'de>,
+    {
+        deserializer.deserialize_map(SparseVisitor(PhantomData))
+    }
+
+    struct SparseVisitor<N, const SIZE: usize>(PhantomData<N>);
+
+    impl<'de, N, const SIZE: usize> Visitor<'de> for SparseVisitor<N, SIZE>
+    where
+        N: Counter + Deserialize<'de>,
+    {
+        type Value = Multiset<N, SIZE>;
+
+        fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+            write!(formatter, "a map of elements less than {} to counts", SIZE)
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut res = Multiset::empty();
+            let mut seen = vec![false; SIZE];
+            while let Some((elem, count)) = map.next_entry::<usize, N>()? {
+                if elem >= SIZE {
+                    return Err(A::Error::custom(MultisetError::ElementOutOfBounds {
+                        elem,
+                        size: SIZE,
+                    }));
+                }
+                if seen[elem] {
+                    return Err(A::Error::custom(MultisetError::DuplicateElement { elem }));
+                }
+                seen[elem] = true;
+                res.data[elem] = count;
+            }
+            Ok(res)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_round_trip() {
+        let multiset = Multiset::from([0u16, 3, 0, 700, 1]);
+        let json = serde_json::to_string(&multiset).unwrap();
+        assert_eq!(json, "[0,3,0,700,1]");
+        let parsed: Multiset<u16, 5> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, multiset);
+
+        let empty = Multiset::<u8, 0>::empty();
+        let json = serde_json::to_string(&empty).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Multiset<u8, 0>>(&json).unwrap(),
+            empty
+        );
+    }
+
+    #[test]
+    fn test_json_wrong_length() {
+        let short = serde_json::from_str::<Multiset<u16, 5>>("[0,3,0,700]").unwrap_err();
+        assert!(short.to_string().contains("invalid length 4"), "{}", short);
+        let long = serde_json::from_str::<Multiset<u16, 5>>("[0,3,0,700,1,2,3]").unwrap_err();
+        assert!(long.to_string().contains("invalid length 7"), "{}", long);
+        assert!(serde_json::from_str::<Multiset<u8, 2>>("[1,256]").is_err());
+    }
+
+    #[test]
+    fn test_bincode_round_trip() {
+        let multiset = Multiset::from([0u32, 3, 0, 70000, 1]);
+        let bytes = bincode::serialize(&multiset).unwrap();
+        // No length prefix, only the counts.
+        assert_eq!(bytes.len(), 5 * 4);
+        let parsed: Multiset<u32, 5> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(parsed, multiset);
+
+        assert!(bincode::deserialize::<Multiset<u32, 5>>(&bytes[..16]).is_err());
+    }
+
+    #[derive(serde::Serialize, serde::Deserialize, PartialEq, Debug)]
+    struct Sparse {
+        #[serde(with = "crate::serde_sparse")]
+        counts: Multiset<u32, 4096>,
+    }
+
+    #[test]
+    fn test_sparse_round_trip() {
+        let mut counts = Multiset::empty();
+        counts.insert(0, 5);
+        counts.insert(1234, 1);
+        counts.insert(4095, 70000);
+        let sparse = Sparse { counts };
+
+        let json = serde_json::to_string(&sparse).unwrap();
+        assert_eq!(json, r#"{"counts":{"0":5,"1234":1,"4095":70000}}"#);
+        assert_eq!(serde_json::from_str::<Sparse>(&json).unwrap(), sparse);
+
+        let bytes = bincode::serialize(&sparse).unwrap();
+        assert_eq!(bincode::deserialize::<Sparse>(&bytes).unwrap(), sparse);
+
+        let empty = Sparse {
+            counts: Multiset::empty(),
+        };
+        assert_eq!(serde_json::to_string(&empty).unwrap(), r#"{"counts":{}}"#);
+    }
+
+    #[test]
+    fn test_sparse_invalid() {
+        let out_of_bounds = serde_json::from_str::<Sparse>(r#"{"counts":{"4096":1}}"#);
+        assert!(out_of_bounds
+            .unwrap_err()
+            .to_string()
+            .contains("element: 4096 not in Multiset"));
+        let duplicate = serde_json::from_str::<Sparse>(r#"{"counts":{"3":1,"3":2}}"#);
+        assert!(duplicate
+            .unwrap_err()
+            .to_string()
+            .contains("element: 3 given more than once"));
+    }
+}
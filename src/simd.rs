@@ -1,14 +1,31 @@
+#[cfg(any(
+    target_arch = "x86",
+    target_arch = "x86_64",
+    target_arch = "aarch64",
+    all(target_arch = "wasm32", target_feature = "simd128")
+))]
 use crate::chunks::ChunkUtils;
+use crate::multiset::compensated_sum;
+use crate::ord::ByEntropy;
 use crate::{Counter, Multiset};
-use num_traits::AsPrimitive;
+use num_traits::{AsPrimitive, Bounded, CheckedAdd, SaturatingAdd, SaturatingSub};
 use packed_simd::*;
 use paste::paste;
 #[cfg(feature = "rand")]
-use rand::{Rng, RngCore};
+use rand::Rng;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
 use std::fmt::Debug;
+#[cfg(any(
+    target_arch = "x86",
+    target_arch = "x86_64",
+    target_arch = "aarch64",
+    all(target_arch = "wasm32", target_feature = "simd128")
+))]
 use std::mem::MaybeUninit;
-use std::ops::{Add, Div, Mul};
-use std::cmp::Ordering;
+use std::ops::{Add, BitAnd, BitAndAssign, BitOr, BitOrAssign, Div, Mul, Sub};
+#[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64"))]
+use std::sync::OnceLock;
 
 mod sealed {
     use packed_simd::*;
@@ -105,7 +122,7 @@ impl_simd_bool!(f64, f64x4, m64x4);
 
 #[doc(hidden)]
 pub trait SimdBasic<N>:
-    sealed::Sealed + Copy + PartialEq + Add<Self, Output = Self> + Debug
+    sealed::Sealed + Copy + PartialEq + Add<Self, Output = Self> + Sub<Self, Output = Self> + Debug
 {
     const LANES: usize;
     type SIMDBool: SimdBool<N, Select = Self>;
@@ -194,6 +211,7 @@ where
         + Copy
         + Add<Self, Output = Self>
         + Mul<Self, Output = Self>
+        + Div<Self, Output = Self>
         + Div<f64, Output = Self>,
 {
     type SIMDBool: SimdBool<N, Select = Self>;
@@ -201,6 +219,7 @@ where
     fn is_nan(self) -> Self::SIMDBool;
     fn ln(self) -> Self;
     fn powf(self, other: Self) -> Self;
+    fn sqrt(self) -> Self;
     fn sum(self) -> N;
 }
 
@@ -224,274 +243,1421 @@ impl SimdFloat<f64> for f64x4 {
         Self::powf(self, other)
     }
 
+    //noinspection RsUnresolvedReference
+    #[inline]
+    fn sqrt(self) -> Self {
+        Self::sqrt(self)
+    }
+
     #[inline]
     fn sum(self) -> f64 {
         Self::sum(self)
     }
 }
 
-macro_rules! intersection_simd {
-    ($name:ident, $simd:ty, $lanes:expr) => {
-        #[doc(hidden)]
-        #[inline]
-        unsafe fn $name(&self, other: &Self) -> Self {
-            let mut data = std::mem::MaybeUninit::<[N; SIZE]>::uninit().assume_init();
-            self.data
-                .zip_map_chunks::<_, $lanes>(&other.data, &mut data, |a, b, out| {
-                    let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
-                    let simd_b = <$simd>::from_slice_unaligned_unchecked(b);
-                    simd_a.min(simd_b).write_to_slice_unaligned_unchecked(out);
-                });
-            Multiset { data }
-        }
-    };
+// Adds up the lanes of a Kahan sum, and the compensations still to be
+// subtracted from them.
+#[cfg(any(
+    target_arch = "x86",
+    target_arch = "x86_64",
+    target_arch = "aarch64",
+    all(target_arch = "wasm32", target_feature = "simd128")
+))]
+#[inline]
+unsafe fn sum_lanes<S: SimdBasic<f64>, const LANES: usize>(sum: S, compensation: S) -> f64 {
+    let mut sums = MaybeUninit::<[f64; LANES]>::uninit().assume_init();
+    let mut compensations = MaybeUninit::<[f64; LANES]>::uninit().assume_init();
+    sum.write_to_slice_unaligned_unchecked(&mut sums);
+    compensation.write_to_slice_unaligned_unchecked(&mut compensations);
+    compensated_sum(sums.iter().copied().chain(compensations.iter().map(|c| -c)))
 }
 
-macro_rules! union_simd {
-    ($name:ident, $simd:ty, $lanes:expr) => {
-        #[doc(hidden)]
-        #[inline]
-        unsafe fn $name(&self, other: &Self) -> Self {
-            let mut data = std::mem::MaybeUninit::<[N; SIZE]>::uninit().assume_init();
-            self.data
-                .zip_map_chunks::<_, $lanes>(&other.data, &mut data, |a, b, out| {
-                    let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
-                    let simd_b = <$simd>::from_slice_unaligned_unchecked(b);
-                    simd_a.max(simd_b).write_to_slice_unaligned_unchecked(out);
-                });
-            Multiset { data }
-        }
-    };
-}
+// The kernels, and the chunk and lane helpers they use, are only built on
+// targets with a simd tier. Other targets dispatch straight to the default
+// kernels.
+#[cfg(any(
+    target_arch = "x86",
+    target_arch = "x86_64",
+    target_arch = "aarch64",
+    all(target_arch = "wasm32", target_feature = "simd128")
+))]
+#[macro_use]
+mod kernels {
+    macro_rules! intersection_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self, other: &Self) -> Self {
+                let mut data = std::mem::MaybeUninit::<[N; SIZE]>::uninit().assume_init();
+                self.data
+                    .zip_map_chunks::<_, $lanes>(&other.data, &mut data, |a, b, out| {
+                        let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
+                        let simd_b = <$simd>::from_slice_unaligned_unchecked(b);
+                        simd_a.min(simd_b).write_to_slice_unaligned_unchecked(out);
+                    });
+                Multiset { data }
+            }
+        };
+    }
 
-macro_rules! count_non_zero_simd {
-    ($name:ident, $simd:ty, $lanes:expr) => {
-        #[doc(hidden)]
-        #[inline]
-        unsafe fn $name(&self) -> usize {
-            self.data.fold_chunks::<_, _, $lanes>(0, |acc, slice| {
-                let vec = <$simd>::from_slice_unaligned_unchecked(slice);
-                acc + vec.gt(<$simd>::splat(N::zero())).count_true()
-            })
-        }
-    };
-}
+    macro_rules! union_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self, other: &Self) -> Self {
+                let mut data = std::mem::MaybeUninit::<[N; SIZE]>::uninit().assume_init();
+                self.data
+                    .zip_map_chunks::<_, $lanes>(&other.data, &mut data, |a, b, out| {
+                        let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
+                        let simd_b = <$simd>::from_slice_unaligned_unchecked(b);
+                        simd_a.max(simd_b).write_to_slice_unaligned_unchecked(out);
+                    });
+                Multiset { data }
+            }
+        };
+    }
 
-macro_rules! is_disjoint_simd {
-    ($name:ident, $simd:ty, $lanes:expr) => {
-        #[doc(hidden)]
-        #[inline]
-        unsafe fn $name(&self, other: &Self) -> bool {
-            self.data.zip_all_chunks::<_, $lanes>(&other.data, |a, b| {
-                let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
-                let simd_b = <$simd>::from_slice_unaligned_unchecked(b);
-                simd_a.min(simd_b) == <$simd>::splat(N::zero())
-            })
-        }
-    };
-}
+    macro_rules! difference_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self, other: &Self) -> Self {
+                let mut data = std::mem::MaybeUninit::<[N; SIZE]>::uninit().assume_init();
+                self.data
+                    .zip_map_chunks::<_, $lanes>(&other.data, &mut data, |a, b, out| {
+                        let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
+                        let simd_b = <$simd>::from_slice_unaligned_unchecked(b);
+                        // max(a, b) >= b, so this subtraction saturates at zero.
+                        (simd_a.max(simd_b) - simd_b).write_to_slice_unaligned_unchecked(out);
+                    });
+                Multiset { data }
+            }
+        };
+    }
 
-macro_rules! is_subset_simd {
-    ($name:ident, $simd:ty, $lanes:expr) => {
-        #[doc(hidden)]
-        #[inline]
-        unsafe fn $name(&self, other: &Self) -> bool {
-            self.data.zip_all_chunks::<_, $lanes>(&other.data, |a, b| {
-                let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
-                let simd_b = <$simd>::from_slice_unaligned_unchecked(b);
-                simd_a.le(simd_b).all()
-            })
-        }
-    };
-}
+    macro_rules! symmetric_difference_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self, other: &Self) -> Self {
+                let mut data = std::mem::MaybeUninit::<[N; SIZE]>::uninit().assume_init();
+                self.data
+                    .zip_map_chunks::<_, $lanes>(&other.data, &mut data, |a, b, out| {
+                        let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
+                        let simd_b = <$simd>::from_slice_unaligned_unchecked(b);
+                        (simd_a.max(simd_b) - simd_a.min(simd_b))
+                            .write_to_slice_unaligned_unchecked(out);
+                    });
+                Multiset { data }
+            }
+        };
+    }
 
-macro_rules! is_superset_simd {
-    ($name:ident, $simd:ty, $lanes:expr) => {
-        #[doc(hidden)]
-        #[inline]
-        unsafe fn $name(&self, other: &Self) -> bool {
-            self.data.zip_all_chunks::<_, $lanes>(&other.data, |a, b| {
-                let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
-                let simd_b = <$simd>::from_slice_unaligned_unchecked(b);
-                simd_a.ge(simd_b).all()
-            })
-        }
-    };
-}
+    macro_rules! sum_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self, other: &Self) -> Self {
+                let mut data = std::mem::MaybeUninit::<[N; SIZE]>::uninit().assume_init();
+                let max = <$simd>::splat(N::max_value());
+                self.data
+                    .zip_map_chunks::<_, $lanes>(&other.data, &mut data, |a, b, out| {
+                        let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
+                        let simd_b = <$simd>::from_slice_unaligned_unchecked(b);
+                        // Saturating add: b is limited to the headroom left in a.
+                        (simd_a + simd_b.min(max - simd_a)).write_to_slice_unaligned_unchecked(out);
+                    });
+                Multiset { data }
+            }
+        };
+    }
 
-macro_rules! is_any_lesser_simd {
-    ($name:ident, $simd:ty, $lanes:expr) => {
-        #[doc(hidden)]
-        #[inline]
-        unsafe fn $name(&self, other: &Self) -> bool {
-            self.data.zip_any_chunks::<_, $lanes>(&other.data, |a, b| {
-                let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
-                let simd_b = <$simd>::from_slice_unaligned_unchecked(b);
-                simd_a.lt(simd_b).any()
-            })
-        }
-    };
-}
+    macro_rules! checked_sum_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self, other: &Self) -> Option<Self> {
+                let mut data = std::mem::MaybeUninit::<[N; SIZE]>::uninit().assume_init();
+                let mut overflow = false;
+                self.data
+                    .zip_map_chunks::<_, $lanes>(&other.data, &mut data, |a, b, out| {
+                        let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
+                        let simd_b = <$simd>::from_slice_unaligned_unchecked(b);
+                        let wrapped = simd_a + simd_b;
+                        // An unsigned add has wrapped iff the result is less than
+                        // either operand.
+                        overflow |= wrapped.lt(simd_a).any();
+                        wrapped.write_to_slice_unaligned_unchecked(out);
+                    });
+                if overflow {
+                    None
+                } else {
+                    Some(Multiset { data })
+                }
+            }
+        };
+    }
 
-macro_rules! is_any_greater_simd {
-    ($name:ident, $simd:ty, $lanes:expr) => {
-        #[doc(hidden)]
-        #[inline]
-        unsafe fn $name(&self, other: &Self) -> bool {
-            self.data.zip_any_chunks::<_, $lanes>(&other.data, |a, b| {
-                let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
-                let simd_b = <$simd>::from_slice_unaligned_unchecked(b);
-                simd_a.gt(simd_b).any()
-            })
-        }
-    };
-}
+    macro_rules! intersection_mut_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&mut self, other: &Self) {
+                self.data
+                    .zip_map_chunks_mut::<_, $lanes>(&other.data, |a, b| {
+                        let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
+                        let simd_b = <$simd>::from_slice_unaligned_unchecked(b);
+                        simd_a.min(simd_b).write_to_slice_unaligned_unchecked(a);
+                    });
+            }
+        };
+    }
 
-macro_rules! total_simd {
-    ($name:ident, $simd:ty, $lanes:expr) => {
-        #[doc(hidden)]
-        #[inline]
-        unsafe fn $name(&self) -> usize {
-            if SIZE < <$simd>::LANES {
-                self.iter()
-                    .map(|e| <N as AsPrimitive<usize>>::as_(*e))
-                    .sum()
-            } else {
+    macro_rules! intersection_into_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self, other: &Self, out: &mut Self) {
+                self.data
+                    .zip_map_chunks::<_, $lanes>(&other.data, &mut out.data, |a, b, out| {
+                        let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
+                        let simd_b = <$simd>::from_slice_unaligned_unchecked(b);
+                        simd_a.min(simd_b).write_to_slice_unaligned_unchecked(out);
+                    });
+            }
+        };
+    }
+
+    macro_rules! union_into_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self, other: &Self, out: &mut Self) {
+                self.data
+                    .zip_map_chunks::<_, $lanes>(&other.data, &mut out.data, |a, b, out| {
+                        let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
+                        let simd_b = <$simd>::from_slice_unaligned_unchecked(b);
+                        simd_a.max(simd_b).write_to_slice_unaligned_unchecked(out);
+                    });
+            }
+        };
+    }
+
+    macro_rules! union_mut_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&mut self, other: &Self) {
+                self.data
+                    .zip_map_chunks_mut::<_, $lanes>(&other.data, |a, b| {
+                        let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
+                        let simd_b = <$simd>::from_slice_unaligned_unchecked(b);
+                        simd_a.max(simd_b).write_to_slice_unaligned_unchecked(a);
+                    });
+            }
+        };
+    }
+
+    macro_rules! min_scalar_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self, n: N) -> Self {
+                let mut res = *self;
+                let simd_n = <$simd>::splat(n);
+                res.data
+                    .zip_map_chunks_mut::<_, $lanes>(&self.data, |r, a| {
+                        let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
+                        simd_a.min(simd_n).write_to_slice_unaligned_unchecked(r);
+                    });
+                res
+            }
+        };
+    }
+
+    macro_rules! max_scalar_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self, n: N) -> Self {
+                let mut res = *self;
+                let simd_n = <$simd>::splat(n);
+                res.data
+                    .zip_map_chunks_mut::<_, $lanes>(&self.data, |r, a| {
+                        let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
+                        simd_a.max(simd_n).write_to_slice_unaligned_unchecked(r);
+                    });
+                res
+            }
+        };
+    }
+
+    macro_rules! subtract_support_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self, other: &Self) -> Self {
+                let mut data = std::mem::MaybeUninit::<[N; SIZE]>::uninit().assume_init();
+                let zero = <$simd>::splat(N::zero());
+                self.data
+                    .zip_map_chunks::<_, $lanes>(&other.data, &mut data, |a, b, out| {
+                        let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
+                        let simd_b = <$simd>::from_slice_unaligned_unchecked(b);
+                        // Keep counts where other is zero.
+                        simd_b
+                            .le(zero)
+                            .select(simd_a, zero)
+                            .write_to_slice_unaligned_unchecked(out);
+                    });
+                Multiset { data }
+            }
+        };
+    }
+
+    macro_rules! retain_support_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self, other: &Self) -> Self {
+                let mut data = std::mem::MaybeUninit::<[N; SIZE]>::uninit().assume_init();
+                let zero = <$simd>::splat(N::zero());
+                self.data
+                    .zip_map_chunks::<_, $lanes>(&other.data, &mut data, |a, b, out| {
+                        let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
+                        let simd_b = <$simd>::from_slice_unaligned_unchecked(b);
+                        // Drop counts where other is zero.
+                        simd_b
+                            .le(zero)
+                            .select(zero, simd_a)
+                            .write_to_slice_unaligned_unchecked(out);
+                    });
+                Multiset { data }
+            }
+        };
+    }
+
+    macro_rules! count_intersection_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self, other: &Self) -> u128 {
                 let mut out = [N::zero(); $lanes];
-                let sum_vec =
-                    self.data
-                        .fold_chunks::<_, _, $lanes>(<$simd>::splat(N::zero()), |acc, a| {
-                            let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
-                            acc + simd_a
-                        });
-                sum_vec.write_to_slice_unaligned_unchecked(&mut out);
-                out.iter().map(|e| <N as AsPrimitive<usize>>::as_(*e)).sum()
+                self.data
+                    .zip_fold_chunks::<_, _, $lanes>(&other.data, 0, |acc, a, b| {
+                        let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
+                        let simd_b = <$simd>::from_slice_unaligned_unchecked(b);
+                        simd_a
+                            .min(simd_b)
+                            .write_to_slice_unaligned_unchecked(&mut out);
+                        acc + out
+                            .iter()
+                            .map(|e| <N as AsPrimitive<u128>>::as_(*e))
+                            .sum::<u128>()
+                    })
             }
-        }
-    };
-}
+        };
+    }
 
-macro_rules! collision_entropy_simd {
-    ($name:ident, $simd:ty, $lanes:expr) => {
-        #[doc(hidden)]
-        #[inline]
-        unsafe fn $name(&self) -> f64 {
-            let total: f64 = self.total() as f64;
-            -self
-                .data
-                .fold_chunks::<_, _, $lanes>(<$simd>::splat(0.0), |acc, slice| {
-                    let mut f64_slice = MaybeUninit::<[f64; $lanes]>::uninit().assume_init();
-                    for i in 0..<$simd>::LANES {
-                        *f64_slice.get_unchecked_mut(i) =
-                            <N as AsPrimitive<f64>>::as_(*slice.get_unchecked(i));
-                    }
-                    let data = <$simd>::from_slice_unaligned_unchecked(&f64_slice);
-                    acc + (data / total).powf(<$simd>::splat(2.0))
+    macro_rules! count_union_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self, other: &Self) -> u128 {
+                let mut out = [N::zero(); $lanes];
+                self.data
+                    .zip_fold_chunks::<_, _, $lanes>(&other.data, 0, |acc, a, b| {
+                        let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
+                        let simd_b = <$simd>::from_slice_unaligned_unchecked(b);
+                        simd_a
+                            .max(simd_b)
+                            .write_to_slice_unaligned_unchecked(&mut out);
+                        acc + out
+                            .iter()
+                            .map(|e| <N as AsPrimitive<u128>>::as_(*e))
+                            .sum::<u128>()
+                    })
+            }
+        };
+    }
+
+    macro_rules! count_non_zero_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self) -> usize {
+                self.data.fold_chunks::<_, _, $lanes>(0, |acc, slice| {
+                    let vec = <$simd>::from_slice_unaligned_unchecked(slice);
+                    acc + vec.gt(<$simd>::splat(N::zero())).count_true()
                 })
-                .sum()
-                .log2()
-        }
-    };
-}
+            }
+        };
+    }
 
-macro_rules! shannon_entropy_simd {
-    ($name:ident, $simd:ty, $lanes:expr) => {
-        #[doc(hidden)]
-        #[inline]
-        unsafe fn $name(&self) -> f64 {
-            let total: f64 = self.total() as f64;
-            -self
-                .data
-                .fold_chunks::<_, _, $lanes>(<$simd>::splat(0.0), |acc, slice| {
-                    let mut f64_slice = MaybeUninit::<[f64; $lanes]>::uninit().assume_init();
-                    for i in 0..<$simd>::LANES {
-                        *f64_slice.get_unchecked_mut(i) =
-                            <N as AsPrimitive<f64>>::as_(*slice.get_unchecked(i));
-                    }
-                    let data = <$simd>::from_slice_unaligned_unchecked(&f64_slice);
-                    let prob = data / total;
-                    let prob_log = prob * prob.ln();
-                    acc + prob_log.is_nan().select(<$simd>::splat(0.0), prob_log)
+    macro_rules! count_greater_than_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self, n: N) -> usize {
+                // The final chunk is padded with zeros, which are never counted.
+                let simd_n = <$simd>::splat(n);
+                self.data.fold_chunks::<_, _, $lanes>(0, |acc, slice| {
+                    let vec = <$simd>::from_slice_unaligned_unchecked(slice);
+                    acc + vec.gt(simd_n).count_true()
                 })
-                .sum()
-        }
-    };
-}
+            }
+        };
+    }
 
-macro_rules! simd_dispatch {
-    (simd128 = $simd128:ty, simd256 = $simd256:ty, lanes128 = $lanes128:expr, lanes256 = $lanes256:expr;
-    pub fn $name:ident (&$self_:ty $(, $arg:ident: $typ:ty)*) -> $ret:ty $body:block) => {
-        paste! {
-            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-            #[target_feature(enable = "avx2,fma")]
-            [<$name _simd>]! { [<_ $name _avx2>], $simd256, $lanes256 }
+    macro_rules! is_disjoint_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self, other: &Self) -> bool {
+                self.data.zip_all_chunks::<_, $lanes>(&other.data, |a, b| {
+                    let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
+                    let simd_b = <$simd>::from_slice_unaligned_unchecked(b);
+                    simd_a.min(simd_b) == <$simd>::splat(N::zero())
+                })
+            }
+        };
+    }
 
-            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-            #[target_feature(enable = "avx")]
-            [<$name _simd>]! { [<_ $name _avx>], $simd256, $lanes256 }
+    macro_rules! is_subset_of_sum_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self, b: &Self, c: &Self) -> bool {
+                self.data
+                    .zip3_all_chunks::<_, $lanes>(&b.data, &c.data, |a, b, c| {
+                        let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
+                        let simd_b = <$simd>::from_slice_unaligned_unchecked(b);
+                        let simd_c = <$simd>::from_slice_unaligned_unchecked(c);
+                        // a <= b + c, without overflowing b + c.
+                        (simd_a.max(simd_b) - simd_b).le(simd_c).all()
+                    })
+            }
+        };
+    }
 
-            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-            #[target_feature(enable = "sse4.2")]
-            [<$name _simd>]! { [<_ $name _sse42>], $simd128, $lanes128 }
+    macro_rules! is_subset_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self, other: &Self) -> bool {
+                self.data.zip_all_chunks::<_, $lanes>(&other.data, |a, b| {
+                    let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
+                    let simd_b = <$simd>::from_slice_unaligned_unchecked(b);
+                    simd_a.le(simd_b).all()
+                })
+            }
+        };
+    }
+
+    macro_rules! is_superset_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self, other: &Self) -> bool {
+                self.data.zip_all_chunks::<_, $lanes>(&other.data, |a, b| {
+                    let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
+                    let simd_b = <$simd>::from_slice_unaligned_unchecked(b);
+                    simd_a.ge(simd_b).all()
+                })
+            }
+        };
+    }
+
+    macro_rules! is_any_lesser_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self, other: &Self) -> bool {
+                self.data.zip_any_chunks::<_, $lanes>(&other.data, |a, b| {
+                    let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
+                    let simd_b = <$simd>::from_slice_unaligned_unchecked(b);
+                    simd_a.lt(simd_b).any()
+                })
+            }
+        };
+    }
+
+    macro_rules! is_any_greater_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self, other: &Self) -> bool {
+                self.data.zip_any_chunks::<_, $lanes>(&other.data, |a, b| {
+                    let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
+                    let simd_b = <$simd>::from_slice_unaligned_unchecked(b);
+                    simd_a.gt(simd_b).any()
+                })
+            }
+        };
+    }
+
+    macro_rules! count_max_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self) -> &N {
+                let mut out = [N::zero(); $lanes];
+                // The final chunk is padded with zeros, which cannot be larger
+                // than any count.
+                self.data
+                    .fold_chunks::<_, _, $lanes>(<$simd>::splat(N::zero()), |acc, a| {
+                        acc.max(<$simd>::from_slice_unaligned_unchecked(a))
+                    })
+                    .write_to_slice_unaligned_unchecked(&mut out);
+                let max = out.iter().fold(N::zero(), |acc, e| acc.max(*e));
+                // iter cannot be empty, so it's fine to unwrap
+                self.iter().find(|e| **e == max).unwrap()
+            }
+        };
+    }
+
+    macro_rules! count_min_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self) -> &N {
+                // A padded final chunk would give a minimum of zero, so only whole
+                // chunks are folded and the remainder is checked afterwards.
+                let (chunked, rest) = self.data.split_at(SIZE - SIZE % $lanes);
+                let mut out = [N::max_value(); $lanes];
+                chunked
+                    .fold_chunks::<_, _, $lanes>(<$simd>::splat(N::max_value()), |acc, a| {
+                        acc.min(<$simd>::from_slice_unaligned_unchecked(a))
+                    })
+                    .write_to_slice_unaligned_unchecked(&mut out);
+                let min = out
+                    .iter()
+                    .chain(rest)
+                    .fold(N::max_value(), |acc, e| acc.min(*e));
+                // iter cannot be empty, so it's fine to unwrap
+                self.iter().find(|e| **e == min).unwrap()
+            }
+        };
+    }
 
+    macro_rules! jaccard_index_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
             #[doc(hidden)]
             #[inline]
-            fn [<_ $name _default>](&$self_, $($arg: $typ),*) -> $ret $body
+            unsafe fn $name(&self, other: &Self) -> f64 {
+                let mut min_out = [N::zero(); $lanes];
+                let mut max_out = [N::zero(); $lanes];
+                let (intersection, union) = self.data.zip_fold_chunks::<_, _, $lanes>(
+                    &other.data,
+                    (0u128, 0u128),
+                    |(min_acc, max_acc), a, b| {
+                        let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
+                        let simd_b = <$simd>::from_slice_unaligned_unchecked(b);
+                        simd_a
+                            .min(simd_b)
+                            .write_to_slice_unaligned_unchecked(&mut min_out);
+                        simd_a
+                            .max(simd_b)
+                            .write_to_slice_unaligned_unchecked(&mut max_out);
+                        (
+                            min_acc
+                                + min_out
+                                    .iter()
+                                    .map(|e| <N as AsPrimitive<u128>>::as_(*e))
+                                    .sum::<u128>(),
+                            max_acc
+                                + max_out
+                                    .iter()
+                                    .map(|e| <N as AsPrimitive<u128>>::as_(*e))
+                                    .sum::<u128>(),
+                        )
+                    },
+                );
+                if union == 0 {
+                    1.0
+                } else {
+                    intersection as f64 / union as f64
+                }
+            }
+        };
+    }
 
+    macro_rules! tversky_index_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
             #[doc(hidden)]
             #[inline]
-            pub fn $name(&self, $($arg: $typ),*) -> $ret {
-                unsafe {
-                    if is_x86_feature_detected!("avx2") {
-                        self.[<_ $name _avx2>]($($arg),*)
-                    } else if is_x86_feature_detected!("avx") {
-                        self.[<_ $name _avx>]($($arg),*)
-                    } else if is_x86_feature_detected!("sse4.2") {
-                        self.[<_ $name _sse42>]($($arg),*)
+            unsafe fn $name(&self, other: &Self, alpha: f64, beta: f64) -> f64 {
+                let mut common_out = [N::zero(); $lanes];
+                let mut self_out = [N::zero(); $lanes];
+                let mut other_out = [N::zero(); $lanes];
+                let sum = |out: &[N]| {
+                    out.iter()
+                        .map(|e| <N as AsPrimitive<u128>>::as_(*e))
+                        .sum::<u128>()
+                };
+                let (common, self_only, other_only) = self.data.zip_fold_chunks::<_, _, $lanes>(
+                    &other.data,
+                    (0u128, 0u128, 0u128),
+                    |(common, self_only, other_only), a, b| {
+                        let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
+                        let simd_b = <$simd>::from_slice_unaligned_unchecked(b);
+                        let simd_min = simd_a.min(simd_b);
+                        simd_min.write_to_slice_unaligned_unchecked(&mut common_out);
+                        (simd_a - simd_min).write_to_slice_unaligned_unchecked(&mut self_out);
+                        (simd_b - simd_min).write_to_slice_unaligned_unchecked(&mut other_out);
+                        (
+                            common + sum(&common_out),
+                            self_only + sum(&self_out),
+                            other_only + sum(&other_out),
+                        )
+                    },
+                );
+                if common == 0 {
+                    return if self_only == 0 && other_only == 0 {
+                        1.0
                     } else {
-                        self.[<_ $name _default>]($($arg),*)
-                    }
+                        0.0
+                    };
                 }
+                let common = common as f64;
+                common / (common + alpha * self_only as f64 + beta * other_only as f64)
             }
-        }
-    };
-}
+        };
+    }
 
-// pub trait MultisetSimdFn<N> {
-//     unsafe fn _intersection_simd<S, const C: usize>(&self, other: &Self, out: &mut Self)
-//     where
-//         S: SimdBasic<N>;
-//     unsafe fn _union_simd<S, const C: usize>(&self, other: &Self, out: &mut Self)
-//     where
-//         S: SimdBasic<N>;
-//     unsafe fn _count_non_zero_simd<S, const C: usize>(&self) -> usize
-//     where
-//         S: SimdBasic<N>;
-//     unsafe fn _is_disjoint_simd<S, const C: usize>(&self, other: &Self) -> bool
-//     where
-//         S: SimdBasic<N>;
-//     unsafe fn _is_subset_simd<S, const C: usize>(&self, other: &Self) -> bool
-//     where
-//         S: SimdBasic<N>;
-//     unsafe fn _is_superset_simd<S, const C: usize>(&self, other: &Self) -> bool
-//     where
-//         S: SimdBasic<N>;
-//     unsafe fn _is_any_lesser_simd<S, const C: usize>(&self, other: &Self) -> bool
-//     where
-//         S: SimdBasic<N>;
-//     unsafe fn _is_any_greater_simd<S, const C: usize>(&self, other: &Self) -> bool
-//     where
-//         S: SimdBasic<N>;
-//     unsafe fn _total_simd<S, const C: usize>(&self) -> usize
-//     where
-//         S: SimdBasic<N>;
-//     unsafe fn _collision_entropy_simd<S, F, const SC: usize, const FC: usize>(&self) -> f64
-//     where
-//         S: SimdBasic<N>,
-//         F: SimdBasic<f64> + SimdFloat<f64>;
-//     unsafe fn _shannon_entropy_simd<S, F, const SC: usize, const FC: usize>(&self) -> f64
-//     where
-//         S: SimdBasic<N>,
+    macro_rules! l1_distance_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self, other: &Self) -> u64 {
+                let mut out = [N::zero(); $lanes];
+                self.data
+                    .zip_fold_chunks::<_, _, $lanes>(&other.data, 0u64, |acc, a, b| {
+                        let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
+                        let simd_b = <$simd>::from_slice_unaligned_unchecked(b);
+                        (simd_a.max(simd_b) - simd_a.min(simd_b))
+                            .write_to_slice_unaligned_unchecked(&mut out);
+                        out.iter().fold(acc, |acc, e| {
+                            acc.saturating_add(<N as AsPrimitive<u64>>::as_(*e))
+                        })
+                    })
+            }
+        };
+    }
+
+    macro_rules! l2_distance_squared_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self, other: &Self) -> u64 {
+                let mut out = [N::zero(); $lanes];
+                let res =
+                    self.data
+                        .zip_fold_chunks::<_, _, $lanes>(&other.data, 0u128, |acc, a, b| {
+                            let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
+                            let simd_b = <$simd>::from_slice_unaligned_unchecked(b);
+                            (simd_a.max(simd_b) - simd_a.min(simd_b))
+                                .write_to_slice_unaligned_unchecked(&mut out);
+                            out.iter().fold(acc, |acc, e| {
+                                let diff = <N as AsPrimitive<u128>>::as_(*e);
+                                acc.saturating_add(diff * diff)
+                            })
+                        });
+                u64::try_from(res).unwrap_or(u64::MAX)
+            }
+        };
+    }
+
+    macro_rules! total_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self) -> usize {
+                if SIZE < <$simd>::LANES {
+                    self.iter()
+                        .map(|e| <N as AsPrimitive<usize>>::as_(*e))
+                        .sum()
+                } else {
+                    let mut out = [N::zero(); $lanes];
+                    // The lanes are summed in the counter type, so they are
+                    // widened into the usize total whenever a lane would wrap.
+                    let (total, sum_vec) = self.data.fold_chunks::<_, _, $lanes>(
+                        (0usize, <$simd>::splat(N::zero())),
+                        |(total, acc), a| {
+                            let simd_a = <$simd>::from_slice_unaligned_unchecked(a);
+                            let sum = acc + simd_a;
+                            if sum.lt(acc).any() {
+                                let mut lanes = [N::zero(); $lanes];
+                                acc.write_to_slice_unaligned_unchecked(&mut lanes);
+                                let widened: usize = lanes
+                                    .iter()
+                                    .map(|e| <N as AsPrimitive<usize>>::as_(*e))
+                                    .sum();
+                                (total + widened, simd_a)
+                            } else {
+                                (total, sum)
+                            }
+                        },
+                    );
+                    sum_vec.write_to_slice_unaligned_unchecked(&mut out);
+                    total
+                        + out
+                            .iter()
+                            .map(|e| <N as AsPrimitive<usize>>::as_(*e))
+                            .sum::<usize>()
+                }
+            }
+        };
+    }
+
+    macro_rules! sum_squared_probabilities_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self) -> f64 {
+                let total: f64 = self.total_wide() as f64;
+                let zero = <$simd>::splat(0.0);
+                // Kahan summation in each lane keeps the many tiny terms of large
+                // multisets from being lost.
+                let (sum, compensation) = self.data.fold_chunks::<_, _, $lanes>(
+                    (zero, zero),
+                    |(sum, compensation), slice| {
+                        let mut f64_slice = MaybeUninit::<[f64; $lanes]>::uninit().assume_init();
+                        for i in 0..<$simd>::LANES {
+                            *f64_slice.get_unchecked_mut(i) =
+                                <N as AsPrimitive<f64>>::as_(*slice.get_unchecked(i));
+                        }
+                        let data = <$simd>::from_slice_unaligned_unchecked(&f64_slice);
+                        let term = (data / total).powf(<$simd>::splat(2.0)) - compensation;
+                        let next = sum + term;
+                        (next, (next - sum) - term)
+                    },
+                );
+                sum_lanes::<$simd, $lanes>(sum, compensation)
+            }
+        };
+    }
+
+    macro_rules! shannon_entropy_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self) -> f64 {
+                let total: f64 = self.total_wide() as f64;
+                let zero = <$simd>::splat(0.0);
+                // Kahan summation in each lane keeps the many tiny terms of large
+                // multisets from being lost.
+                let (sum, compensation) = self.data.fold_chunks::<_, _, $lanes>(
+                    (zero, zero),
+                    |(sum, compensation), slice| {
+                        let mut f64_slice = MaybeUninit::<[f64; $lanes]>::uninit().assume_init();
+                        for i in 0..<$simd>::LANES {
+                            *f64_slice.get_unchecked_mut(i) =
+                                <N as AsPrimitive<f64>>::as_(*slice.get_unchecked(i));
+                        }
+                        let data = <$simd>::from_slice_unaligned_unchecked(&f64_slice);
+                        let prob = data / total;
+                        let prob_log = prob * prob.ln();
+                        let term = zero - prob_log.is_nan().select(zero, prob_log) - compensation;
+                        let next = sum + term;
+                        (next, (next - sum) - term)
+                    },
+                );
+                sum_lanes::<$simd, $lanes>(sum, compensation)
+            }
+        };
+    }
+
+    macro_rules! kl_divergence_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self, other: &Self) -> f64 {
+                let total_p: f64 = self.total_wide() as f64;
+                let total_q: f64 = other.total_wide() as f64;
+                if total_p == 0.0 || total_q == 0.0 {
+                    return f64::NAN;
+                }
+                self.data
+                    .zip_fold_chunks::<_, _, $lanes>(
+                        &other.data,
+                        <$simd>::splat(0.0),
+                        |acc, a, b| {
+                            let mut p_slice = MaybeUninit::<[f64; $lanes]>::uninit().assume_init();
+                            let mut q_slice = MaybeUninit::<[f64; $lanes]>::uninit().assume_init();
+                            for i in 0..<$simd>::LANES {
+                                *p_slice.get_unchecked_mut(i) =
+                                    <N as AsPrimitive<f64>>::as_(*a.get_unchecked(i));
+                                *q_slice.get_unchecked_mut(i) =
+                                    <N as AsPrimitive<f64>>::as_(*b.get_unchecked(i));
+                            }
+                            let prob_p =
+                                <$simd>::from_slice_unaligned_unchecked(&p_slice) / total_p;
+                            let prob_q =
+                                <$simd>::from_slice_unaligned_unchecked(&q_slice) / total_q;
+                            // Zero counts in self give NaN, and zero counts in other
+                            // only give infinity.
+                            let term = prob_p * (prob_p.ln() - prob_q.ln());
+                            acc + term.is_nan().select(<$simd>::splat(0.0), term)
+                        },
+                    )
+                    .sum()
+            }
+        };
+    }
+
+    macro_rules! cross_entropy_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self, other: &Self) -> f64 {
+                let total_p: f64 = self.total_wide() as f64;
+                let total_q: f64 = other.total_wide() as f64;
+                if total_p == 0.0 || total_q == 0.0 {
+                    return f64::NAN;
+                }
+                -self
+                    .data
+                    .zip_fold_chunks::<_, _, $lanes>(
+                        &other.data,
+                        <$simd>::splat(0.0),
+                        |acc, a, b| {
+                            let mut p_slice = MaybeUninit::<[f64; $lanes]>::uninit().assume_init();
+                            let mut q_slice = MaybeUninit::<[f64; $lanes]>::uninit().assume_init();
+                            for i in 0..<$simd>::LANES {
+                                *p_slice.get_unchecked_mut(i) =
+                                    <N as AsPrimitive<f64>>::as_(*a.get_unchecked(i));
+                                *q_slice.get_unchecked_mut(i) =
+                                    <N as AsPrimitive<f64>>::as_(*b.get_unchecked(i));
+                            }
+                            let prob_p =
+                                <$simd>::from_slice_unaligned_unchecked(&p_slice) / total_p;
+                            let prob_q =
+                                <$simd>::from_slice_unaligned_unchecked(&q_slice) / total_q;
+                            // Zero counts in self give NaN, and zero counts in other
+                            // only give infinity.
+                            let term = prob_p * prob_q.ln();
+                            acc + term.is_nan().select(<$simd>::splat(0.0), term)
+                        },
+                    )
+                    .sum()
+            }
+        };
+    }
+
+    macro_rules! cosine_similarity_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self, other: &Self) -> f64 {
+                let zero = <$simd>::splat(0.0);
+                let (dot, norm_a, norm_b) = self.data.zip_fold_chunks::<_, _, $lanes>(
+                    &other.data,
+                    (zero, zero, zero),
+                    |(dot, norm_a, norm_b), a, b| {
+                        let mut a_slice = MaybeUninit::<[f64; $lanes]>::uninit().assume_init();
+                        let mut b_slice = MaybeUninit::<[f64; $lanes]>::uninit().assume_init();
+                        for i in 0..<$simd>::LANES {
+                            *a_slice.get_unchecked_mut(i) =
+                                <N as AsPrimitive<f64>>::as_(*a.get_unchecked(i));
+                            *b_slice.get_unchecked_mut(i) =
+                                <N as AsPrimitive<f64>>::as_(*b.get_unchecked(i));
+                        }
+                        let simd_a = <$simd>::from_slice_unaligned_unchecked(&a_slice);
+                        let simd_b = <$simd>::from_slice_unaligned_unchecked(&b_slice);
+                        (
+                            dot + simd_a * simd_b,
+                            norm_a + simd_a * simd_a,
+                            norm_b + simd_b * simd_b,
+                        )
+                    },
+                );
+                let (dot, norm_a, norm_b) = (dot.sum(), norm_a.sum(), norm_b.sum());
+                if norm_a == 0.0 || norm_b == 0.0 {
+                    0.0
+                } else {
+                    dot / (norm_a.sqrt() * norm_b.sqrt())
+                }
+            }
+        };
+    }
+
+    macro_rules! hellinger_distance_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self, other: &Self) -> f64 {
+                let total_p: f64 = self.total_wide() as f64;
+                let total_q: f64 = other.total_wide() as f64;
+                if total_p == 0.0 || total_q == 0.0 {
+                    return if total_p == total_q { 0.0 } else { 1.0 };
+                }
+                let coefficient = self
+                    .data
+                    .zip_fold_chunks::<_, _, $lanes>(
+                        &other.data,
+                        <$simd>::splat(0.0),
+                        |acc, a, b| {
+                            let mut p_slice = MaybeUninit::<[f64; $lanes]>::uninit().assume_init();
+                            let mut q_slice = MaybeUninit::<[f64; $lanes]>::uninit().assume_init();
+                            for i in 0..<$simd>::LANES {
+                                *p_slice.get_unchecked_mut(i) =
+                                    <N as AsPrimitive<f64>>::as_(*a.get_unchecked(i));
+                                *q_slice.get_unchecked_mut(i) =
+                                    <N as AsPrimitive<f64>>::as_(*b.get_unchecked(i));
+                            }
+                            let prob_p =
+                                <$simd>::from_slice_unaligned_unchecked(&p_slice) / total_p;
+                            let prob_q =
+                                <$simd>::from_slice_unaligned_unchecked(&q_slice) / total_q;
+                            acc + (prob_p * prob_q).sqrt()
+                        },
+                    )
+                    .sum();
+                (1.0 - coefficient).max(0.0).sqrt()
+            }
+        };
+    }
+
+    macro_rules! chi_squared_distance_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self, other: &Self) -> f64 {
+                self.data
+                    .zip_fold_chunks::<_, _, $lanes>(
+                        &other.data,
+                        <$simd>::splat(0.0),
+                        |acc, a, b| {
+                            let mut a_slice = MaybeUninit::<[f64; $lanes]>::uninit().assume_init();
+                            let mut b_slice = MaybeUninit::<[f64; $lanes]>::uninit().assume_init();
+                            for i in 0..<$simd>::LANES {
+                                *a_slice.get_unchecked_mut(i) =
+                                    <N as AsPrimitive<f64>>::as_(*a.get_unchecked(i));
+                                *b_slice.get_unchecked_mut(i) =
+                                    <N as AsPrimitive<f64>>::as_(*b.get_unchecked(i));
+                            }
+                            let simd_a = <$simd>::from_slice_unaligned_unchecked(&a_slice);
+                            let simd_b = <$simd>::from_slice_unaligned_unchecked(&b_slice);
+                            let diff = simd_a - simd_b;
+                            // Lanes where both counts are zero give NaN.
+                            let term = diff * diff / (simd_a + simd_b);
+                            acc + term.is_nan().select(<$simd>::splat(0.0), term)
+                        },
+                    )
+                    .sum()
+                    / 2.0
+            }
+        };
+    }
+
+    macro_rules! variance_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self) -> f64 {
+                let mean = self.mean_count();
+                // A padded final chunk would add deviations for the padding, so
+                // only whole chunks are folded and the remainder is added after.
+                let (chunked, rest) = self.data.split_at(SIZE - SIZE % $lanes);
+                let chunked_sum = chunked
+                    .fold_chunks::<_, _, $lanes>(<$simd>::splat(0.0), |acc, slice| {
+                        let mut f64_slice = MaybeUninit::<[f64; $lanes]>::uninit().assume_init();
+                        for i in 0..<$simd>::LANES {
+                            *f64_slice.get_unchecked_mut(i) =
+                                <N as AsPrimitive<f64>>::as_(*slice.get_unchecked(i));
+                        }
+                        let deviation = <$simd>::from_slice_unaligned_unchecked(&f64_slice)
+                            - <$simd>::splat(mean);
+                        acc + deviation * deviation
+                    })
+                    .sum();
+                let rest_sum: f64 = rest
+                    .iter()
+                    .map(|count| {
+                        let deviation = <N as AsPrimitive<f64>>::as_(*count) - mean;
+                        deviation * deviation
+                    })
+                    .sum();
+                (chunked_sum + rest_sum) / SIZE as f64
+            }
+        };
+    }
+
+    macro_rules! normalize_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self) -> [f64; SIZE] {
+                let mut res = [0.0; SIZE];
+                let total: f64 = self.total_wide() as f64;
+                if total == 0.0 {
+                    return res;
+                }
+                let mut chunks = self.data.chunks_exact($lanes);
+                let mut res_chunks = res.chunks_exact_mut($lanes);
+                for (chunk, res_chunk) in (&mut chunks).zip(&mut res_chunks) {
+                    let mut f64_slice = MaybeUninit::<[f64; $lanes]>::uninit().assume_init();
+                    for i in 0..<$simd>::LANES {
+                        *f64_slice.get_unchecked_mut(i) =
+                            <N as AsPrimitive<f64>>::as_(*chunk.get_unchecked(i));
+                    }
+                    (<$simd>::from_slice_unaligned_unchecked(&f64_slice) / total)
+                        .write_to_slice_unaligned_unchecked(res_chunk);
+                }
+                for (prob, count) in res_chunks
+                    .into_remainder()
+                    .iter_mut()
+                    .zip(chunks.remainder())
+                {
+                    *prob = <N as AsPrimitive<f64>>::as_(*count) / total;
+                }
+                res
+            }
+        };
+    }
+
+    macro_rules! expected_value_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self, values: &[f64]) -> f64 {
+                assert_eq!(values.len(), SIZE, "values must have length SIZE");
+                let total: f64 = self.total_wide() as f64;
+                if total == 0.0 {
+                    return 0.0;
+                }
+                let mut chunks = self.data.chunks_exact($lanes);
+                let mut value_chunks = values.chunks_exact($lanes);
+                let mut acc = <$simd>::splat(0.0);
+                for (chunk, value_chunk) in (&mut chunks).zip(&mut value_chunks) {
+                    let mut f64_slice = MaybeUninit::<[f64; $lanes]>::uninit().assume_init();
+                    for i in 0..<$simd>::LANES {
+                        *f64_slice.get_unchecked_mut(i) =
+                            <N as AsPrimitive<f64>>::as_(*chunk.get_unchecked(i));
+                    }
+                    acc = acc
+                        + <$simd>::from_slice_unaligned_unchecked(&f64_slice)
+                            * <$simd>::from_slice_unaligned_unchecked(value_chunk);
+                }
+                let rest: f64 = chunks
+                    .remainder()
+                    .iter()
+                    .zip(value_chunks.remainder())
+                    .map(|(count, value)| <N as AsPrimitive<f64>>::as_(*count) * value)
+                    .sum();
+                (acc.sum() + rest) / total
+            }
+        };
+    }
+
+    macro_rules! dot_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self, other: &Self) -> u64 {
+                // Products of counters of at most 16 bits, and the sum of one
+                // chunk of them, are exact in f64. Wider counters are not.
+                if std::mem::size_of::<N>() > 2 {
+                    return self._dot_default(other);
+                }
+                let res =
+                    self.data
+                        .zip_fold_chunks::<_, _, $lanes>(&other.data, 0u128, |acc, a, b| {
+                            let mut a_slice = MaybeUninit::<[f64; $lanes]>::uninit().assume_init();
+                            let mut b_slice = MaybeUninit::<[f64; $lanes]>::uninit().assume_init();
+                            for i in 0..<$simd>::LANES {
+                                *a_slice.get_unchecked_mut(i) =
+                                    <N as AsPrimitive<f64>>::as_(*a.get_unchecked(i));
+                                *b_slice.get_unchecked_mut(i) =
+                                    <N as AsPrimitive<f64>>::as_(*b.get_unchecked(i));
+                            }
+                            let products = <$simd>::from_slice_unaligned_unchecked(&a_slice)
+                                * <$simd>::from_slice_unaligned_unchecked(&b_slice);
+                            acc + products.sum() as u128
+                        });
+                u64::try_from(res).unwrap_or(u64::MAX)
+            }
+        };
+    }
+
+    macro_rules! moment_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self, k: u32) -> f64 {
+                // Squares and cubes of counters of at most 16 bits, and the sum of
+                // one chunk of them, are exact in f64. Other moments and wider
+                // counters are not.
+                if !(k == 2 || k == 3) || std::mem::size_of::<N>() > 2 {
+                    return self._moment_default(k);
+                }
+                self.data.fold_chunks::<_, _, $lanes>(0u128, |acc, a| {
+                    let mut f64_slice = MaybeUninit::<[f64; $lanes]>::uninit().assume_init();
+                    for i in 0..<$simd>::LANES {
+                        *f64_slice.get_unchecked_mut(i) =
+                            <N as AsPrimitive<f64>>::as_(*a.get_unchecked(i));
+                    }
+                    let counts = <$simd>::from_slice_unaligned_unchecked(&f64_slice);
+                    let powers = if k == 2 {
+                        counts * counts
+                    } else {
+                        counts * counts * counts
+                    };
+                    acc + powers.sum() as u128
+                }) as f64
+            }
+        };
+    }
+
+    macro_rules! weighted_shannon_entropy_simd {
+        ($name:ident, $simd:ty, $lanes:expr) => {
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn $name(&self, weights: &[f64]) -> f64 {
+                assert_eq!(weights.len(), SIZE, "weights must have length SIZE");
+                let zero = <$simd>::splat(0.0);
+                let mut chunks = self.data.chunks_exact($lanes);
+                let mut weight_chunks = weights.chunks_exact($lanes);
+                let (mut total_vec, mut log_vec) = (zero, zero);
+                for (chunk, weight_chunk) in (&mut chunks).zip(&mut weight_chunks) {
+                    let mut f64_slice = MaybeUninit::<[f64; $lanes]>::uninit().assume_init();
+                    for i in 0..<$simd>::LANES {
+                        *f64_slice.get_unchecked_mut(i) =
+                            <N as AsPrimitive<f64>>::as_(*chunk.get_unchecked(i));
+                    }
+                    let counts = <$simd>::from_slice_unaligned_unchecked(&f64_slice);
+                    let weight = <$simd>::from_slice_unaligned_unchecked(weight_chunk);
+                    let weight = counts.gt(zero).select(weight, zero);
+                    let weight = weight.gt(zero).select(weight, zero);
+                    // Masked lanes give 0 * ln(0), which is NaN.
+                    let term = weight * weight.ln();
+                    total_vec = total_vec + weight;
+                    log_vec = log_vec + term.is_nan().select(zero, term);
+                }
+                let (total, weighted_log) = chunks
+                    .remainder()
+                    .iter()
+                    .zip(weight_chunks.remainder())
+                    .filter(|(count, weight)| **count > N::zero() && **weight > 0.0)
+                    .fold(
+                        (total_vec.sum(), log_vec.sum()),
+                        |(total, weighted_log), (_, weight)| {
+                            (total + weight, weighted_log + weight * weight.ln())
+                        },
+                    );
+                if total == 0.0 {
+                    0.0
+                } else {
+                    total.ln() - weighted_log / total
+                }
+            }
+        };
+    }
+}
+
+/// The simd kernels used by dispatched methods, from the widest supported by
+/// the cpu, as returned by [`cpu_feature`].
+///
+/// The feature is detected once, on first use. Variants for other
+/// architectures are never detected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum CPUFeature {
+    /// 256-bit kernels using AVX2 and FMA, on x86 and x86_64.
+    Avx2,
+    /// 256-bit kernels using AVX, on x86 and x86_64.
+    Avx,
+    /// 128-bit kernels using SSE4.2, on x86 and x86_64.
+    Sse42,
+    /// 128-bit kernels using NEON, on aarch64.
+    Neon,
+    /// The scalar kernels.
+    Default,
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64"))]
+impl CPUFeature {
+    fn detect() -> Self {
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        {
+            if is_x86_feature_detected!("avx2") {
+                return CPUFeature::Avx2;
+            } else if is_x86_feature_detected!("avx") {
+                return CPUFeature::Avx;
+            } else if is_x86_feature_detected!("sse4.2") {
+                return CPUFeature::Sse42;
+            }
+        }
+        #[cfg(target_arch = "aarch64")]
+        {
+            if std::arch::is_aarch64_feature_detected!("neon") {
+                return CPUFeature::Neon;
+            }
+        }
+        CPUFeature::Default
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64"))]
+static CPU_FEATURE: OnceLock<CPUFeature> = OnceLock::new();
+
+/// Returns the feature that dispatched methods use, detecting it on the first
+/// call.
+///
+/// Only x86, x86_64 and aarch64 detect features at runtime. Other targets
+/// always give [`CPUFeature::Default`]; on wasm32 the 128-bit kernels are
+/// instead chosen at compile time by the `simd128` target feature.
+///
+/// # Examples
+///
+/// ```
+/// use utote::{cpu_feature, CPUFeature};
+///
+/// if cpu_feature() == CPUFeature::Default {
+///     println!("no simd kernels detected");
+/// }
+/// ```
+#[inline]
+pub fn cpu_feature() -> CPUFeature {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64"))]
+    {
+        *CPU_FEATURE.get_or_init(CPUFeature::detect)
+    }
+    #[cfg(not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")))]
+    {
+        CPUFeature::Default
+    }
+}
+
+macro_rules! simd_dispatch {
+    (simd128 = $simd128:ty, simd256 = $simd256:ty, lanes128 = $lanes128:expr, lanes256 = $lanes256:expr;
+    pub fn $name:ident (&mut $self_:ident $(, $arg:ident: $typ:ty)*) $body:block) => {
+        paste! {
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            #[target_feature(enable = "avx2,fma")]
+            [<$name _simd>]! { [<_ $name _avx2>], $simd256, $lanes256 }
+
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            #[target_feature(enable = "avx")]
+            [<$name _simd>]! { [<_ $name _avx>], $simd256, $lanes256 }
+
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            #[target_feature(enable = "sse4.2")]
+            [<$name _simd>]! { [<_ $name _sse42>], $simd128, $lanes128 }
+
+            #[cfg(target_arch = "aarch64")]
+            #[target_feature(enable = "neon")]
+            [<$name _simd>]! { [<_ $name _neon>], $simd128, $lanes128 }
+
+            #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+            [<$name _simd>]! { [<_ $name _simd128>], $simd128, $lanes128 }
+
+            #[doc(hidden)]
+            #[inline]
+            fn [<_ $name _default>](&mut $self_, $($arg: $typ),*) $body
+
+            /// Runs the kernels for `feature`, or the default kernel if there
+            /// are none for it on this architecture.
+            ///
+            /// # Safety
+            /// The cpu must support `feature`.
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            #[doc(hidden)]
+            #[inline]
+            pub(crate) unsafe fn [<_ $name _with>](&mut self, feature: CPUFeature, $($arg: $typ),*) {
+                match feature {
+                    CPUFeature::Avx2 => self.[<_ $name _avx2>]($($arg),*),
+                    CPUFeature::Avx => self.[<_ $name _avx>]($($arg),*),
+                    CPUFeature::Sse42 => self.[<_ $name _sse42>]($($arg),*),
+                    _ => self.[<_ $name _default>]($($arg),*),
+                }
+            }
+
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            #[doc(hidden)]
+            #[inline]
+            pub fn $name(&mut self, $($arg: $typ),*) {
+                unsafe { self.[<_ $name _with>](cpu_feature(), $($arg),*) }
+            }
+
+            /// Runs the kernels for `feature`, or the default kernel if there
+            /// are none for it on this architecture.
+            ///
+            /// # Safety
+            /// The cpu must support `feature`.
+            #[cfg(target_arch = "aarch64")]
+            #[doc(hidden)]
+            #[inline]
+            pub(crate) unsafe fn [<_ $name _with>](&mut self, feature: CPUFeature, $($arg: $typ),*) {
+                match feature {
+                    CPUFeature::Neon => self.[<_ $name _neon>]($($arg),*),
+                    _ => self.[<_ $name _default>]($($arg),*),
+                }
+            }
+
+            #[cfg(target_arch = "aarch64")]
+            #[doc(hidden)]
+            #[inline]
+            pub fn $name(&mut self, $($arg: $typ),*) {
+                unsafe { self.[<_ $name _with>](cpu_feature(), $($arg),*) }
+            }
+
+            // WASM has no runtime feature detection, simd128 is either enabled
+            // at compile time or not at all.
+            #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+            #[doc(hidden)]
+            #[inline]
+            pub fn $name(&mut self, $($arg: $typ),*) {
+                unsafe { self.[<_ $name _simd128>]($($arg),*) }
+            }
+
+            #[cfg(not(any(
+                target_arch = "x86",
+                target_arch = "x86_64",
+                target_arch = "aarch64",
+                all(target_arch = "wasm32", target_feature = "simd128")
+            )))]
+            #[doc(hidden)]
+            #[inline]
+            pub fn $name(&mut self, $($arg: $typ),*) {
+                self.[<_ $name _default>]($($arg),*)
+            }
+        }
+    };
+    (simd128 = $simd128:ty, simd256 = $simd256:ty, lanes128 = $lanes128:expr, lanes256 = $lanes256:expr;
+    $(#[$attr:meta])*
+    pub fn $name:ident (&$self_:ty $(, $arg:ident: $typ:ty)*) $(-> $ret:ty)? $body:block) => {
+        paste! {
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            #[target_feature(enable = "avx2,fma")]
+            [<$name _simd>]! { [<_ $name _avx2>], $simd256, $lanes256 }
+
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            #[target_feature(enable = "avx")]
+            [<$name _simd>]! { [<_ $name _avx>], $simd256, $lanes256 }
+
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            #[target_feature(enable = "sse4.2")]
+            [<$name _simd>]! { [<_ $name _sse42>], $simd128, $lanes128 }
+
+            #[cfg(target_arch = "aarch64")]
+            #[target_feature(enable = "neon")]
+            [<$name _simd>]! { [<_ $name _neon>], $simd128, $lanes128 }
+
+            #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+            [<$name _simd>]! { [<_ $name _simd128>], $simd128, $lanes128 }
+
+            #[doc(hidden)]
+            #[inline]
+            fn [<_ $name _default>](&$self_, $($arg: $typ),*) $(-> $ret)? $body
+
+            /// Runs the kernels for `feature`, or the default kernel if there
+            /// are none for it on this architecture.
+            ///
+            /// # Safety
+            /// The cpu must support `feature`.
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            #[doc(hidden)]
+            #[inline]
+            pub(crate) unsafe fn [<_ $name _with>](&self, feature: CPUFeature, $($arg: $typ),*) $(-> $ret)? {
+                match feature {
+                    CPUFeature::Avx2 => self.[<_ $name _avx2>]($($arg),*),
+                    CPUFeature::Avx => self.[<_ $name _avx>]($($arg),*),
+                    CPUFeature::Sse42 => self.[<_ $name _sse42>]($($arg),*),
+                    _ => self.[<_ $name _default>]($($arg),*),
+                }
+            }
+
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            #[doc(hidden)]
+            #[inline]
+            $(#[$attr])*
+            pub fn $name(&self, $($arg: $typ),*) $(-> $ret)? {
+                unsafe { self.[<_ $name _with>](cpu_feature(), $($arg),*) }
+            }
+
+            /// Runs the kernels for `feature`, or the default kernel if there
+            /// are none for it on this architecture.
+            ///
+            /// # Safety
+            /// The cpu must support `feature`.
+            #[cfg(target_arch = "aarch64")]
+            #[doc(hidden)]
+            #[inline]
+            pub(crate) unsafe fn [<_ $name _with>](&self, feature: CPUFeature, $($arg: $typ),*) $(-> $ret)? {
+                match feature {
+                    CPUFeature::Neon => self.[<_ $name _neon>]($($arg),*),
+                    _ => self.[<_ $name _default>]($($arg),*),
+                }
+            }
+
+            #[cfg(target_arch = "aarch64")]
+            #[doc(hidden)]
+            #[inline]
+            $(#[$attr])*
+            pub fn $name(&self, $($arg: $typ),*) $(-> $ret)? {
+                unsafe { self.[<_ $name _with>](cpu_feature(), $($arg),*) }
+            }
+
+            #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+            #[doc(hidden)]
+            #[inline]
+            $(#[$attr])*
+            pub fn $name(&self, $($arg: $typ),*) $(-> $ret)? {
+                unsafe { self.[<_ $name _simd128>]($($arg),*) }
+            }
+
+            #[cfg(not(any(
+                target_arch = "x86",
+                target_arch = "x86_64",
+                target_arch = "aarch64",
+                all(target_arch = "wasm32", target_feature = "simd128")
+            )))]
+            #[doc(hidden)]
+            #[inline]
+            $(#[$attr])*
+            pub fn $name(&self, $($arg: $typ),*) $(-> $ret)? {
+                self.[<_ $name _default>]($($arg),*)
+            }
+        }
+    };
+}
+
+// pub trait MultisetSimdFn<N> {
+//     unsafe fn _intersection_simd<S, const C: usize>(&self, other: &Self, out: &mut Self)
+//     where
+//         S: SimdBasic<N>;
+//     unsafe fn _union_simd<S, const C: usize>(&self, other: &Self, out: &mut Self)
+//     where
+//         S: SimdBasic<N>;
+//     unsafe fn _count_non_zero_simd<S, const C: usize>(&self) -> usize
+//     where
+//         S: SimdBasic<N>;
+//     unsafe fn _is_disjoint_simd<S, const C: usize>(&self, other: &Self) -> bool
+//     where
+//         S: SimdBasic<N>;
+//     unsafe fn _is_subset_simd<S, const C: usize>(&self, other: &Self) -> bool
+//     where
+//         S: SimdBasic<N>;
+//     unsafe fn _is_superset_simd<S, const C: usize>(&self, other: &Self) -> bool
+//     where
+//         S: SimdBasic<N>;
+//     unsafe fn _is_any_lesser_simd<S, const C: usize>(&self, other: &Self) -> bool
+//     where
+//         S: SimdBasic<N>;
+//     unsafe fn _is_any_greater_simd<S, const C: usize>(&self, other: &Self) -> bool
+//     where
+//         S: SimdBasic<N>;
+//     unsafe fn _total_simd<S, const C: usize>(&self) -> usize
+//     where
+//         S: SimdBasic<N>;
+//     unsafe fn _collision_entropy_simd<S, F, const SC: usize, const FC: usize>(&self) -> f64
+//     where
+//         S: SimdBasic<N>,
+//         F: SimdBasic<f64> + SimdFloat<f64>;
+//     unsafe fn _shannon_entropy_simd<S, F, const SC: usize, const FC: usize>(&self) -> f64
+//     where
+//         S: SimdBasic<N>,
 //         F: SimdBasic<f64> + SimdFloat<f64>;
 // }
 //
@@ -722,150 +1888,651 @@ where
 {
     simd_dispatch! {
         simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
-        pub fn intersection(&self, other: &Self) -> Self {
-            self.zip_map(other, |s1, s2| s1.min(s2))
+        #[must_use]
+        pub fn intersection(&self, other: &Self) -> Self {
+            self.zip_map(other, |s1, s2| s1.min(s2))
+        }
+    }
+
+    simd_dispatch! {
+        simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
+        #[must_use]
+        pub fn union(&self, other: &Self) -> Self {
+            self.zip_map(other, |s1, s2| s1.max(s2))
+        }
+    }
+
+    simd_dispatch! {
+        simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
+        pub fn intersection_mut(&mut self, other: &Self) {
+            self.iter_mut()
+                .zip(other.data.iter())
+                .for_each(|(a, b)| *a = (*a).min(*b));
+        }
+    }
+
+    simd_dispatch! {
+        simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
+        pub fn union_mut(&mut self, other: &Self) {
+            self.iter_mut()
+                .zip(other.data.iter())
+                .for_each(|(a, b)| *a = (*a).max(*b));
+        }
+    }
+
+    simd_dispatch! {
+        simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
+        pub fn intersection_into(&self, other: &Self, out: &mut Self) {
+            out.iter_mut()
+                .zip(self.data.iter().zip(other.data.iter()))
+                .for_each(|(r, (a, b))| *r = (*a).min(*b));
+        }
+    }
+
+    simd_dispatch! {
+        simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
+        pub fn union_into(&self, other: &Self, out: &mut Self) {
+            out.iter_mut()
+                .zip(self.data.iter().zip(other.data.iter()))
+                .for_each(|(r, (a, b))| *r = (*a).max(*b));
+        }
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    #[must_use]
+    pub fn intersection_all(sets: &[Self]) -> Option<Self> {
+        let (first, rest) = sets.split_first()?;
+        let mut res = *first;
+        rest.iter().for_each(|set| res.intersection_mut(set));
+        Some(res)
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    #[must_use]
+    pub fn union_all(sets: &[Self]) -> Option<Self> {
+        let (first, rest) = sets.split_first()?;
+        let mut res = *first;
+        rest.iter().for_each(|set| res.union_mut(set));
+        Some(res)
+    }
+
+    simd_dispatch! {
+        simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
+        #[must_use]
+        pub fn difference(&self, other: &Self) -> Self {
+            self.zip_map(other, |s1, s2| s1.saturating_sub(&s2))
+        }
+    }
+
+    simd_dispatch! {
+        simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
+        #[must_use]
+        pub fn symmetric_difference(&self, other: &Self) -> Self {
+            self.zip_map(other, |s1, s2| s1.max(s2) - s1.min(s2))
+        }
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    #[must_use]
+    pub fn abs_diff(&self, other: &Self) -> Self {
+        self.symmetric_difference(other)
+    }
+
+    simd_dispatch! {
+        simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
+        #[must_use]
+        pub fn sum(&self, other: &Self) -> Self {
+            self.zip_map(other, |s1, s2| s1.saturating_add(&s2))
+        }
+    }
+
+    simd_dispatch! {
+        simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
+        #[must_use]
+        pub fn checked_sum(&self, other: &Self) -> Option<Self> {
+            let mut res = Multiset::empty();
+            for (r, (a, b)) in res.iter_mut().zip(self.iter().zip(other.iter())) {
+                *r = a.checked_add(b)?;
+            }
+            Some(res)
+        }
+    }
+
+    simd_dispatch! {
+        simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
+        #[must_use]
+        pub fn min_scalar(&self, n: N) -> Self {
+            self.iter().map(|e| (*e).min(n)).collect()
+        }
+    }
+
+    simd_dispatch! {
+        simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
+        #[must_use]
+        pub fn max_scalar(&self, n: N) -> Self {
+            self.iter().map(|e| (*e).max(n)).collect()
+        }
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    #[must_use]
+    pub fn to_support(&self) -> Self {
+        self.min_scalar(N::one())
+    }
+
+    simd_dispatch! {
+        simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
+        #[must_use]
+        pub fn subtract_support(&self, other: &Self) -> Self {
+            self.zip_map(other, |s1, s2| if s2 == N::zero() { s1 } else { N::zero() })
+        }
+    }
+
+    simd_dispatch! {
+        simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
+        #[must_use]
+        pub fn retain_support(&self, other: &Self) -> Self {
+            self.zip_map(other, |s1, s2| if s2 == N::zero() { N::zero() } else { s1 })
+        }
+    }
+
+    simd_dispatch! {
+        simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
+        pub fn count_intersection(&self, other: &Self) -> u128 {
+            self.iter()
+                .zip(other.data.iter())
+                .map(|(a, b)| <N as AsPrimitive<u128>>::as_(*a.min(b)))
+                .sum()
+        }
+    }
+
+    simd_dispatch! {
+        simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
+        pub fn count_union(&self, other: &Self) -> u128 {
+            self.iter()
+                .zip(other.data.iter())
+                .map(|(a, b)| <N as AsPrimitive<u128>>::as_(*a.max(b)))
+                .sum()
+        }
+    }
+
+    simd_dispatch! {
+        simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
+        pub fn count_non_zero(&self) -> usize {
+            self.iter().fold(0, |acc, &elem| {
+                acc + <N as AsPrimitive<usize>>::as_(elem.min(N::one()))
+            })
+        }
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub fn count_zero(&self) -> usize {
+        SIZE - self.count_non_zero()
+    }
+
+    simd_dispatch! {
+        simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
+        pub fn count_greater_than(&self, n: N) -> usize {
+            self.iter().filter(|count| **count > n).count()
+        }
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub fn count_at_most(&self, n: N) -> usize {
+        SIZE - self.count_greater_than(n)
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub fn is_singleton(&self) -> bool {
+        self.count_non_zero() == 1
+    }
+
+    simd_dispatch! {
+        simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
+        pub fn is_disjoint(&self, other: &Self) -> bool {
+            self.iter()
+                .zip(other.data.iter())
+                .all(|(a, b)| a.min(b) == &N::zero())
+        }
+    }
+
+    simd_dispatch! {
+        simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
+        pub fn is_subset(&self, other: &Self) -> bool {
+            self.iter().zip(other.data.iter()).all(|(a, b)| a <= b)
+        }
+    }
+
+    simd_dispatch! {
+        simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
+        pub fn is_subset_of_sum(&self, b: &Self, c: &Self) -> bool {
+            self.iter()
+                .zip(b.data.iter().zip(c.data.iter()))
+                .all(|(a, (b, c))| a.saturating_sub(b) <= *c)
+        }
+    }
+
+    simd_dispatch! {
+        simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
+        pub fn is_superset(&self, other: &Self) -> bool {
+            self.iter().zip(other.data.iter()).all(|(a, b)| a >= b)
+        }
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub fn is_proper_subset(&self, other: &Self) -> bool {
+        self != other && self.is_subset(other)
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub fn is_proper_superset(&self, other: &Self) -> bool {
+        self != other && self.is_superset(other)
+    }
+
+    simd_dispatch! {
+        simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
+        pub fn is_any_lesser(&self, other: &Self) -> bool {
+            self.iter().zip(other.data.iter()).any(|(a, b)| a < b)
+        }
+    }
+
+    simd_dispatch! {
+        simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
+        pub fn is_any_greater(&self, other: &Self) -> bool {
+            self.iter().zip(other.data.iter()).any(|(a, b)| a > b)
+        }
+    }
+
+    simd_dispatch! {
+        simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
+        pub fn total(&self) -> usize {
+            self.iter().map(|e| <N as AsPrimitive<usize>>::as_(*e)).sum()
         }
     }
 
     simd_dispatch! {
         simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
-        pub fn union(&self, other: &Self) -> Self {
-            self.zip_map(other, |s1, s2| s1.max(s2))
+        pub fn count_max(&self) -> &N {
+            self.iter().max().unwrap()
         }
     }
 
     simd_dispatch! {
         simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
-        pub fn count_non_zero(&self) -> usize {
-            self.iter().fold(0, |acc, &elem| {
-                acc + <N as AsPrimitive<usize>>::as_(elem.min(N::zero()))
-            })
+        pub fn count_min(&self) -> &N {
+            self.iter().min().unwrap()
+        }
+    }
+
+    simd_dispatch! {
+        simd128 = N::SIMDFloat, simd256 = N::SIMDFloat, lanes128 = {N::LF}, lanes256 = {N::LF};
+        pub fn sum_squared_probabilities(&self) -> f64 {
+            let total: f64 = self.total_wide() as f64;
+            compensated_sum(self.into_iter().map(|&frequency| {
+                let freq_f64: f64 = <N as AsPrimitive<f64>>::as_(frequency);
+                (freq_f64 / total).powf(2.0)
+            }))
+        }
+    }
+
+    simd_dispatch! {
+        simd128 = N::SIMDFloat, simd256 = N::SIMDFloat, lanes128 = {N::LF}, lanes256 = {N::LF};
+        pub fn normalize(&self) -> [f64; SIZE] {
+            let mut res = [0.0; SIZE];
+            let total: f64 = self.total_wide() as f64;
+            if total > 0.0 {
+                for (prob, count) in res.iter_mut().zip(self.iter()) {
+                    *prob = <N as AsPrimitive<f64>>::as_(*count) / total;
+                }
+            }
+            res
+        }
+    }
+
+    simd_dispatch! {
+        simd128 = N::SIMDFloat, simd256 = N::SIMDFloat, lanes128 = {N::LF}, lanes256 = {N::LF};
+        pub fn expected_value(&self, values: &[f64]) -> f64 {
+            assert_eq!(values.len(), SIZE, "values must have length SIZE");
+            let total: f64 = self.total_wide() as f64;
+            if total == 0.0 {
+                return 0.0;
+            }
+            self.iter()
+                .zip(values)
+                .map(|(count, value)| <N as AsPrimitive<f64>>::as_(*count) * value)
+                .sum::<f64>()
+                / total
         }
     }
 
     #[doc(hidden)]
     #[inline]
-    pub fn count_zero(&self) -> usize {
-        SIZE - self.count_non_zero()
+    pub fn mean_count(&self) -> f64 {
+        self.total_wide() as f64 / SIZE as f64
+    }
+
+    simd_dispatch! {
+        simd128 = N::SIMDFloat, simd256 = N::SIMDFloat, lanes128 = {N::LF}, lanes256 = {N::LF};
+        pub fn variance(&self) -> f64 {
+            let mean = self.mean_count();
+            self.iter()
+                .map(|count| {
+                    let deviation = <N as AsPrimitive<f64>>::as_(*count) - mean;
+                    deviation * deviation
+                })
+                .sum::<f64>()
+                / SIZE as f64
+        }
     }
 
     #[doc(hidden)]
     #[inline]
-    pub fn is_singleton(&self) -> bool {
-        self.count_non_zero() == 1
+    pub fn std_dev(&self) -> f64 {
+        self.variance().sqrt()
     }
 
     simd_dispatch! {
-        simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
-        pub fn is_disjoint(&self, other: &Self) -> bool {
+        simd128 = N::SIMDFloat, simd256 = N::SIMDFloat, lanes128 = {N::LF}, lanes256 = {N::LF};
+        pub fn moment(&self, k: u32) -> f64 {
+            if k <= 3 {
+                let exact = self.iter().try_fold(0u128, |acc, count| {
+                    let count = <N as AsPrimitive<u128>>::as_(*count);
+                    count.checked_pow(k).and_then(|power| acc.checked_add(power))
+                });
+                if let Some(exact) = exact {
+                    return exact as f64;
+                }
+            }
+            let k = i32::try_from(k).unwrap_or(i32::MAX);
             self.iter()
-                .zip(other.data.iter())
-                .all(|(a, b)| a.min(b) == &N::zero())
+                .map(|count| <N as AsPrimitive<f64>>::as_(*count).powi(k))
+                .sum()
+        }
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub fn collision_entropy(&self) -> f64 {
+        let sum = self.sum_squared_probabilities();
+        if sum.is_nan() || sum == 0.0 {
+            0.0
+        } else {
+            0.0 - sum.log2()
+        }
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub fn gini_impurity(&self) -> f64 {
+        let sum = self.sum_squared_probabilities();
+        if sum.is_nan() || sum == 0.0 {
+            0.0
+        } else {
+            1.0 - sum
         }
     }
 
     simd_dispatch! {
-        simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
-        pub fn is_subset(&self, other: &Self) -> bool {
-            self.iter().zip(other.data.iter()).all(|(a, b)| a <= b)
+        simd128 = N::SIMDFloat, simd256 = N::SIMDFloat, lanes128 = {N::LF}, lanes256 = {N::LF};
+        pub fn shannon_entropy(&self) -> f64 {
+            let total: f64 = self.total_wide() as f64;
+            compensated_sum(
+                self.into_iter()
+                    .filter(|&&frequency| frequency > N::zero())
+                    .map(|&frequency| {
+                        let freq_f64: f64 = <N as AsPrimitive<f64>>::as_(frequency);
+                        let prob = freq_f64 / total;
+                        -(prob * prob.ln())
+                    }),
+            )
         }
     }
 
     simd_dispatch! {
-        simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
-        pub fn is_superset(&self, other: &Self) -> bool {
-            self.iter().zip(other.data.iter()).all(|(a, b)| a >= b)
+        simd128 = N::SIMDFloat, simd256 = N::SIMDFloat, lanes128 = {N::LF}, lanes256 = {N::LF};
+        pub fn weighted_shannon_entropy(&self, weights: &[f64]) -> f64 {
+            assert_eq!(weights.len(), SIZE, "weights must have length SIZE");
+            let (total, weighted_log) = self
+                .iter()
+                .zip(weights)
+                .filter(|(count, weight)| **count > N::zero() && **weight > 0.0)
+                .fold((0.0, 0.0), |(total, weighted_log), (_, weight)| {
+                    (total + weight, weighted_log + weight * weight.ln())
+                });
+            if total == 0.0 {
+                0.0
+            } else {
+                total.ln() - weighted_log / total
+            }
+        }
+    }
+
+    simd_dispatch! {
+        simd128 = N::SIMDFloat, simd256 = N::SIMDFloat, lanes128 = {N::LF}, lanes256 = {N::LF};
+        pub fn kl_divergence(&self, other: &Self) -> f64 {
+            let total_p: f64 = self.total_wide() as f64;
+            let total_q: f64 = other.total_wide() as f64;
+            if total_p == 0.0 || total_q == 0.0 {
+                return f64::NAN;
+            }
+            self.iter().zip(other.iter()).fold(0.0, |acc, (p, q)| {
+                if *p == N::zero() {
+                    acc
+                } else if *q == N::zero() {
+                    f64::INFINITY
+                } else {
+                    let prob_p = <N as AsPrimitive<f64>>::as_(*p) / total_p;
+                    let prob_q = <N as AsPrimitive<f64>>::as_(*q) / total_q;
+                    acc + prob_p * (prob_p / prob_q).ln()
+                }
+            })
+        }
+    }
+
+    simd_dispatch! {
+        simd128 = N::SIMDFloat, simd256 = N::SIMDFloat, lanes128 = {N::LF}, lanes256 = {N::LF};
+        pub fn cross_entropy(&self, other: &Self) -> f64 {
+            let total_p: f64 = self.total_wide() as f64;
+            let total_q: f64 = other.total_wide() as f64;
+            if total_p == 0.0 || total_q == 0.0 {
+                return f64::NAN;
+            }
+            -self.iter().zip(other.iter()).fold(0.0, |acc, (p, q)| {
+                if *p == N::zero() {
+                    acc
+                } else if *q == N::zero() {
+                    f64::NEG_INFINITY
+                } else {
+                    let prob_p = <N as AsPrimitive<f64>>::as_(*p) / total_p;
+                    let prob_q = <N as AsPrimitive<f64>>::as_(*q) / total_q;
+                    acc + prob_p * prob_q.ln()
+                }
+            })
         }
     }
 
     #[doc(hidden)]
     #[inline]
-    pub fn is_proper_subset(&self, other: &Self) -> bool {
-        self != other && self.is_subset(other)
+    pub fn cross_entropy_bits(&self, other: &Self) -> f64 {
+        self.cross_entropy(other) / std::f64::consts::LN_2
+    }
+
+    simd_dispatch! {
+        simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
+        pub fn jaccard_index(&self, other: &Self) -> f64 {
+            let (intersection, union) = self.iter().zip(other.iter()).fold(
+                (0u128, 0u128),
+                |(min_acc, max_acc), (a, b)| {
+                    (
+                        min_acc + <N as AsPrimitive<u128>>::as_(*a.min(b)),
+                        max_acc + <N as AsPrimitive<u128>>::as_(*a.max(b)),
+                    )
+                },
+            );
+            if union == 0 {
+                1.0
+            } else {
+                intersection as f64 / union as f64
+            }
+        }
     }
 
     #[doc(hidden)]
     #[inline]
-    pub fn is_proper_superset(&self, other: &Self) -> bool {
-        self != other && self.is_superset(other)
+    pub fn overlap_coefficient(&self, other: &Self) -> f64 {
+        let total = self.total_wide().min(other.total_wide());
+        if total == 0 {
+            0.0
+        } else {
+            self.count_intersection(other) as f64 / total as f64
+        }
     }
 
     simd_dispatch! {
         simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
-        pub fn is_any_lesser(&self, other: &Self) -> bool {
-            self.iter().zip(other.data.iter()).any(|(a, b)| a < b)
+        pub fn tversky_index(&self, other: &Self, alpha: f64, beta: f64) -> f64 {
+            let (common, self_only, other_only) = self.iter().zip(other.iter()).fold(
+                (0u128, 0u128, 0u128),
+                |(common, self_only, other_only), (a, b)| {
+                    let min = *a.min(b);
+                    (
+                        common + <N as AsPrimitive<u128>>::as_(min),
+                        self_only + <N as AsPrimitive<u128>>::as_(*a - min),
+                        other_only + <N as AsPrimitive<u128>>::as_(*b - min),
+                    )
+                },
+            );
+            if common == 0 {
+                return if self_only == 0 && other_only == 0 {
+                    1.0
+                } else {
+                    0.0
+                };
+            }
+            let common = common as f64;
+            common / (common + alpha * self_only as f64 + beta * other_only as f64)
         }
     }
 
     simd_dispatch! {
         simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
-        pub fn is_any_greater(&self, other: &Self) -> bool {
-            self.iter().zip(other.data.iter()).any(|(a, b)| a > b)
+        pub fn l1_distance(&self, other: &Self) -> u64 {
+            self.iter().zip(other.iter()).fold(0, |acc, (a, b)| {
+                acc.saturating_add(<N as AsPrimitive<u64>>::as_(*a.max(b) - *a.min(b)))
+            })
         }
     }
 
     simd_dispatch! {
         simd128 = N::SIMD128, simd256 = N::SIMD256, lanes128 = {N::L128}, lanes256 = {N::L256};
-        pub fn total(&self) -> usize {
-            self.iter().map(|e| <N as AsPrimitive<usize>>::as_(*e)).sum()
+        pub fn l2_distance_squared(&self, other: &Self) -> u64 {
+            u64::try_from(self.l2_distance_squared_wide(other)).unwrap_or(u64::MAX)
         }
     }
 
-    #[cfg(feature = "rand")]
-    #[doc(hidden)]
-    #[inline]
-    pub fn choose_random<T: RngCore>(&mut self, rng: &mut T) {
-        let total = self.total();
-        if total == 0 {
-            return;
+    simd_dispatch! {
+        simd128 = N::SIMDFloat, simd256 = N::SIMDFloat, lanes128 = {N::LF}, lanes256 = {N::LF};
+        pub fn dot(&self, other: &Self) -> u64 {
+            let dot: u128 = self
+                .iter()
+                .zip(other.iter())
+                .map(|(a, b)| <N as AsPrimitive<u128>>::as_(*a) * <N as AsPrimitive<u128>>::as_(*b))
+                .fold(0, u128::saturating_add);
+            u64::try_from(dot).unwrap_or(u64::MAX)
         }
-        let choice_value = rng.gen_range(1..=total);
-        let mut res = [N::zero(); SIZE];
-        let mut acc = 0;
-        for (i, elem) in self.iter().enumerate() {
-            acc += <N as AsPrimitive<usize>>::as_(*elem);
-            if acc >= choice_value {
-                // Safety: `i` cannot be outside of `res`.
-                unsafe { *res.get_unchecked_mut(i) = *elem }
-                break;
+    }
+
+    simd_dispatch! {
+        simd128 = N::SIMDFloat, simd256 = N::SIMDFloat, lanes128 = {N::LF}, lanes256 = {N::LF};
+        pub fn cosine_similarity(&self, other: &Self) -> f64 {
+            let (dot, norm_a, norm_b) = self.iter().zip(other.iter()).fold(
+                (0.0, 0.0, 0.0),
+                |(dot, norm_a, norm_b), (a, b)| {
+                    let a: f64 = a.as_();
+                    let b: f64 = b.as_();
+                    (dot + a * b, norm_a + a * a, norm_b + b * b)
+                },
+            );
+            if norm_a == 0.0 || norm_b == 0.0 {
+                0.0
+            } else {
+                dot / (norm_a.sqrt() * norm_b.sqrt())
             }
         }
-        self.data = res
     }
 
     simd_dispatch! {
         simd128 = N::SIMDFloat, simd256 = N::SIMDFloat, lanes128 = {N::LF}, lanes256 = {N::LF};
-        pub fn collision_entropy(&self) -> f64 {
-            let total: f64 = self.total().as_();
-            -self.into_iter()
-                .fold(0.0, |acc, &frequency| {
-                    let freq_f64: f64 = <N as AsPrimitive<f64>>::as_(frequency);
-                    acc + (freq_f64 / total).powf(2.0)
+        pub fn hellinger_distance(&self, other: &Self) -> f64 {
+            let total_p: f64 = self.total_wide() as f64;
+            let total_q: f64 = other.total_wide() as f64;
+            if total_p == 0.0 || total_q == 0.0 {
+                return if total_p == total_q { 0.0 } else { 1.0 };
+            }
+            let coefficient: f64 = self
+                .iter()
+                .zip(other.iter())
+                .map(|(p, q)| {
+                    let prob_p = <N as AsPrimitive<f64>>::as_(*p) / total_p;
+                    let prob_q = <N as AsPrimitive<f64>>::as_(*q) / total_q;
+                    (prob_p * prob_q).sqrt()
                 })
-                .log2()
+                .sum();
+            (1.0 - coefficient).max(0.0).sqrt()
         }
     }
 
+    // A simd prefix scan is not yet implemented, so this is only the scalar
+    // version.
+    #[doc(hidden)]
+    #[inline]
+    pub fn emd_1d(&self, other: &Self) -> f64 {
+        let total_p: f64 = self.total_wide() as f64;
+        let total_q: f64 = other.total_wide() as f64;
+        if total_p == 0.0 || total_q == 0.0 {
+            return if total_p == total_q { 0.0 } else { 1.0 };
+        }
+        let (_, res) =
+            self.iter()
+                .zip(other.iter())
+                .fold((0.0, 0.0), |(cumulative, acc), (p, q)| {
+                    let prob_p = <N as AsPrimitive<f64>>::as_(*p) / total_p;
+                    let prob_q = <N as AsPrimitive<f64>>::as_(*q) / total_q;
+                    let cumulative: f64 = cumulative + prob_p - prob_q;
+                    (cumulative, acc + cumulative.abs())
+                });
+        res
+    }
+
     simd_dispatch! {
         simd128 = N::SIMDFloat, simd256 = N::SIMDFloat, lanes128 = {N::LF}, lanes256 = {N::LF};
-        pub fn shannon_entropy(&self) -> f64 {
-            let total: f64 = self.total().as_();
-            -self.into_iter().fold(0.0, |acc, &frequency| {
-                if frequency > N::zero() {
-                    let freq_f64: f64 = <N as AsPrimitive<f64>>::as_(frequency);
-                    let prob = freq_f64 / total;
-                    acc + prob * prob.ln()
-                } else {
-                    acc
-                }
-            })
+        pub fn chi_squared_distance(&self, other: &Self) -> f64 {
+            self.iter()
+                .zip(other.iter())
+                .filter(|(a, b)| **a > N::zero() || **b > N::zero())
+                .map(|(a, b)| {
+                    let a: f64 = a.as_();
+                    let b: f64 = b.as_();
+                    (a - b).powi(2) / (a + b)
+                })
+                .sum::<f64>()
+                / 2.0
         }
     }
 }
 
 impl<N: Counter, const SIZE: usize> PartialOrd for Multiset<N, SIZE>
-    where
-        [(); N::L128 * N::L256 * N::LF]: Sized,
+where
+    [(); N::L128 * N::L256 * N::LF]: Sized,
 {
     #[inline]
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
@@ -902,3 +2569,312 @@ impl<N: Counter, const SIZE: usize> PartialOrd for Multiset<N, SIZE>
         self.is_superset(other)
     }
 }
+
+impl<N: Counter, const SIZE: usize> BitAnd for Multiset<N, SIZE>
+where
+    [(); N::L128 * N::L256 * N::LF]: Sized,
+{
+    type Output = Multiset<N, SIZE>;
+
+    #[inline]
+    fn bitand(self, rhs: Self) -> Self::Output {
+        self.intersection(&rhs)
+    }
+}
+
+impl<N: Counter, const SIZE: usize> BitAnd<&Multiset<N, SIZE>> for &Multiset<N, SIZE>
+where
+    [(); N::L128 * N::L256 * N::LF]: Sized,
+{
+    type Output = Multiset<N, SIZE>;
+
+    #[inline]
+    fn bitand(self, rhs: &Multiset<N, SIZE>) -> Self::Output {
+        self.intersection(rhs)
+    }
+}
+
+impl<N: Counter, const SIZE: usize> BitAndAssign for Multiset<N, SIZE>
+where
+    [(); N::L128 * N::L256 * N::LF]: Sized,
+{
+    #[inline]
+    fn bitand_assign(&mut self, rhs: Self) {
+        self.intersection_mut(&rhs);
+    }
+}
+
+impl<N: Counter, const SIZE: usize> BitAndAssign<&Multiset<N, SIZE>> for Multiset<N, SIZE>
+where
+    [(); N::L128 * N::L256 * N::LF]: Sized,
+{
+    #[inline]
+    fn bitand_assign(&mut self, rhs: &Multiset<N, SIZE>) {
+        self.intersection_mut(rhs);
+    }
+}
+
+impl<N: Counter, const SIZE: usize> BitOr for Multiset<N, SIZE>
+where
+    [(); N::L128 * N::L256 * N::LF]: Sized,
+{
+    type Output = Multiset<N, SIZE>;
+
+    #[inline]
+    fn bitor(self, rhs: Self) -> Self::Output {
+        self.union(&rhs)
+    }
+}
+
+impl<N: Counter, const SIZE: usize> BitOr<&Multiset<N, SIZE>> for &Multiset<N, SIZE>
+where
+    [(); N::L128 * N::L256 * N::LF]: Sized,
+{
+    type Output = Multiset<N, SIZE>;
+
+    #[inline]
+    fn bitor(self, rhs: &Multiset<N, SIZE>) -> Self::Output {
+        self.union(rhs)
+    }
+}
+
+impl<N: Counter, const SIZE: usize> BitOrAssign for Multiset<N, SIZE>
+where
+    [(); N::L128 * N::L256 * N::LF]: Sized,
+{
+    #[inline]
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.union_mut(&rhs);
+    }
+}
+
+impl<N: Counter, const SIZE: usize> BitOrAssign<&Multiset<N, SIZE>> for Multiset<N, SIZE>
+where
+    [(); N::L128 * N::L256 * N::LF]: Sized,
+{
+    #[inline]
+    fn bitor_assign(&mut self, rhs: &Multiset<N, SIZE>) {
+        self.union_mut(rhs);
+    }
+}
+
+impl<N: Counter, const SIZE: usize> ByEntropy<Multiset<N, SIZE>>
+where
+    [(); N::L128 * N::L256 * N::LF]: Sized,
+{
+    #[inline]
+    pub fn new(multiset: Multiset<N, SIZE>) -> Self {
+        ByEntropy {
+            entropy: multiset.shannon_entropy(),
+            inner: multiset,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Multiset;
+    use approx::assert_relative_eq;
+    use std::iter::FromIterator;
+
+    // The default kernels are the only ones on some targets, and are never
+    // dispatched to on most machines, so they are checked directly.
+    #[test]
+    fn test_default_kernels() {
+        let a = Multiset::<u16, 37>::from_iter((0..37).map(|i| i % 5));
+        let b = Multiset::<u16, 37>::from_iter((0..37).map(|i| 4 - i % 5));
+        let min = Multiset::<u16, 37>::from_iter((0..37).map(|i| (i % 5).min(4 - i % 5)));
+        let max = Multiset::<u16, 37>::from_iter((0..37).map(|i| (i % 5).max(4 - i % 5)));
+        assert_eq!(a._intersection_default(&b), min);
+        assert_eq!(a._union_default(&b), max);
+        assert_eq!(a._total_default(), 71);
+        assert!(min._is_subset_default(&a) && a._is_superset_default(&min));
+        assert!(!a._is_subset_default(&b));
+
+        let uniform = Multiset::<u32, 4>::from([3, 3, 3, 3]);
+        assert_relative_eq!(uniform._shannon_entropy_default(), 4f64.ln());
+        assert_relative_eq!(uniform._sum_squared_probabilities_default(), 0.25);
+        assert_eq!(Multiset::<u8, 100>::repeat(200)._total_default(), 20_000);
+    }
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64"))]
+    #[test]
+    fn test_cpu_feature_concurrent_init() {
+        use super::CPUFeature;
+        use std::sync::{Arc, Barrier, OnceLock};
+        use std::thread;
+
+        // CPU_FEATURE may already be set by other tests, so the threads race
+        // to initialise a cell of their own instead.
+        static CELL: OnceLock<CPUFeature> = OnceLock::new();
+        let set = Multiset::<u32, 37>::repeat(3);
+        let barrier = Arc::new(Barrier::new(8));
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let barrier = Arc::clone(&barrier);
+                thread::spawn(move || {
+                    barrier.wait();
+                    let feature = *CELL.get_or_init(CPUFeature::detect);
+                    (feature, set.total(), set.intersection(&set))
+                })
+            })
+            .collect();
+        for handle in handles {
+            let (feature, total, intersection) = handle.join().unwrap();
+            assert_eq!(feature, CPUFeature::detect());
+            assert_eq!(total, 111);
+            assert_eq!(intersection, set);
+        }
+        assert_eq!(CELL.get(), Some(&CPUFeature::detect()));
+    }
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64"))]
+    #[test]
+    fn test_cpu_feature() {
+        use super::CPUFeature;
+
+        assert_eq!(crate::cpu_feature(), CPUFeature::detect());
+        assert_eq!(crate::cpu_feature(), crate::cpu_feature());
+    }
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    #[test]
+    fn test_with_each_feature() {
+        use super::CPUFeature;
+
+        let set = Multiset::<u8, 100>::repeat(200);
+        let other = Multiset::<u8, 100>::repeat(7);
+        let mut features = vec![CPUFeature::Default, CPUFeature::Neon];
+        if is_x86_feature_detected!("avx2") {
+            features.push(CPUFeature::Avx2);
+        }
+        if is_x86_feature_detected!("avx") {
+            features.push(CPUFeature::Avx);
+        }
+        if is_x86_feature_detected!("sse4.2") {
+            features.push(CPUFeature::Sse42);
+        }
+        for feature in features {
+            unsafe {
+                assert_eq!(set._total_with(feature), 20_000, "{:?}", feature);
+                assert_eq!(set._intersection_with(feature, &other), other);
+                assert_relative_eq!(
+                    set._shannon_entropy_with(feature),
+                    set._shannon_entropy_default(),
+                    max_relative = 1e-12
+                );
+            }
+        }
+    }
+
+    // The dispatched methods only reach the widest kernel the cpu supports,
+    // so each kernel is called directly.
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    #[test]
+    fn test_entropy_empty_all_paths() {
+        fn assert_positive_zero(x: f64) {
+            assert_eq!(x.to_bits(), 0f64.to_bits(), "{} is not 0.0", x);
+        }
+
+        let empty = Multiset::<u32, 37>::empty();
+        assert_positive_zero(empty.shannon_entropy());
+        assert_positive_zero(empty.collision_entropy());
+        assert_positive_zero(empty._shannon_entropy_default());
+        assert!(empty._sum_squared_probabilities_default().is_nan());
+        unsafe {
+            if is_x86_feature_detected!("avx2") {
+                assert_positive_zero(empty._shannon_entropy_avx2());
+                assert!(empty._sum_squared_probabilities_avx2().is_nan());
+            }
+            if is_x86_feature_detected!("avx") {
+                assert_positive_zero(empty._shannon_entropy_avx());
+                assert!(empty._sum_squared_probabilities_avx().is_nan());
+            }
+            if is_x86_feature_detected!("sse4.2") {
+                assert_positive_zero(empty._shannon_entropy_sse42());
+                assert!(empty._sum_squared_probabilities_sse42().is_nan());
+            }
+        }
+    }
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    #[test]
+    fn test_entropy_precision_all_paths() {
+        let mut set = Multiset::<u32, 40_000>::repeat(1);
+        set.data[0] = 4_000_000_000;
+        let shannon = set._shannon_entropy_default();
+        let sum_squares = set._sum_squared_probabilities_default();
+        unsafe {
+            if is_x86_feature_detected!("avx2") {
+                assert_relative_eq!(set._shannon_entropy_avx2(), shannon, max_relative = 1e-12);
+                assert_relative_eq!(
+                    set._sum_squared_probabilities_avx2(),
+                    sum_squares,
+                    max_relative = 1e-15
+                );
+            }
+            if is_x86_feature_detected!("avx") {
+                assert_relative_eq!(set._shannon_entropy_avx(), shannon, max_relative = 1e-12);
+                assert_relative_eq!(
+                    set._sum_squared_probabilities_avx(),
+                    sum_squares,
+                    max_relative = 1e-15
+                );
+            }
+            if is_x86_feature_detected!("sse4.2") {
+                assert_relative_eq!(set._shannon_entropy_sse42(), shannon, max_relative = 1e-12);
+                assert_relative_eq!(
+                    set._sum_squared_probabilities_sse42(),
+                    sum_squares,
+                    max_relative = 1e-15
+                );
+            }
+        }
+    }
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    #[test]
+    fn test_total_wrapping_lanes_all_paths() {
+        let set = Multiset::<u8, 100>::repeat(200);
+        assert_eq!(set._total_default(), 20_000);
+        unsafe {
+            if is_x86_feature_detected!("avx2") {
+                assert_eq!(set._total_avx2(), 20_000);
+            }
+            if is_x86_feature_detected!("avx") {
+                assert_eq!(set._total_avx(), 20_000);
+            }
+            if is_x86_feature_detected!("sse4.2") {
+                assert_eq!(set._total_sse42(), 20_000);
+            }
+        }
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    #[test]
+    fn test_neon_matches_default() {
+        if !std::arch::is_aarch64_feature_detected!("neon") {
+            return;
+        }
+        let empty = Multiset::<u32, 37>::empty();
+        let mut set = Multiset::<u32, 40_000>::repeat(1);
+        set.data[0] = 4_000_000_000;
+        let wrapping = Multiset::<u8, 100>::repeat(200);
+        unsafe {
+            assert_eq!(empty._shannon_entropy_neon().to_bits(), 0f64.to_bits());
+            assert!(empty._sum_squared_probabilities_neon().is_nan());
+            assert_relative_eq!(
+                set._shannon_entropy_neon(),
+                set._shannon_entropy_default(),
+                max_relative = 1e-12
+            );
+            assert_relative_eq!(
+                set._sum_squared_probabilities_neon(),
+                set._sum_squared_probabilities_default(),
+                max_relative = 1e-15
+            );
+            assert_eq!(wrapping._total_neon(), 20_000);
+            assert_eq!(wrapping._total_default(), 20_000);
+        }
+    }
+}
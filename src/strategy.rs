@@ -0,0 +1,176 @@
+//! [`proptest`] strategies for generating multisets.
+//!
+//! `Multiset` implements [`Arbitrary`], so `any::<Multiset<N, SIZE>>()` can be
+//! used directly in property tests. The strategies here give more control
+//! over what is generated.
+//!
+//! # Examples
+//!
+//! ```
+//! use proptest::prelude::*;
+//! use utote::{strategy, Multiset};
+//!
+//! proptest! {
+//!     // Add #[test] to run these as part of a test suite.
+//!     fn total_is_exact(ms in strategy::with_total::<u16, 8>(100)) {
+//!         prop_assert_eq!(ms.total(), 100);
+//!     }
+//!
+//!     fn union_contains_both(a: Multiset<u8, 8>, b: Multiset<u8, 8>) {
+//!         prop_assert!(a.is_subset(&a.union(&b)));
+//!     }
+//! }
+//!
+//! total_is_exact();
+//! union_contains_both();
+//! ```
+
+use crate::{Counter, Multiset};
+use proptest::arbitrary::{any, Arbitrary};
+use proptest::collection::vec;
+use proptest::prop_oneof;
+use proptest::strategy::{BoxedStrategy, Just, Strategy};
+
+/// Generates multisets with every count chosen uniformly from all values of
+/// the counter.
+pub fn counts<N, const SIZE: usize>() -> impl Strategy<Value = Multiset<N, SIZE>>
+where
+    N: Counter + Arbitrary,
+{
+    vec(any::<N>(), SIZE).prop_map(|counts| counts.into_iter().collect())
+}
+
+/// Generates multisets with every count chosen uniformly from `0..=max`.
+pub fn counts_up_to<N, const SIZE: usize>(max: N) -> impl Strategy<Value = Multiset<N, SIZE>>
+where
+    N: Counter + Arbitrary,
+{
+    // A max of the largest counter value allows every count.
+    let bound = max.checked_add(&N::one());
+    vec(any::<N>(), SIZE).prop_map(move |counts| {
+        counts
+            .into_iter()
+            .map(|count| bound.map_or(count, |bound| count % bound))
+            .collect()
+    })
+}
+
+/// Generates multisets with a single non-zero count.
+///
+/// # Panics
+/// Panics if `SIZE` is zero, as no such multiset exists.
+pub fn singleton<N, const SIZE: usize>() -> impl Strategy<Value = Multiset<N, SIZE>>
+where
+    N: Counter + Arbitrary,
+{
+    assert!(SIZE > 0, "no singleton Multiset of SIZE: 0");
+    (0..SIZE, any::<N>()).prop_map(|(elem, count)| {
+        let mut res = Multiset::empty();
+        res.insert(elem, count.max(N::one()));
+        res
+    })
+}
+
+/// Generates multisets whose counts add up to `total`, by adding `total`
+/// random elements to the empty multiset.
+///
+/// Counts saturate at the maximum value of the counter, so `total` should be
+/// small enough to fit.
+///
+/// # Panics
+/// Panics if `SIZE` is zero and `total` is not.
+pub fn with_total<N, const SIZE: usize>(total: usize) -> impl Strategy<Value = Multiset<N, SIZE>>
+where
+    N: Counter,
+{
+    assert!(
+        SIZE > 0 || total == 0,
+        "no Multiset of SIZE: 0 with total: {}",
+        total
+    );
+    vec(0..SIZE.max(1), total).prop_map(Multiset::from_elements)
+}
+
+/// Generates empty, singleton and saturated multisets, as well as multisets
+/// with arbitrary counts.
+impl<N, const SIZE: usize> Arbitrary for Multiset<N, SIZE>
+where
+    N: Counter + Arbitrary,
+{
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        if SIZE == 0 {
+            return Just(Multiset::empty()).boxed();
+        }
+        prop_oneof![
+            1 => Just(Multiset::empty()),
+            1 => Just(Multiset::repeat(N::max_value())),
+            2 => singleton(),
+            6 => counts(),
+        ]
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    // Small counts make overlapping multisets, and so interesting relations
+    // between them, far more likely.
+    fn small<const SIZE: usize>() -> impl Strategy<Value = Multiset<u8, SIZE>> {
+        vec(0u8..4, SIZE).prop_map(|counts| counts.into_iter().collect())
+    }
+
+    proptest! {
+        #[test]
+        fn test_with_total(ms in with_total::<u16, 13>(300)) {
+            prop_assert_eq!(ms.total(), 300);
+        }
+
+        #[test]
+        fn test_singleton(ms in singleton::<u32, 13>()) {
+            prop_assert!(ms.is_singleton());
+        }
+
+        #[test]
+        fn test_intersection_distributes_over_union(
+            a in small::<13>(),
+            b in small::<13>(),
+            c in small::<13>(),
+        ) {
+            prop_assert_eq!(
+                a.intersection(&b.union(&c)),
+                a.intersection(&b).union(&a.intersection(&c))
+            );
+            prop_assert_eq!(
+                a.union(&b.intersection(&c)),
+                a.union(&b).intersection(&a.union(&c))
+            );
+        }
+
+        #[test]
+        fn test_subset_antisymmetry(a in small::<5>(), b in small::<5>()) {
+            if a.is_subset(&b) && b.is_subset(&a) {
+                prop_assert_eq!(a, b);
+            }
+        }
+
+        #[test]
+        fn test_difference_sum_inverse(a: Multiset<u8, 13>, b: Multiset<u8, 13>) {
+            let sub = a.intersection(&b);
+            prop_assert_eq!(a.difference(&sub).sum(&sub), a);
+        }
+
+        #[test]
+        fn test_count_intersection_union(a: Multiset<u16, 13>, b: Multiset<u16, 13>) {
+            prop_assert_eq!(
+                a.count_intersection(&b) + a.count_union(&b),
+                a.total_wide() + b.total_wide()
+            );
+        }
+    }
+}
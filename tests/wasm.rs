@@ -0,0 +1,32 @@
+//! Runs in a headless browser with
+//! `wasm-pack test --headless --firefox`, and again with
+//! `RUSTFLAGS="-C target-feature=+simd128"` to cover the simd128 kernels.
+#![cfg(target_arch = "wasm32")]
+
+use utote::Multiset;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn test_intersection() {
+    let a: Multiset<u16, 37> = (0..37).map(|i| i % 5).collect();
+    let b: Multiset<u16, 37> = (0..37).map(|i| 4 - i % 5).collect();
+    let expected: Multiset<u16, 37> = (0..37).map(|i| (i % 5).min(4 - i % 5)).collect();
+    assert_eq!(a.intersection(&b), expected);
+}
+
+#[wasm_bindgen_test]
+fn test_total() {
+    let set = Multiset::<u8, 100>::repeat(200);
+    assert_eq!(set.total(), 20_000);
+    assert_eq!(Multiset::<u32, 3>::empty().total(), 0);
+}
+
+#[wasm_bindgen_test]
+fn test_entropy() {
+    let set = Multiset::<u32, 4>::from([1, 1, 1, 1]);
+    assert!((set.shannon_entropy() - 4f64.ln()).abs() < 1e-12);
+    assert!((set.collision_entropy() - 2.0).abs() < 1e-12);
+    assert_eq!(Multiset::<u32, 4>::empty().shannon_entropy(), 0.0);
+}